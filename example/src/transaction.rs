@@ -3,6 +3,7 @@ pub mod model;
 use crate::model::{init_db, BizActivity};
 use rbatis::executor::{RBatisConnExecutor, RBatisTxExecutor};
 use rbatis::rbdc::datetime::DateTime;
+use rbatis::Rbatis;
 
 rbatis::impl_insert!(BizActivity {});
 rbatis::impl_delete!(BizActivity {});
@@ -15,6 +16,7 @@ pub async fn main() {
     tx_run(rb.acquire_begin().await.unwrap(), "2", true).await;
     tx_conn(rb.acquire().await.unwrap()).await;
     tx_tx(rb.acquire_begin().await.unwrap()).await;
+    tx_closure(&rb).await;
     //wait log flush
     log::logger().flush();
 }
@@ -57,3 +59,16 @@ async fn tx_tx(mut tx: RBatisTxExecutor) {
     let tx = tx.begin().await.unwrap();
     tx_run(tx, "4", true).await;
 }
+
+async fn tx_closure(rb: &Rbatis) {
+    //commit if return Ok, rollback if return Err
+    let r = rb
+        .transaction(|tx| {
+            Box::pin(async move {
+                let _ = BizActivity::delete_in_column(tx, "id", &["5"]).await?;
+                Ok(())
+            })
+        })
+        .await;
+    println!("tx_closure: {:?}", r);
+}
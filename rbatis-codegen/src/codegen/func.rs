@@ -4,19 +4,25 @@ use quote::ToTokens;
 use syn;
 use syn::{BinOp, Expr, Lit, Member};
 
+use crate::error::Error;
+
 fn token_steam_string(arg: proc_macro2::TokenStream) -> String {
     arg.to_token_stream().to_string().trim().to_string()
 }
 
-fn convert_to_arg_access(context: &str, arg: Expr, ignore: &[String]) -> Expr {
+fn parse_code(code: &str) -> Result<Expr, Error> {
+    syn::parse_str::<Expr>(code).map_err(|e| Error::from(format!("{} (code: {})", e, code)))
+}
+
+fn convert_to_arg_access(arg: Expr, ignore: &[String]) -> Result<Expr, Error> {
     match arg {
         Expr::Path(b) => {
             let token = b.to_token_stream().to_string();
             if token == "null" {
-                return syn::parse_str::<Expr>("rbs::Value::Null").expect("codegen_func fail");
+                return parse_code("rbs::Value::Null");
             }
             if token == "sql" {
-                return Expr::Path(b);
+                return Ok(Expr::Path(b));
             }
             let param = token_steam_string(b.to_token_stream());
             let mut fetch_from_arg = true;
@@ -26,287 +32,261 @@ fn convert_to_arg_access(context: &str, arg: Expr, ignore: &[String]) -> Expr {
                 }
             }
             if fetch_from_arg {
-                return syn::parse_str::<Expr>(&format!("&arg[\"{}\"]", param))
-                    .expect("codegen_func fail");
+                parse_code(&format!("&arg[\"{}\"]", param))
             } else {
-                return syn::parse_str::<Expr>(&format!("{}", param)).expect("codegen_func fail");
+                parse_code(&param)
             }
         }
         Expr::MethodCall(mut b) => {
             //receiver is named need to convert to arg["xxx"]
-            b.receiver = Box::new(convert_to_arg_access(context, *b.receiver.clone(), ignore));
-            return Expr::MethodCall(b);
+            b.receiver = Box::new(convert_to_arg_access(*b.receiver.clone(), ignore)?);
+            Ok(Expr::MethodCall(b))
         }
         Expr::Binary(mut b) => {
-            b.left = Box::new(convert_to_arg_access(context, *b.left.clone(), ignore));
-            b.right = Box::new(convert_to_arg_access(context, *b.right.clone(), ignore));
+            b.left = Box::new(convert_to_arg_access(*b.left.clone(), ignore)?);
+            b.right = Box::new(convert_to_arg_access(*b.right.clone(), ignore)?);
             match b.op {
                 BinOp::Add(_) => {
                     let left_token = b.left.to_token_stream().to_string();
                     if left_token.trim().ends_with("\"") && left_token.trim().starts_with("\"") {
-                        return syn::parse_str::<Expr>(&format!(
+                        return parse_code(&format!(
                             "(String::from({})).op_add({})",
                             b.left.to_token_stream(),
                             b.right.to_token_stream()
-                        ))
-                        .expect("codegen_func fail");
+                        ));
                     } else {
-                        return syn::parse_str::<Expr>(&format!(
+                        return parse_code(&format!(
                             "({}).op_add(&{})",
                             b.left.to_token_stream(),
                             b.right.to_token_stream()
-                        ))
-                        .expect("codegen_func fail");
+                        ));
                     }
                 }
                 BinOp::And(_) => {
-                    b.left = Box::new(
-                        syn::parse_str::<Expr>(&format!(
-                            "bool::op_from({})",
-                            b.left.to_token_stream().to_string().trim()
-                        ))
-                        .expect("codegen_func fail"),
-                    );
-                    b.right = Box::new(
-                        syn::parse_str::<Expr>(&format!(
-                            "bool::op_from({})",
-                            b.right.to_token_stream().to_string().trim()
-                        ))
-                        .expect("codegen_func fail"),
-                    );
+                    b.left = Box::new(parse_code(&format!(
+                        "bool::op_from({})",
+                        b.left.to_token_stream().to_string().trim()
+                    ))?);
+                    b.right = Box::new(parse_code(&format!(
+                        "bool::op_from({})",
+                        b.right.to_token_stream().to_string().trim()
+                    ))?);
                 }
                 BinOp::Or(_) => {
-                    b.left = Box::new(
-                        syn::parse_str::<Expr>(&format!(
-                            "bool::op_from({})",
-                            b.left.to_token_stream().to_string().trim()
-                        ))
-                        .expect("codegen_func fail"),
-                    );
-                    b.right = Box::new(
-                        syn::parse_str::<Expr>(&format!(
-                            "bool::op_from({})",
-                            b.right.to_token_stream().to_string().trim()
-                        ))
-                        .expect("codegen_func fail"),
-                    );
+                    b.left = Box::new(parse_code(&format!(
+                        "bool::op_from({})",
+                        b.left.to_token_stream().to_string().trim()
+                    ))?);
+                    b.right = Box::new(parse_code(&format!(
+                        "bool::op_from({})",
+                        b.right.to_token_stream().to_string().trim()
+                    ))?);
                 }
 
                 // The `-` operator (subtraction)
                 BinOp::Sub(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_sub(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `*` operator (multiplication)
                 BinOp::Mul(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_mul(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `/` operator (division)
                 BinOp::Div(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_div(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `%` operator (modulus)
                 BinOp::Rem(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_rem(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `&` operator (bitwise and)
                 BinOp::BitAnd(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_bitand(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `|` operator (bitwise or)
                 BinOp::BitOr(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_bitor(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `==` operator (equality)
                 BinOp::Eq(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_eq(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `<` operator (less than)
                 BinOp::Lt(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_lt(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `<=` operator (less than or equal to)
                 BinOp::Le(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_le(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `!=` operator (not equal to)
                 BinOp::Ne(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_ne(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `>=` operator (greater than or equal to)
                 BinOp::Ge(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_ge(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `>` operator (greater than)
                 BinOp::Gt(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_gt(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `^` operator (bitwise xor)
                 BinOp::BitXor(_) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}).op_bitxor(&{})",
                         b.left.to_token_stream(),
                         b.right.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 // The `<<` operator (shift left)
                 BinOp::Shl(_) => {
-                    panic!("unsupported token << ")
+                    return Err(Error::from("unsupported operator `<<`"));
                 }
                 // The `>>` operator (shift right)
                 BinOp::Shr(_) => {
-                    panic!("unsupported token >> ")
+                    return Err(Error::from("unsupported operator `>>`"));
                 }
                 // The `+=` operator
                 BinOp::AddEq(_) => {
-                    panic!("unsupported token += ")
+                    return Err(Error::from("unsupported operator `+=`"));
                 }
                 // The `-=` operator
                 BinOp::SubEq(_) => {
-                    panic!("unsupported token -= ")
+                    return Err(Error::from("unsupported operator `-=`"));
                 }
                 // The `*=` operator
                 BinOp::MulEq(_) => {
-                    panic!("unsupported token *= ")
+                    return Err(Error::from("unsupported operator `*=`"));
                 }
                 // The `/=` operator
                 BinOp::DivEq(_) => {
-                    panic!("unsupported token /= ")
+                    return Err(Error::from("unsupported operator `/=`"));
                 }
                 // The `%=` operator
                 BinOp::RemEq(_) => {
-                    panic!("unsupported token %= ")
+                    return Err(Error::from("unsupported operator `%=`"));
                 }
                 // The `^=` operator
                 BinOp::BitXorEq(_) => {
-                    panic!("unsupported token ^= ")
+                    return Err(Error::from("unsupported operator `^=`"));
                 }
                 // The `&=` operator
                 BinOp::BitAndEq(_) => {
-                    panic!("unsupported token &= ")
+                    return Err(Error::from("unsupported operator `&=`"));
                 }
                 // The `|=` operator
                 BinOp::BitOrEq(_) => {
-                    panic!("unsupported token |= ")
+                    return Err(Error::from("unsupported operator `|=`"));
                 }
                 // The `<<=` operator
                 BinOp::ShlEq(_) => {
-                    panic!("unsupported token <<= ")
+                    return Err(Error::from("unsupported operator `<<=`"));
                 }
                 // The `>>=` operator
                 BinOp::ShrEq(_) => {
-                    panic!("unsupported token >>= ")
+                    return Err(Error::from("unsupported operator `>>=`"));
                 }
             }
-            return Expr::Binary(b);
+            Ok(Expr::Binary(b))
         }
         Expr::Unary(mut b) => {
-            b.expr = Box::new(convert_to_arg_access(context, *b.expr, ignore));
+            b.expr = Box::new(convert_to_arg_access(*b.expr, ignore)?);
             if b.op.to_token_stream().to_string().trim() == "-" {
-                return syn::parse_str::<Expr>(&format!(
+                return parse_code(&format!(
                     " (0.op_sub(&{}))",
                     b.expr.to_token_stream().to_string().trim()
-                ))
-                .expect("codegen_func fail");
+                ));
             }
-            return Expr::Unary(b);
+            Ok(Expr::Unary(b))
         }
         Expr::Paren(mut b) => {
-            b.expr = Box::new(convert_to_arg_access(context, *b.expr, ignore));
-            return Expr::Paren(b);
+            b.expr = Box::new(convert_to_arg_access(*b.expr, ignore)?);
+            Ok(Expr::Paren(b))
         }
         Expr::Field(mut b) => {
-            b.base = Box::new(convert_to_arg_access(context, *b.base.clone(), ignore));
+            b.base = Box::new(convert_to_arg_access(*b.base.clone(), ignore)?);
             match b.member {
                 Member::Named(named) => {
-                    return syn::parse_str::<Expr>(&format!(
+                    return parse_code(&format!(
                         "({}[\"{}\"])",
                         b.base.to_token_stream(),
                         named.to_token_stream()
-                    ))
-                    .expect("codegen_func fail");
+                    ));
                 }
                 Member::Unnamed(_) => {}
             }
-            return Expr::Field(b);
+            Ok(Expr::Field(b))
         }
         Expr::Reference(mut b) => {
-            b.expr = Box::new(convert_to_arg_access(context, *b.expr, ignore));
+            b.expr = Box::new(convert_to_arg_access(*b.expr, ignore)?);
             let result = Expr::Reference(b);
-            return result;
+            Ok(result)
         }
         Expr::Index(mut b) => {
-            b.expr = Box::new(convert_to_arg_access(context, *b.expr, ignore));
-            return syn::parse_str::<Expr>(&format!(
+            *b.expr = convert_to_arg_access(*b.expr, ignore)?;
+            parse_code(&format!(
                 "{}[{}]",
                 b.expr.to_token_stream(),
                 b.index.to_token_stream()
             ))
-            .expect("codegen_func fail");
         }
+        Expr::Assign(_) => Err(Error::from("unsupported operator `=`")),
+        Expr::AssignOp(b) => Err(Error::from(format!(
+            "unsupported operator `{}`",
+            b.op.to_token_stream()
+        ))),
         Expr::Let(mut let_expr) => {
-            let_expr.expr = Box::new(convert_to_arg_access(context, *let_expr.expr, ignore));
-            return Expr::Let(let_expr);
+            let_expr.expr = Box::new(convert_to_arg_access(*let_expr.expr, ignore)?);
+            Ok(Expr::Let(let_expr))
         }
         Expr::Lit(b) => {
             match b.lit.clone() {
@@ -316,86 +296,78 @@ fn convert_to_arg_access(context: &str, arg: Expr, ignore: &[String]) -> Expr {
                 Lit::Char(_) => {}
                 Lit::Int(i) => {
                     //cast int to i64
-                    return syn::parse_str::<Expr>(&format!("{}i64", i))
-                        .expect("codegen_func fail");
+                    return parse_code(&format!("{}i64", i));
                 }
                 Lit::Float(f) => {
                     //cast int to f64
-                    return syn::parse_str::<Expr>(&format!("{}f64", f))
-                        .expect("codegen_func fail");
+                    return parse_code(&format!("{}f64", f));
                 }
                 Lit::Bool(_) => {}
                 Lit::Verbatim(_) => {}
             }
-            return Expr::Lit(b);
-        }
-        _ => {
-            return arg;
+            Ok(Expr::Lit(b))
         }
+        _ => Ok(arg),
     }
 }
 
-/// gen method or body(if func_name_ident is empty)
-pub fn impl_fn(
-    context: &str,
-    func_name_ident: &str,
-    args: &str,
-    serialize_result: bool,
-    ignore: &[String],
-) -> proc_macro2::TokenStream {
-    let mut string_data = args.to_string();
-    string_data = string_data[1..string_data.len() - 1].to_string();
-    string_data = string_data.replace(".string()", ".to_string()");
+/// convert the expression of template to rust code, the `'` string is `"` string
+pub(crate) fn convert_expr(expr: &str) -> String {
+    let expr = expr.replace(".string()", ".to_string()");
     //convert string define
     let mut last_char = '_';
-    let mut string_data_new = String::new();
-    for x in string_data.chars() {
+    let mut code = String::with_capacity(expr.len());
+    for x in expr.chars() {
         if x == '\'' && last_char != '\\' {
-            string_data_new.push('\"');
+            code.push('"');
         } else {
-            string_data_new.push(x);
+            code.push(x);
         }
         last_char = x;
     }
-    string_data = string_data_new;
-    let t = syn::parse_str::<Expr>(&string_data);
-    if t.is_err() {
-        panic!(
-            "[rexpr]syn::parse_str: {} fail for: {}",
-            string_data,
-            t.err().expect("codegen_func fail").to_string()
-        )
-    }
-    let mut t = t.expect("codegen_func fail");
-    t = convert_to_arg_access(context, t, ignore);
-    string_data = t.to_token_stream().to_string();
-    string_data = string_data.replace(" . ", ".");
-    let t = syn::parse_str::<Expr>(&string_data);
-    if t.is_err() {
-        panic!(
-            "[rexpr]syn::parse_str: {} fail for: {}",
-            string_data,
-            t.err().expect("codegen_func fail").to_string()
-        )
-    }
-    let t = t.expect("codegen_func fail");
+    code
+}
+
+/// convert the expression(`"expr"`) of template to the rust expression
+pub(crate) fn convert_fn_expr(args: &str, ignore: &[String]) -> Result<Expr, Error> {
+    let string_data = convert_expr(&args[1..args.len() - 1]);
+    let t = syn::parse_str::<Expr>(&string_data).map_err(|e| Error::from(e.to_string()))?;
+    let t = convert_to_arg_access(t, ignore)?;
+    parse_code(&t.to_token_stream().to_string().replace(" . ", "."))
+}
+
+/// gen method or body(if func_name_ident is empty)
+pub fn impl_fn(
+    _context: &str,
+    func_name_ident: &str,
+    args: &str,
+    serialize_result: bool,
+    ignore: &[String],
+) -> Result<proc_macro2::TokenStream, Error> {
+    let t = convert_fn_expr(args, ignore).map_err(|e| {
+        Error::from(format!(
+            "[rbatis] parse the expression `{}` fail: {}",
+            &args[1..args.len() - 1],
+            e
+        ))
+    })?;
     let mut result_impl = quote! { {#t} };
     if serialize_result {
         result_impl = quote! {rbs::to_value({#t}).unwrap_or_default()};
     }
     if func_name_ident.is_empty() || func_name_ident.eq("\"\"") {
-        return quote! {
+        Ok(quote! {
                #result_impl
         }
-        .to_token_stream();
+        .to_token_stream())
     } else {
         let func_name_ident = Ident::new(&func_name_ident.to_string(), Span::call_site());
-        return quote! {
+        Ok(quote! {
             pub fn #func_name_ident(arg:&rbs::Value) -> rbs::Value {
                use rbatis_codegen::ops::*;
                #result_impl
             }
         }
-        .to_token_stream();
+        .to_token_stream())
     }
 }
@@ -0,0 +1,516 @@
+//! the runtime dynamic sql, it's evaluate the html(`Element`)/py_sql(`NodeType`) tree
+//! against the `rbs::Value` arg without the proc macro.
+//! the sql and the expression have the same semantics with the code generated by `parser_html`(use the `ops` traits).
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use quote::ToTokens;
+use rbs::Value;
+use syn::{BinOp, Expr, Lit, Member, UnOp};
+
+use crate::codegen::func::convert_expr;
+use crate::codegen::loader_html::Element;
+use crate::codegen::parser_html::{load_mapper_map, remove_extra};
+use crate::codegen::parser_pysql::ParsePySql;
+use crate::codegen::string_util::find_convert_string;
+use crate::codegen::syntax_tree_pysql::{to_html, NodeType};
+use crate::error::Error;
+use crate::identifier::quote_sql;
+use crate::ops::{
+    Add, AsProxy, BitAnd, BitOr, BitXor, Div, Mul, Not, PartialEq, PartialOrd, Rem, Sub,
+};
+use crate::sql_policy::to_sql;
+
+/// the mapper loaded at runtime, for example:
+/// ```rust
+/// use rbatis_codegen::codegen::interpreter::Mapper;
+/// let mapper = Mapper::from_html(r#"<mapper><select id="select_by_name">
+///   `select * from biz_activity`
+///   <where><if test="name != ''">` and name = #{name}`</if></where>
+/// </select></mapper>"#).unwrap();
+/// let (sql, args) = mapper.render("select_by_name", &rbs::to_value!{"name":"a",}).unwrap();
+/// assert_eq!(sql, "select * from biz_activity where name = ?");
+/// assert_eq!(args, vec![rbs::to_value!("a")]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Mapper {
+    elements: BTreeMap<String, Element>,
+}
+
+impl Mapper {
+    pub fn from_html(html: &str) -> Result<Self, Error> {
+        Ok(Self {
+            elements: load_mapper_map(html)?,
+        })
+    }
+
+    /// reload the mapper html, the old mapper is not changed if the html is invalid
+    pub fn reload(&mut self, html: &str) -> Result<(), Error> {
+        self.elements = load_mapper_map(html)?;
+        Ok(())
+    }
+
+    /// the ids of `<select>`,`<insert>`,`<update>`,`<delete>`...
+    pub fn ids(&self) -> Vec<&str> {
+        self.elements.keys().map(|v| v.as_str()).collect()
+    }
+
+    /// render the element of id to (sql, args)
+    pub fn render(&self, id: &str, arg: &Value) -> Result<(String, Vec<Value>), Error> {
+        let element = self
+            .elements
+            .get(id)
+            .ok_or_else(|| Error::from(format!("[rbatis] html not find id: {}", id)))?;
+        eval_elements(std::slice::from_ref(element), arg)
+    }
+}
+
+/// render the py_sql to (sql, args)
+pub fn eval_py_sql(py_sql: &str, arg: &Value) -> Result<(String, Vec<Value>), Error> {
+    let nodes = NodeType::parse_pysql(py_sql).map_err(|e| Error::from(e.to_string()))?;
+    eval_nodes(&nodes, arg)
+}
+
+/// render the py_sql nodes to (sql, args)
+pub fn eval_nodes(nodes: &[NodeType], arg: &Value) -> Result<(String, Vec<Value>), Error> {
+    let id = "py_sql";
+    let html = to_html(&nodes.to_vec(), true, id);
+    Mapper::from_html(&html)?.render(id, arg)
+}
+
+/// render the html elements to (sql, args)
+pub fn eval_elements(elements: &[Element], arg: &Value) -> Result<(String, Vec<Value>), Error> {
+    let mut ctx = Context {
+        arg,
+        vars: vec![],
+        sql: String::new(),
+        args: vec![],
+    };
+    ctx.eval_elements(elements)?;
+    Ok((ctx.sql, ctx.args))
+}
+
+/// evaluate the expression, for example `name != '' && age > 1`
+pub fn eval_expr(expr: &str, arg: &Value) -> Result<Value, Error> {
+    let ctx = Context {
+        arg,
+        vars: vec![],
+        sql: String::new(),
+        args: vec![],
+    };
+    ctx.eval_str(expr)
+}
+
+enum Flow {
+    Next,
+    Continue,
+}
+
+struct Context<'a> {
+    arg: &'a Value,
+    /// the `<bind>` and `<foreach>` vars
+    vars: Vec<(String, Value)>,
+    sql: String,
+    args: Vec<Value>,
+}
+
+impl<'a> Context<'a> {
+    /// the vars of the block is dropped at the end of block
+    fn eval_elements(&mut self, elements: &[Element]) -> Result<Flow, Error> {
+        let vars_len = self.vars.len();
+        let mut flow = Flow::Next;
+        for x in elements {
+            flow = self.eval_element(x)?;
+            if let Flow::Continue = flow {
+                break;
+            }
+        }
+        self.vars.truncate(vars_len);
+        Ok(flow)
+    }
+
+    fn eval_element(&mut self, x: &Element) -> Result<Flow, Error> {
+        match x.tag.as_str() {
+            "" => self.eval_string(&x.data)?,
+            "continue" => return Ok(Flow::Continue),
+            "mapper" | "sql" | "include" | "select" | "update" | "insert" | "delete" => {
+                return self.eval_elements(&x.childs);
+            }
+            "if" if self.eval_str(attr(x, "test")?)?.bool() => {
+                return self.eval_elements(&x.childs);
+            }
+            "if" => {}
+            "trim" => {
+                return self.eval_trim(
+                    x,
+                    x.attrs
+                        .get("prefix")
+                        .map(|v| v.as_str())
+                        .unwrap_or_default(),
+                    x.attrs
+                        .get("suffix")
+                        .map(|v| v.as_str())
+                        .unwrap_or_default(),
+                    x.attrs
+                        .get("prefixOverrides")
+                        .map(|v| v.as_str())
+                        .unwrap_or_default(),
+                    x.attrs
+                        .get("suffixOverrides")
+                        .map(|v| v.as_str())
+                        .unwrap_or_default(),
+                );
+            }
+            "where" => {
+                if let Flow::Continue =
+                    self.eval_trim(x, " where ", " ", " |and |or ", " | and| or")?
+                {
+                    return Ok(Flow::Continue);
+                }
+                //check ends with where
+                self.sql = self.sql.trim_end().trim_end_matches(" where").to_string();
+            }
+            "set" => return self.eval_trim(x, " set ", " ", " |,", " |,"),
+            "bind" => {
+                let name = attr(x, "name")?.to_string();
+                let value = self.eval_str(attr(x, "value")?)?;
+                self.vars.push((name, value));
+            }
+            "choose" => {
+                let outer = std::mem::take(&mut self.sql);
+                for child in &x.childs {
+                    match child.tag.as_str() {
+                        "when" => {
+                            if self.eval_str(attr(child, "test")?)?.bool() {
+                                self.eval_elements(&child.childs)?;
+                                break;
+                            }
+                        }
+                        "otherwise" => {
+                            self.eval_elements(&child.childs)?;
+                        }
+                        _ => {
+                            return Err(Error::from(
+                                "choose node's childs must be when node and otherwise node!",
+                            ));
+                        }
+                    }
+                }
+                let inner = std::mem::replace(&mut self.sql, outer);
+                self.sql.push_str(&inner);
+            }
+            "foreach" => self.eval_foreach(x)?,
+            _ => {}
+        }
+        Ok(Flow::Next)
+    }
+
+    fn eval_string(&mut self, data: &str) -> Result<(), Error> {
+        let mut string_data = remove_extra(data);
+        let mut replaces = vec![];
+        for (k, v) in find_convert_string(&string_data) {
+            let raw = k.trim().strip_suffix(".raw()");
+            let quote = k.trim().strip_suffix(".quote()");
+            let value = self.eval_str(raw.or(quote).unwrap_or(&k))?;
+            if v.starts_with('#') {
+                string_data = string_data.replacen(&v, "?", 1);
+                self.args.push(value);
+            } else if raw.is_some() {
+                replaces.push((v, value.as_sql()));
+            } else if quote.is_some() {
+                let driver_type = self.eval_str("quote_driver")?;
+                let sql = quote_sql(&value, driver_type.as_str().unwrap_or_default())?;
+                replaces.push((v, sql));
+            } else {
+                replaces.push((v, to_sql(&value)?));
+            }
+        }
+        for (v, sql) in replaces {
+            string_data = string_data.replacen(&v, &sql, 1);
+        }
+        self.sql.push_str(&string_data);
+        Ok(())
+    }
+
+    fn eval_trim(
+        &mut self,
+        x: &Element,
+        prefix: &str,
+        suffix: &str,
+        prefix_overrides: &str,
+        suffix_overrides: &str,
+    ) -> Result<Flow, Error> {
+        self.sql.push_str(prefix);
+        let outer = std::mem::take(&mut self.sql);
+        let flow = self.eval_elements(&x.childs);
+        let mut inner = std::mem::replace(&mut self.sql, outer);
+        // same as the compiled code, `continue` is not push the trim body and suffix
+        if let Flow::Continue = flow? {
+            return Ok(Flow::Continue);
+        }
+        let mut trimmed = inner.as_str();
+        for x in prefix_overrides.split('|') {
+            trimmed = trimmed.trim_start_matches(x);
+        }
+        for x in suffix_overrides.split('|') {
+            trimmed = trimmed.trim_end_matches(x);
+        }
+        inner = trimmed.to_string();
+        self.sql.push_str(&inner);
+        self.sql.push_str(suffix);
+        Ok(Flow::Next)
+    }
+
+    fn eval_foreach(&mut self, x: &Element) -> Result<(), Error> {
+        let get = |k: &str, default: &str| {
+            x.attrs
+                .get(k)
+                .filter(|v| !v.is_empty())
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| default.to_string())
+        };
+        let item = get("item", "item");
+        let index = get("index", "index");
+        let open = get("open", "");
+        let close = get("close", "");
+        let separator = get("separator", "");
+        let collection = self.eval_str(&get("collection", ""))?;
+        self.sql.push_str(&open);
+        for (k, v) in &collection {
+            self.vars.push((index.clone(), k.into_owned()));
+            self.vars.push((item.clone(), v.clone()));
+            let flow = self.eval_elements(&x.childs);
+            self.vars.truncate(self.vars.len() - 2);
+            if let Flow::Continue = flow? {
+                continue;
+            }
+            self.sql.push_str(&separator);
+        }
+        if !separator.is_empty() {
+            self.sql = self.sql.trim_end_matches(separator.as_str()).to_string();
+        }
+        self.sql.push_str(&close);
+        Ok(())
+    }
+
+    fn eval_str(&self, expr: &str) -> Result<Value, Error> {
+        let code = convert_expr(expr);
+        let expr = syn::parse_str::<Expr>(&code)
+            .map_err(|e| Error::from(format!("[rbatis] parse expr: {} fail for: {}", code, e)))?;
+        self.eval(&expr)
+    }
+
+    fn var(&self, name: &str) -> Value {
+        match name {
+            "null" => Value::Null,
+            "sql" => Value::String(self.sql.clone()),
+            _ => match self.vars.iter().rev().find(|(k, _)| k == name) {
+                Some((_, v)) => v.clone(),
+                None => self.arg[name].clone(),
+            },
+        }
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<Value, Error> {
+        let v = match expr {
+            Expr::Path(p) => self.var(&p.to_token_stream().to_string()),
+            Expr::Lit(l) => match &l.lit {
+                Lit::Str(s) => Value::String(s.value()),
+                Lit::Char(c) => Value::String(c.value().to_string()),
+                Lit::Bool(b) => Value::Bool(b.value),
+                Lit::Int(i) => {
+                    Value::I64(i.base10_parse().map_err(|e| Error::from(e.to_string()))?)
+                }
+                Lit::Float(f) => {
+                    Value::F64(f.base10_parse().map_err(|e| Error::from(e.to_string()))?)
+                }
+                _ => return Err(unsupported(expr)),
+            },
+            Expr::Paren(p) => self.eval(&p.expr)?,
+            Expr::Group(g) => self.eval(&g.expr)?,
+            Expr::Reference(r) => self.eval(&r.expr)?,
+            Expr::Unary(u) => {
+                let v = self.eval(&u.expr)?;
+                match u.op {
+                    UnOp::Not(_) => v.op_not(),
+                    UnOp::Neg(_) => Value::I64(0).op_sub(&v),
+                    UnOp::Deref(_) => v,
+                }
+            }
+            Expr::Binary(b) => {
+                let left = self.eval(&b.left)?;
+                match b.op {
+                    BinOp::And(_) => {
+                        return Ok(Value::Bool(left.bool() && self.eval(&b.right)?.bool()));
+                    }
+                    BinOp::Or(_) => {
+                        return Ok(Value::Bool(left.bool() || self.eval(&b.right)?.bool()));
+                    }
+                    _ => {}
+                }
+                let right = self.eval(&b.right)?;
+                match b.op {
+                    BinOp::Add(_) => left.op_add(&right),
+                    BinOp::Sub(_) => left.op_sub(&right),
+                    BinOp::Mul(_) => left.op_mul(&right),
+                    BinOp::Div(_) => left.op_div(&right),
+                    BinOp::Rem(_) => left.op_rem(&right),
+                    BinOp::BitAnd(_) => Value::Bool((&left).op_bitand(&right)),
+                    BinOp::BitOr(_) => Value::Bool((&left).op_bitor(&right)),
+                    BinOp::BitXor(_) => left.op_bitxor(&right),
+                    BinOp::Eq(_) => match cmp_number(&left, &right) {
+                        Some(o) => Value::Bool(o == Ordering::Equal),
+                        None => Value::Bool(left.op_eq(&right)),
+                    },
+                    BinOp::Ne(_) => match cmp_number(&left, &right) {
+                        Some(o) => Value::Bool(o != Ordering::Equal),
+                        None => Value::Bool(left.op_ne(&right)),
+                    },
+                    BinOp::Lt(_) => match cmp_number(&left, &right) {
+                        Some(o) => Value::Bool(o == Ordering::Less),
+                        None => Value::Bool(left.op_lt(&right)),
+                    },
+                    BinOp::Le(_) => match cmp_number(&left, &right) {
+                        Some(o) => Value::Bool(o != Ordering::Greater),
+                        None => Value::Bool(left.op_le(&right)),
+                    },
+                    BinOp::Gt(_) => match cmp_number(&left, &right) {
+                        Some(o) => Value::Bool(o == Ordering::Greater),
+                        None => Value::Bool(left.op_gt(&right)),
+                    },
+                    BinOp::Ge(_) => match cmp_number(&left, &right) {
+                        Some(o) => Value::Bool(o != Ordering::Less),
+                        None => Value::Bool(left.op_ge(&right)),
+                    },
+                    _ => {
+                        return Err(Error::from(format!(
+                            "unsupported token {}",
+                            b.op.to_token_stream()
+                        )))
+                    }
+                }
+            }
+            Expr::Field(f) => {
+                let base = self.eval(&f.base)?;
+                match &f.member {
+                    Member::Named(name) => base[name.to_string().as_str()].clone(),
+                    Member::Unnamed(i) => index(&base, &Value::U32(i.index))?,
+                }
+            }
+            Expr::Index(i) => index(&self.eval(&i.expr)?, &self.eval(&i.index)?)?,
+            Expr::MethodCall(m) => {
+                let receiver = self.eval(&m.receiver)?;
+                let mut args = Vec::with_capacity(m.args.len());
+                for x in &m.args {
+                    args.push(self.eval(x)?);
+                }
+                call(&receiver, &m.method.to_string(), &args)?
+            }
+            _ => return Err(unsupported(expr)),
+        };
+        Ok(v)
+    }
+}
+
+/// compare the numbers like the literal of compile time(`arg == 1` is `eq_i64`),
+/// so the `I32(1)` is equal to `I64(1)`. None if any of them is not number
+fn cmp_number(left: &Value, right: &Value) -> Option<Ordering> {
+    let is_float = |v: &Value| matches!(v, Value::F32(_) | Value::F64(_));
+    let is_int = |v: &Value| {
+        matches!(
+            v,
+            Value::I32(_) | Value::I64(_) | Value::U32(_) | Value::U64(_)
+        )
+    };
+    let is_number = |v: &Value| is_float(v) || is_int(v);
+    if !is_number(left) || !is_number(right) {
+        return None;
+    }
+    if is_float(left) || is_float(right) {
+        left.f64().partial_cmp(&right.f64())
+    } else {
+        let int = |v: &Value| match v {
+            Value::U64(v) => *v as i128,
+            v => v.i64() as i128,
+        };
+        Some(int(left).cmp(&int(right)))
+    }
+}
+
+fn attr<'e>(x: &'e Element, name: &str) -> Result<&'e str, Error> {
+    x.attrs.get(name).map(|v| v.as_str()).ok_or_else(|| {
+        Error::from(format!(
+            "[rbatis] <{}> element must be have {} field!",
+            x.tag, name
+        ))
+    })
+}
+
+fn unsupported(expr: &Expr) -> Error {
+    Error::from(format!(
+        "[rbatis] unsupported expr: {}",
+        expr.to_token_stream()
+    ))
+}
+
+fn index(base: &Value, index: &Value) -> Result<Value, Error> {
+    match index {
+        Value::String(k) => Ok(base[k.as_str()].clone()),
+        _ => {
+            let i = index
+                .as_u64()
+                .ok_or_else(|| Error::from(format!("[rbatis] unsupported index: {}", index)))?;
+            Ok(base
+                .as_array()
+                .and_then(|arr| arr.get(i as usize))
+                .cloned()
+                .unwrap_or_default())
+        }
+    }
+}
+
+/// the method of value
+fn call(receiver: &Value, method: &str, args: &[Value]) -> Result<Value, Error> {
+    let arg = |i: usize| {
+        args.get(i)
+            .ok_or_else(|| Error::from(format!("[rbatis] method {} need {} args", method, i + 1)))
+    };
+    let v = match method {
+        "contains" => {
+            let arg = arg(0)?;
+            Value::Bool(match receiver {
+                Value::Array(arr) => arr.iter().any(|v| v.op_eq(arg)),
+                Value::Map(m) => m.into_iter().any(|(k, _)| k.op_eq(arg)),
+                _ => receiver.str().contains(arg.str()),
+            })
+        }
+        "starts_with" => Value::Bool(receiver.str().starts_with(arg(0)?.str())),
+        "ends_with" => Value::Bool(receiver.str().ends_with(arg(0)?.str())),
+        "is_empty" => Value::Bool(receiver.is_empty()),
+        "is_null" => Value::Bool(receiver.is_null()),
+        "len" => Value::I64(match receiver {
+            Value::String(s) => s.len(),
+            Value::Binary(b) => b.len(),
+            Value::Array(arr) => arr.len(),
+            Value::Map(m) => m.len(),
+            _ => 0,
+        } as i64),
+        "trim" => Value::String(receiver.str().trim().to_string()),
+        "to_lowercase" => Value::String(receiver.str().to_lowercase()),
+        "to_uppercase" => Value::String(receiver.str().to_uppercase()),
+        "to_string" => Value::String(receiver.to_string()),
+        "as_sql" => Value::String(receiver.as_sql()),
+        "str" => Value::String(receiver.str().to_string()),
+        "i32" | "i64" => Value::I64(receiver.i64()),
+        "u32" | "u64" => Value::U64(receiver.u64()),
+        "f64" => Value::F64(receiver.f64()),
+        "bool" => Value::Bool(receiver.bool()),
+        _ => {
+            return Err(Error::from(format!(
+                "[rbatis] unsupported method: {}",
+                method
+            )))
+        }
+    };
+    Ok(v)
+}
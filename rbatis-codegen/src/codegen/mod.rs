@@ -8,24 +8,28 @@ use syn::{parse_macro_input, AttributeArgs, ItemFn};
 use proc_macro::TokenStream;
 
 pub mod func;
+pub mod interpreter;
 pub mod loader_html;
 pub mod parser_html;
 pub mod parser_pysql;
+pub mod schema;
 pub mod string_util;
 pub mod syntax_tree_pysql;
 
 pub fn expr(args: TokenStream, func: TokenStream) -> TokenStream {
     //let args = parse_macro_input!(args as AttributeArgs);
     let target_fn: ItemFn = syn::parse(func).unwrap();
-    let stream = func::impl_fn(
+    let stream = match func::impl_fn(
         "",
         &target_fn.sig.ident.to_string(),
         &args.to_string(),
         true,
         &[],
-    )
-    .into();
-    stream
+    ) {
+        Ok(v) => v,
+        Err(e) => syn::Error::new(proc_macro2::Span::call_site(), e).to_compile_error(),
+    };
+    stream.into()
 }
 
 pub fn rb_html(args: TokenStream, func: TokenStream) -> TokenStream {
@@ -4,9 +4,10 @@ use std::io::Read;
 
 use proc_macro2::{Ident, Span};
 use quote::{quote, ToTokens};
-use syn::{AttributeArgs, Expr, ItemFn};
+use syn::{AttributeArgs, Expr, ItemFn, ReturnType};
 use url::Url;
 
+use crate::codegen::func::convert_fn_expr;
 use crate::codegen::loader_html::{load_html, Element};
 use crate::codegen::proc_macro::TokenStream;
 use crate::codegen::string_util::find_convert_string;
@@ -17,7 +18,7 @@ use crate::error::Error;
 pub fn load_mapper_map(html: &str) -> Result<BTreeMap<String, Element>, Error> {
     let datas = load_mapper_vec(html)?;
     let mut sql_map = BTreeMap::new();
-    let datas = include_replace(datas, &mut sql_map);
+    let datas = include_replace(datas, &mut sql_map)?;
     let mut m = BTreeMap::new();
     for x in datas {
         if let Some(v) = x.attrs.get("id") {
@@ -43,27 +44,251 @@ pub fn load_mapper_vec(html: &str) -> Result<Vec<Element>, Error> {
     Ok(mappers)
 }
 
-/// parse html to function TokenStream
+/// parse html to function TokenStream, the template error is `compile_error!`
 pub fn parse_html(html: &str, fn_name: &str, ignore: &mut Vec<String>) -> proc_macro2::TokenStream {
+    match try_parse_html(html, fn_name, ignore) {
+        Ok(v) => v,
+        Err(e) => syn::Error::new(Span::call_site(), e).to_compile_error(),
+    }
+}
+
+/// parse html to function TokenStream
+pub(crate) fn try_parse_html(
+    html: &str,
+    fn_name: &str,
+    ignore: &mut Vec<String>,
+) -> Result<proc_macro2::TokenStream, Error> {
     let html = html
         .replace("\\\"", "\"")
         .replace("\\n", "\n")
         .trim_start_matches("\"")
         .trim_end_matches("\"")
         .to_string();
-    let datas = load_mapper_map(&html).expect(&format!("laod html={} fail", html));
+    check_html(&html)?;
+    let datas = load_mapper_map(&html)?;
     match datas.into_iter().next() {
-        None => {
-            panic!("html not find fn:{}", fn_name);
+        None => Err(Error::from(format!(
+            "[rbatis] html not find fn: {}\n  hint: the html must have <select>/<update>/<insert>/<delete> element with id",
+            fn_name
+        ))),
+        Some((_, v)) => parse_html_node(vec![v], ignore, fn_name),
+    }
+}
+
+/// the tags of html template
+const TAGS: &[&str] = &[
+    "mapper",
+    "sql",
+    "include",
+    "select",
+    "update",
+    "insert",
+    "delete",
+    "if",
+    "trim",
+    "where",
+    "set",
+    "bind",
+    "choose",
+    "when",
+    "otherwise",
+    "foreach",
+    "continue",
+];
+
+/// check the tags, attrs and expressions of html template,
+/// the error have the line/column of element, the element and the hint
+pub fn check_html(html: &str) -> Result<(), Error> {
+    let datas = load_html(html).map_err(|e| {
+        Error::from(format!(
+            "[rbatis] load html fail: {}\n  hint: the `<` of sql must be `&lt;`, and the element must be closed",
+            e
+        ))
+    })?;
+    let positions = tag_positions(html);
+    check_elements(&datas, "", &mut positions.iter())
+}
+
+fn check_elements(
+    elements: &[Element],
+    parent: &str,
+    positions: &mut std::slice::Iter<(usize, usize)>,
+) -> Result<(), Error> {
+    for x in elements {
+        if x.tag.is_empty() {
+            if parent == "choose" && !x.data.trim().is_empty() {
+                return Err(Error::from(format!(
+                    "[rbatis] <choose> can't have the sql `{}`\n  hint: the childs of <choose> must be <when> and <otherwise>",
+                    x.data.trim()
+                )));
+            }
+            continue;
+        }
+        let (line, column) = positions.next().copied().unwrap_or_default();
+        let fail = |msg: String, hint: &str| {
+            let mut node = format!("<{}", x.tag);
+            let mut attrs: Vec<_> = x.attrs.iter().collect();
+            attrs.sort();
+            for (k, v) in attrs {
+                node.push_str(&format!(" {}=\"{}\"", k, v));
+            }
+            node.push('>');
+            Error::from(format!(
+                "[rbatis] html line {}, column {}, `{}`: {}\n  hint: {}",
+                line, column, node, msg, hint
+            ))
+        };
+        let attr = |name: &str, hint: &str| {
+            x.attrs.get(name).filter(|v| !v.is_empty()).ok_or_else(|| {
+                fail(
+                    format!("<{}> element must have attr `{}`", x.tag, name),
+                    hint,
+                )
+            })
+        };
+        let expr = |name: &str, hint: &str| -> Result<(), Error> {
+            let v = attr(name, hint)?;
+            convert_fn_expr(&format!("\"{}\"", v), &[]).map_err(|e| {
+                fail(
+                    format!("parse the `{}` expression `{}` fail: {}", name, v, e),
+                    "the expression is rust expression, the string is 'value'",
+                )
+            })?;
+            Ok(())
+        };
+        let ident = |name: &str| -> Result<(), Error> {
+            if let Some(v) = x.attrs.get(name).filter(|v| !v.is_empty() && *v != "_") {
+                syn::parse_str::<Ident>(v).map_err(|_| {
+                    fail(
+                        format!("the `{}` must be identifier, but it's `{}`", name, v),
+                        "for example name=\"item\"",
+                    )
+                })?;
+            }
+            Ok(())
+        };
+        match x.tag.as_str() {
+            "sql" => {
+                attr("id", "for example <sql id=\"columns\">`id,name`</sql>")?;
+            }
+            "include" => {
+                attr("refid", "for example <include refid=\"columns\"></include>")?;
+            }
+            "if" => expr("test", "for example <if test=\"name != ''\">")?,
+            "when" | "otherwise" if parent != "choose" => {
+                return Err(fail(
+                    format!("<{}> must be the child of <choose>", x.tag),
+                    "for example <choose><when test=\"id != null\">`id = #{id}`</when><otherwise>`id = 0`</otherwise></choose>",
+                ));
+            }
+            "when" => expr("test", "for example <when test=\"name != ''\">")?,
+            "choose" => {
+                if let Some(child) = x
+                    .childs
+                    .iter()
+                    .find(|c| !c.tag.is_empty() && c.tag != "when" && c.tag != "otherwise")
+                {
+                    return Err(fail(
+                        format!("<choose> can't have the child <{}>", child.tag),
+                        "the childs of <choose> must be <when> and <otherwise>",
+                    ));
+                }
+            }
+            "bind" => {
+                attr(
+                    "name",
+                    "for example <bind name=\"pattern\" value=\"'%' + name + '%'\">",
+                )?;
+                ident("name")?;
+                expr(
+                    "value",
+                    "for example <bind name=\"pattern\" value=\"'%' + name + '%'\">",
+                )?;
+            }
+            "foreach" => {
+                expr(
+                    "collection",
+                    "for example <foreach collection=\"ids\" item=\"item\" index=\"index\" open=\"(\" close=\")\" separator=\",\">",
+                )?;
+                ident("item")?;
+                ident("index")?;
+            }
+            tag if !TAGS.contains(&tag) => {
+                return Err(fail(
+                    format!("unknown tag <{}>", tag),
+                    &format!("the tags are {}", TAGS.join(", ")),
+                ));
+            }
+            _ => {}
+        }
+        // the `#{}` and `${}` of sql
+        for child in x.childs.iter().filter(|c| c.tag.is_empty()) {
+            for (k, v) in find_convert_string(&child.data) {
+                let k = k.trim();
+                let k = k
+                    .strip_suffix(".raw()")
+                    .or_else(|| k.strip_suffix(".quote()"))
+                    .unwrap_or(k);
+                convert_fn_expr(&format!("\"{}\"", k), &[]).map_err(|e| {
+                    fail(
+                        format!("parse the expression `{}` fail: {}", v, e),
+                        "the expression is rust expression, for example #{name} or ${column.quote()}",
+                    )
+                })?;
+            }
+        }
+        check_elements(&x.childs, &x.tag, positions)?;
+    }
+    Ok(())
+}
+
+/// the (line, column) of start tags in order, it's same as the order of elements
+fn tag_positions(html: &str) -> Vec<(usize, usize)> {
+    let chars: Vec<char> = html.chars().collect();
+    let mut positions = vec![];
+    let (mut line, mut column) = (1, 1);
+    let mut i = 0;
+    // the end of the comment or tag
+    let mut end: Option<&str> = None;
+    let mut quote: Option<char> = None;
+    while i < chars.len() {
+        let c = chars[i];
+        match end {
+            Some("-->") if chars[i..].starts_with(&['-', '-', '>']) => end = None,
+            Some("-->") => {}
+            Some(_) => match quote {
+                Some(q) if q == c => quote = None,
+                Some(_) => {}
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None if c == '>' => end = None,
+                None => {}
+            },
+            None if c == '<' => match chars.get(i + 1) {
+                Some('!') if chars[i..].starts_with(&['<', '!', '-', '-']) => end = Some("-->"),
+                Some('!') | Some('/') => end = Some(">"),
+                Some(n) if n.is_alphabetic() => {
+                    positions.push((line, column));
+                    end = Some(">");
+                }
+                _ => {}
+            },
+            None => {}
         }
-        Some((_, v)) => {
-            let node = parse_html_node(vec![v], ignore, fn_name);
-            return node;
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
         }
+        i += 1;
     }
+    positions
 }
 
-fn include_replace(htmls: Vec<Element>, sql_map: &mut BTreeMap<String, Element>) -> Vec<Element> {
+fn include_replace(
+    htmls: Vec<Element>,
+    sql_map: &mut BTreeMap<String, Element>,
+) -> Result<Vec<Element>, Error> {
     let mut results = vec![];
     for mut x in htmls {
         match x.tag.as_str() {
@@ -71,7 +296,7 @@ fn include_replace(htmls: Vec<Element>, sql_map: &mut BTreeMap<String, Element>)
                 sql_map.insert(
                     x.attrs
                         .get("id")
-                        .expect("[rbatis] <sql> element must have id!")
+                        .ok_or_else(|| Error::from("[rbatis] <sql> element must have id!"))?
                         .clone(),
                     x.clone(),
                 );
@@ -80,19 +305,24 @@ fn include_replace(htmls: Vec<Element>, sql_map: &mut BTreeMap<String, Element>)
                 let ref_id = x
                     .attrs
                     .get("refid")
-                    .expect("[rbatis] <include> element must have attr <include refid=\"\">!")
+                    .ok_or_else(|| {
+                        Error::from(
+                            "[rbatis] <include> element must have attr <include refid=\"\">!",
+                        )
+                    })?
                     .clone();
-                let url;
-                if ref_id.contains("://") {
-                    url = Url::parse(&ref_id).expect(&format!(
+                let parse_fail = |_| {
+                    Error::from(format!(
                         "[rbatis] parse <include refid=\"{}\"> fail!",
                         ref_id
-                    ));
+                    ))
+                };
+                let url = if ref_id.contains("://") {
+                    Url::parse(&ref_id).map_err(parse_fail)?
                 } else {
-                    url = Url::parse(&format!("current://current?refid={}", ref_id)).expect(
-                        &format!("[rbatis] parse <include refid=\"{}\"> fail!", ref_id),
-                    );
-                }
+                    Url::parse(&format!("current://current?refid={}", ref_id))
+                        .map_err(parse_fail)?
+                };
                 let path = url.host_str().unwrap_or_default().to_string()
                     + url.path().trim_end_matches("/").trim_end_matches("\\");
                 match url.scheme() {
@@ -106,15 +336,17 @@ fn include_replace(htmls: Vec<Element>, sql_map: &mut BTreeMap<String, Element>)
                             }
                         }
                         if !have_ref_id {
-                            panic!("not find ref_id on url {}", ref_id);
+                            return Err(Error::from(format!("not find ref_id on url {}", ref_id)));
                         }
-                        let mut f = File::open(&path).expect(&format!(
-                            "[rbatis] can't find file={}",
-                            url.host_str().unwrap_or_default()
-                        ));
+                        let mut f = File::open(&path).map_err(|_| {
+                            Error::from(format!(
+                                "[rbatis] can't find file={}",
+                                url.host_str().unwrap_or_default()
+                            ))
+                        })?;
                         let mut html = String::new();
-                        f.read_to_string(&mut html).expect("read fail");
-                        let datas = load_mapper_vec(&html).expect("read fail");
+                        f.read_to_string(&mut html)?;
+                        let datas = load_mapper_vec(&html)?;
                         let mut not_find = true;
                         for element in datas {
                             if element.tag.eq("sql") && element.attrs.get("id").eq(&Some(&ref_id)) {
@@ -123,7 +355,10 @@ fn include_replace(htmls: Vec<Element>, sql_map: &mut BTreeMap<String, Element>)
                             }
                         }
                         if not_find {
-                            panic!("not find ref_id={} on file={}", ref_id, path);
+                            return Err(Error::from(format!(
+                                "not find ref_id={} on file={}",
+                                ref_id, path
+                            )));
                         }
                     }
                     "current" => {
@@ -135,15 +370,20 @@ fn include_replace(htmls: Vec<Element>, sql_map: &mut BTreeMap<String, Element>)
                         }
                         let element = sql_map
                             .get(ref_id_pair.as_str())
-                            .expect(&format!(
-                                "[rbatis] can not find element <include refid=\"{}\"> !",
-                                ref_id
-                            ))
+                            .ok_or_else(|| {
+                                Error::from(format!(
+                                    "[rbatis] can not find element <include refid=\"{}\"> !",
+                                    ref_id
+                                ))
+                            })?
                             .clone();
                         x = element;
                     }
                     _scheme => {
-                        panic!("unimplemented scheme <include refid=\"{}\">", ref_id)
+                        return Err(Error::from(format!(
+                            "unimplemented scheme <include refid=\"{}\">",
+                            ref_id
+                        )));
                     }
                 }
             }
@@ -157,25 +397,25 @@ fn include_replace(htmls: Vec<Element>, sql_map: &mut BTreeMap<String, Element>)
             },
         }
         if x.childs.len() != 0 {
-            x.childs = include_replace(x.childs.clone(), sql_map);
+            x.childs = include_replace(x.childs.clone(), sql_map)?;
         }
         results.push(x);
     }
-    return results;
+    Ok(results)
 }
 
 fn parse_html_node(
     htmls: Vec<Element>,
     ignore: &mut Vec<String>,
     fn_name: &str,
-) -> proc_macro2::TokenStream {
+) -> Result<proc_macro2::TokenStream, Error> {
     let mut methods = quote!();
-    let fn_impl = parse(&htmls, &mut methods, ignore, fn_name);
+    let fn_impl = parse(&htmls, &mut methods, ignore, fn_name)?;
     let token = quote! {
         #methods
         #fn_impl
     };
-    token
+    Ok(token)
 }
 
 /// gen rust code
@@ -184,7 +424,7 @@ fn parse(
     methods: &mut proc_macro2::TokenStream,
     ignore: &mut Vec<String>,
     fn_name: &str,
-) -> proc_macro2::TokenStream {
+) -> Result<proc_macro2::TokenStream, Error> {
     let mut body = quote! {};
     let fix_sql = quote! {};
     for x in arg {
@@ -193,7 +433,7 @@ fn parse(
                 return parse(&x.childs, methods, ignore, fn_name);
             }
             "sql" => {
-                let code_sql = parse(&x.childs, methods, ignore, fn_name);
+                let code_sql = parse(&x.childs, methods, ignore, fn_name)?;
                 body = quote! {
                             #body
                             #code_sql
@@ -210,21 +450,34 @@ fn parse(
                 let convert_list = find_convert_string(&string_data);
                 let mut replaces = quote! {};
                 for (k, v) in convert_list {
+                    let raw = k.trim().strip_suffix(".raw()");
+                    let quote = k.trim().strip_suffix(".quote()");
                     let method_impl = crate::codegen::func::impl_fn(
                         &body.to_string(),
                         "",
-                        &format!("\"{}\"", k),
+                        &format!("\"{}\"", raw.or(quote).unwrap_or(&k)),
                         false,
                         ignore,
-                    );
+                    )?;
                     if v.starts_with("#") {
                         string_data = string_data.replacen(&v, &"?", 1);
                         body = quote! {
                             #body
                             args.push(rbs::to_value(#method_impl).unwrap_or_default());
                         };
-                    } else {
+                    } else if raw.is_some() {
                         replaces = quote! {#replaces.replacen(#v, &#method_impl.as_sql(), 1)};
+                    } else if quote.is_some() {
+                        let driver_impl = crate::codegen::func::impl_fn(
+                            &body.to_string(),
+                            "",
+                            "\"quote_driver\"",
+                            false,
+                            ignore,
+                        )?;
+                        replaces = quote! {#replaces.replacen(#v, &rbatis_codegen::identifier::quote_value(#method_impl, #driver_impl)?, 1)};
+                    } else {
+                        replaces = quote! {#replaces.replacen(#v, &rbatis_codegen::sql_policy::to_sql(#method_impl)?, 1)};
                     }
                 }
                 if !replaces.is_empty() {
@@ -240,13 +493,10 @@ fn parse(
                 }
             }
             "if" => {
-                let test_value = x
-                    .attrs
-                    .get("test")
-                    .expect(&format!("{} element must be have test field!", x.tag));
+                let test_value = attr(x, "test")?;
                 let mut if_tag_body = quote! {};
                 if x.childs.len() != 0 {
-                    if_tag_body = parse(&x.childs, methods, ignore, fn_name);
+                    if_tag_body = parse(&x.childs, methods, ignore, fn_name)?;
                 }
                 impl_if(
                     test_value,
@@ -255,7 +505,7 @@ fn parse(
                     methods,
                     quote! {},
                     ignore,
-                );
+                )?;
             }
             "trim" => {
                 let empty_string = String::new();
@@ -282,28 +532,20 @@ fn parse(
                     methods,
                     ignore,
                     fn_name,
-                );
+                )?;
             }
             "bind" => {
-                let name = x
-                    .attrs
-                    .get("name")
-                    .expect("<bind> must be have name!")
-                    .to_string();
-                let value = x
-                    .attrs
-                    .get("value")
-                    .expect("<bind> element must be have value!")
-                    .to_string();
+                let name = attr(x, "name")?.to_string();
+                let value = attr(x, "value")?;
 
-                let name_expr = parse_expr(&name);
+                let name_expr = parse_expr(&name)?;
                 let method_impl = crate::codegen::func::impl_fn(
                     &body.to_string(),
                     "",
                     &format!("\"{}\"", value),
                     false,
                     ignore,
-                );
+                )?;
                 body = quote! {
                     #body
                     //bind
@@ -324,7 +566,7 @@ fn parse(
                     methods,
                     ignore,
                     fn_name,
-                );
+                )?;
                 body = quote! {
                     #body
                     //check ends with where
@@ -337,39 +579,38 @@ fn parse(
                 let mut inner_body = quote! {};
                 for x in &x.childs {
                     if x.tag.ne("when") && x.tag.ne("otherwise") {
-                        panic!("choose node's childs must be when node and otherwise node!");
+                        return Err(Error::from(
+                            "[rbatis] the childs of <choose> must be <when> and <otherwise>",
+                        ));
                     }
                     if x.tag.eq("when") {
-                        let test_value = x
-                            .attrs
-                            .get("test")
-                            .expect(&format!("{} element must be have test field!", x.tag));
+                        let test_value = attr(x, "test")?;
                         let mut if_tag_body = quote! {};
                         if x.childs.len() != 0 {
-                            if_tag_body = parse(&x.childs, methods, ignore, fn_name);
+                            if_tag_body = parse(&x.childs, methods, ignore, fn_name)?;
                         }
                         impl_if(
                             test_value,
                             if_tag_body,
                             &mut inner_body,
                             methods,
-                            quote! {return sql;},
+                            quote! {return Ok(sql);},
                             ignore,
-                        );
+                        )?;
                     }
                     if x.tag.eq("otherwise") {
-                        let child_body = parse(&x.childs, methods, ignore, fn_name);
+                        let child_body = parse(&x.childs, methods, ignore, fn_name)?;
                         impl_otherwise(child_body, &mut inner_body, methods, ignore);
                     }
                 }
                 let cup = x.child_string_cup();
                 body = quote! {
                   #body
-                  sql.push_str(&|| -> String {
+                  sql.push_str(&|| -> Result<String, rbatis_codegen::error::Error> {
                            let mut sql = String::with_capacity(#cup);
                            #inner_body
-                           return sql;
-                  }());
+                           return Ok(sql);
+                  }()?);
                 }
             }
 
@@ -404,14 +645,14 @@ fn parse(
                 ignores.push(findex.to_string());
                 ignores.push(item.to_string());
 
-                let impl_body = parse(&x.childs, methods, &mut ignores, fn_name);
+                let impl_body = parse(&x.childs, methods, &mut ignores, fn_name)?;
                 let method_impl = crate::codegen::func::impl_fn(
                     &body.to_string(),
                     "",
                     &format!("\"{}\"", collection),
                     false,
                     ignore,
-                );
+                )?;
                 body = quote! {
                     #body
                 };
@@ -455,21 +696,21 @@ fn parse(
             "set" => {
                 impl_trim(
                     " set ", " ", " |,", " |,", x, &mut body, arg, methods, ignore, fn_name,
-                );
+                )?;
             }
 
             "select" => {
                 let method_name = Ident::new(fn_name, Span::call_site());
-                let child_body = parse(&x.childs, methods, ignore, fn_name);
+                let child_body = parse(&x.childs, methods, ignore, fn_name)?;
                 let cup = x.child_string_cup();
                 let select = quote! {
-                    pub fn #method_name (arg:&rbs::Value, _tag: char) -> (String,Vec<rbs::Value>) {
+                    pub fn #method_name (arg:&rbs::Value, _tag: char) -> Result<(String,Vec<rbs::Value>), rbatis_codegen::error::Error> {
                        use rbatis_codegen::ops::*;
                        let mut sql = String::with_capacity(#cup);
                        let mut args = Vec::with_capacity(20);
                        #child_body
                        #fix_sql
-                       return (sql,args);
+                       return Ok((sql,args));
                     }
                 };
                 body = quote! {
@@ -479,16 +720,16 @@ fn parse(
             }
             "update" => {
                 let method_name = Ident::new(fn_name, Span::call_site());
-                let child_body = parse(&x.childs, methods, ignore, fn_name);
+                let child_body = parse(&x.childs, methods, ignore, fn_name)?;
                 let cup = x.child_string_cup();
                 let select = quote! {
-                    pub fn #method_name (arg:&rbs::Value, _tag: char) -> (String,Vec<rbs::Value>) {
+                    pub fn #method_name (arg:&rbs::Value, _tag: char) -> Result<(String,Vec<rbs::Value>), rbatis_codegen::error::Error> {
                        use rbatis_codegen::ops::*;
                        let mut sql = String::with_capacity(#cup);
                        let mut args = Vec::with_capacity(20);
                        #child_body
                        #fix_sql
-                       return (sql,args);
+                       return Ok((sql,args));
                     }
                 };
                 body = quote! {
@@ -498,16 +739,16 @@ fn parse(
             }
             "insert" => {
                 let method_name = Ident::new(fn_name, Span::call_site());
-                let child_body = parse(&x.childs, methods, ignore, fn_name);
+                let child_body = parse(&x.childs, methods, ignore, fn_name)?;
                 let cup = x.child_string_cup();
                 let select = quote! {
-                    pub fn #method_name (arg:&rbs::Value, _tag: char) -> (String,Vec<rbs::Value>) {
+                    pub fn #method_name (arg:&rbs::Value, _tag: char) -> Result<(String,Vec<rbs::Value>), rbatis_codegen::error::Error> {
                        use rbatis_codegen::ops::*;
                        let mut sql = String::with_capacity(#cup);
                        let mut args = Vec::with_capacity(20);
                        #child_body
                        #fix_sql
-                       return (sql,args);
+                       return Ok((sql,args));
                     }
                 };
                 body = quote! {
@@ -517,16 +758,16 @@ fn parse(
             }
             "delete" => {
                 let method_name = Ident::new(fn_name, Span::call_site());
-                let child_body = parse(&x.childs, methods, ignore, fn_name);
+                let child_body = parse(&x.childs, methods, ignore, fn_name)?;
                 let cup = x.child_string_cup();
                 let select = quote! {
-                    pub fn #method_name (arg:&rbs::Value, _tag: char) -> (String,Vec<rbs::Value>) {
+                    pub fn #method_name (arg:&rbs::Value, _tag: char) -> Result<(String,Vec<rbs::Value>), rbatis_codegen::error::Error> {
                        use rbatis_codegen::ops::*;
                        let mut sql = String::with_capacity(#cup);
                        let mut args = Vec::with_capacity(20);
                        #child_body
                        #fix_sql
-                       return (sql,args);
+                       return Ok((sql,args));
                     }
                 };
                 body = quote! {
@@ -538,10 +779,10 @@ fn parse(
         }
     }
 
-    return body.into();
+    Ok(body)
 }
 
-fn remove_extra(txt: &str) -> String {
+pub(crate) fn remove_extra(txt: &str) -> String {
     let txt = txt.trim().replace("\\r", "");
     let lines: Vec<&str> = txt.split("\n").collect();
     let mut data = String::with_capacity(txt.len());
@@ -584,14 +825,14 @@ fn impl_if(
     _methods: &mut proc_macro2::TokenStream,
     appends: proc_macro2::TokenStream,
     ignore: &mut Vec<String>,
-) {
+) -> Result<(), Error> {
     let method_impl = crate::codegen::func::impl_fn(
         &body.to_string(),
         "",
         &format!("\"{}\"", test_value),
         false,
         ignore,
-    );
+    )?;
     *body = quote! {
           #body
           if #method_impl.to_owned().into() {
@@ -599,6 +840,7 @@ fn impl_if(
              #appends
           }
     };
+    Ok(())
 }
 
 fn impl_otherwise(
@@ -624,8 +866,8 @@ fn impl_trim(
     methods: &mut proc_macro2::TokenStream,
     ignore: &mut Vec<String>,
     fn_name: &str,
-) {
-    let trim_body = parse(&x.childs, methods, ignore, fn_name);
+) -> Result<(), Error> {
+    let trim_body = parse(&x.childs, methods, ignore, fn_name)?;
     let prefixs: Vec<&str> = prefix_overrides.split("|").collect();
     let suffixs: Vec<&str> = suffix_overrides.split("|").collect();
     let have_trim = prefixs.len() != 0 && suffixs.len() != 0;
@@ -665,19 +907,68 @@ fn impl_trim(
            sql.push_str(#suffix);
         };
     }
+    Ok(())
 }
 
 pub fn impl_fn_html(m: &ItemFn, args: &AttributeArgs) -> TokenStream {
     let fn_name = m.sig.ident.to_string();
     let html_data = args.get(0).to_token_stream().to_string();
-    let t = parse_html(&html_data, &fn_name, &mut vec![]);
-    return t.into();
+    match try_parse_html(&html_data, &fn_name, &mut vec![]) {
+        Ok(t) => impl_fn_output(m, t).into(),
+        Err(e) => compile_error_fn(m, args, e),
+    }
+}
+
+/// the generated fn return `Result<(String,Vec<rbs::Value>), rbatis_codegen::error::Error>` if the fn declare a return type,
+/// so the `${}` value rejected by the sql policy is returned as Err.
+/// otherwise it return `(String,Vec<rbs::Value>)`, and the sql is empty if the value is rejected
+pub(crate) fn impl_fn_output(
+    m: &ItemFn,
+    generated: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if let ReturnType::Type(..) = m.sig.output {
+        return generated;
+    }
+    let method_name = &m.sig.ident;
+    quote! {
+        pub fn #method_name (arg:&rbs::Value, _tag: char) -> (String,Vec<rbs::Value>) {
+            #generated
+            #method_name(arg, _tag).unwrap_or_default()
+        }
+    }
+}
+
+/// the compile error at the template, and the fn without sql(the caller have no more errors)
+pub(crate) fn compile_error_fn(m: &ItemFn, args: &AttributeArgs, e: Error) -> TokenStream {
+    let error = syn::Error::new_spanned(args.first(), e).to_compile_error();
+    let method_name = &m.sig.ident;
+    let generated = quote! {
+        pub fn #method_name (_arg:&rbs::Value, _tag: char) -> Result<(String,Vec<rbs::Value>), rbatis_codegen::error::Error> {
+            Ok((String::new(), vec![]))
+        }
+    };
+    let generated = impl_fn_output(m, generated);
+    quote! {
+        #error
+        #generated
+    }
+    .into()
+}
+
+/// the attr of element, it's error if not exist
+fn attr<'a>(x: &'a Element, name: &str) -> Result<&'a String, Error> {
+    x.attrs.get(name).ok_or_else(|| {
+        Error::from(format!(
+            "[rbatis] <{}> element must have attr `{}`",
+            x.tag, name
+        ))
+    })
 }
 
 /// parse to expr
-fn parse_expr(lit_str: &str) -> Expr {
+fn parse_expr(lit_str: &str) -> Result<Expr, Error> {
     let s = syn::parse::<syn::LitStr>(lit_str.to_token_stream().into())
-        .expect(&format!("parse::<syn::LitStr> fail: {}", lit_str));
-    return syn::parse_str::<Expr>(&s.value())
-        .expect(&format!("parse_str::<Expr> fail: {}", lit_str));
+        .map_err(|e| Error::from(format!("[rbatis] parse `{}` fail: {}", lit_str, e)))?;
+    syn::parse_str::<Expr>(&s.value())
+        .map_err(|e| Error::from(format!("[rbatis] parse `{}` fail: {}", lit_str, e)))
 }
@@ -1,4 +1,4 @@
-use crate::codegen::parser_html::parse_html;
+use crate::codegen::parser_html::{compile_error_fn, impl_fn_output, try_parse_html};
 use crate::codegen::proc_macro::TokenStream;
 use crate::codegen::syntax_tree_pysql::bind_node::BindNode;
 use crate::codegen::syntax_tree_pysql::choose_node::ChooseNode;
@@ -17,6 +17,8 @@ use quote::ToTokens;
 use std::collections::HashMap;
 use syn::{AttributeArgs, ItemFn};
 
+const FOR_HINT: &str = "\n  hint: for example `for item in ids:` or `for index,item in ids:`";
+
 pub trait ParsePySql {
     fn parse_pysql(arg: &str) -> Result<Vec<NodeType>, crate::codegen::syntax_tree_pysql::error::Error>;
 }
@@ -28,18 +30,31 @@ pub fn impl_fn_py(m: &ItemFn, args: &AttributeArgs) -> TokenStream {
         data = data[1..data.len() - 1].to_string();
     }
     data = data.replace("\\n", "\n");
-    let nodes = NodeType::parse_pysql(&data).expect("[rbatis] parse py_sql fail!");
+    let nodes = match NodeType::parse_pysql(&data) {
+        Ok(v) => v,
+        Err(e) => return compile_error_fn(m, args, e.to_string().into()),
+    };
     let htmls = crate::codegen::syntax_tree_pysql::to_html(
         &nodes,
         data.starts_with("select") || data.starts_with(" select"),
         &fn_name,
     );
-    return parse_html(&htmls, &fn_name, &mut vec![]).into();
+    match try_parse_html(&htmls, &fn_name, &mut vec![]) {
+        Ok(v) => impl_fn_output(m, v).into(),
+        Err(e) => compile_error_fn(m, args, e),
+    }
 }
 
 impl ParsePySql for NodeType {
     //TODO maybe this use Rust parser crates？
     fn parse_pysql(arg: &str) -> Result<Vec<NodeType>, Error> {
+        Self::parse_pysql_lines(arg, 0)
+    }
+}
+
+impl NodeType {
+    /// parse the lines of py_sql, the `line_offset` is the line of `arg` in the py_sql
+    fn parse_pysql_lines(arg: &str, line_offset: i32) -> Result<Vec<NodeType>, Error> {
         let line_space_map = Self::create_line_space_map(&arg);
         let mut main_node = vec![];
         let ls = arg.lines();
@@ -62,12 +77,11 @@ impl ParsePySql for NodeType {
             if do_skip != -1 && do_skip >= skip {
                 skip = do_skip;
             }
-            let parserd;
-            if !child_str.is_empty() {
-                parserd = Self::parse_pysql(child_str.as_str())?;
+            let parserd = if !child_str.is_empty() {
+                Self::parse_pysql_lines(child_str.as_str(), line_offset + line + 1)?
             } else {
-                parserd = vec![];
-            }
+                vec![]
+            };
             Self::parse_pysql_node(
                 &mut main_node,
                 x,
@@ -76,13 +90,20 @@ impl ParsePySql for NodeType {
                     .ok_or_else(|| Error::from(format!("line:{} not exist！", line)))?
                     as usize,
                 parserd,
-            )?;
+            )
+            .map_err(|e| {
+                Error::from(format!(
+                    "[rbatis] py_sql line {}, column {}, `{}`: {}",
+                    line_offset + line + 1,
+                    count_index + 1,
+                    x.trim(),
+                    e.to_string().trim_start_matches("[rbatis] ")
+                ))
+            })?;
         }
         return Ok(main_node);
     }
-}
 
-impl NodeType {
     fn parse_pysql_node(
         main_node: &mut Vec<NodeType>,
         x: &str,
@@ -103,6 +124,7 @@ impl NodeType {
                         let index = len - 1 - index;
                         let item = vecs[index];
                         childs = vec![Self::parse_trim_node(item, x, childs)?];
+                        Self::check_childs(&childs[0])?;
                         if index == 0 {
                             for x in &childs {
                                 main_node.push(x.clone());
@@ -113,6 +135,7 @@ impl NodeType {
                 }
             }
             let node = Self::parse_trim_node(trim_x, x, childs)?;
+            Self::check_childs(&node)?;
             main_node.push(node);
             return Ok(());
         } else {
@@ -132,6 +155,25 @@ impl NodeType {
         }
     }
 
+    /// the node(except bind and continue) must have the child lines
+    fn check_childs(node: &NodeType) -> Result<(), Error> {
+        let is_empty = match node {
+            NodeType::NIf(n) => n.childs.is_empty(),
+            NodeType::NTrim(n) => n.childs.is_empty(),
+            NodeType::NForEach(n) => n.childs.is_empty(),
+            NodeType::NChoose(n) => n.when_nodes.is_empty() && n.otherwise_node.is_none(),
+            NodeType::NOtherwise(n) => n.childs.is_empty(),
+            NodeType::NWhen(n) => n.childs.is_empty(),
+            NodeType::NSet(n) => n.childs.is_empty(),
+            NodeType::NWhere(n) => n.childs.is_empty(),
+            _ => false,
+        };
+        if is_empty {
+            return Err(Error::from("[rbatis] the node not have child lines\n  hint: the child lines of the node must be indented more than the node"));
+        }
+        Ok(())
+    }
+
     fn count_space(arg: &str) -> i32 {
         let cs = arg.chars();
         let mut index = 0;
@@ -200,16 +242,13 @@ impl NodeType {
             }));
         } else if trim_express.starts_with(ForEachNode::name()) {
             let for_tag = "for";
-            if !trim_express.starts_with(for_tag) {
-                return Err(Error::from(
-                    "[rbatis] parser express fail:".to_string() + source_str,
-                ));
-            }
             let in_tag = " in ";
-            if !trim_express.contains(in_tag) {
-                return Err(Error::from(
-                    "[rbatis] parser express fail:".to_string() + source_str,
-                ));
+            if !trim_express.starts_with(for_tag) || !trim_express.contains(in_tag) {
+                return Err(Error::from(format!(
+                    "[rbatis] parser for express fail: {}{}",
+                    source_str.trim(),
+                    FOR_HINT
+                )));
             }
             let in_index = trim_express
                 .find(in_tag)
@@ -220,7 +259,10 @@ impl NodeType {
             if item.contains(",") {
                 let splits: Vec<&str> = item.split(",").collect();
                 if splits.len() != 2 {
-                    panic!("[rbatis_codegen] for node must be 'for key,item in col:'");
+                    return Err(Error::from(format!(
+                        "[rbatis] for node must be 'for key,item in col:'{}",
+                        FOR_HINT
+                    )));
                 }
                 index = splits[0];
                 item = splits[1];
@@ -240,7 +282,7 @@ impl NodeType {
                     trim: express.to_string(),
                 }));
             } else {
-                return Err(Error::from(format!("[rbatis] express trim value must be string value, for example:  trim 'value',error express: {}", trim_express)));
+                Err(Error::from(format!("[rbatis] express trim value must be string value, error express: {}\n  hint: for example `trim ',':` or `trim 'and ':`", trim_express)))
             }
         } else if trim_express.starts_with(ChooseNode::name()) {
             let mut node = ChooseNode {
@@ -256,7 +298,7 @@ impl NodeType {
                         node.otherwise_node = Some(Box::new(x));
                     }
                     _ => {
-                        return Err(Error::from("[rbatis] parser node fail,choose node' child must be when and otherwise nodes!".to_string()));
+                        return Err(Error::from("[rbatis] parser node fail,choose node' child must be when and otherwise nodes!\n  hint: the `when ...:` and `otherwise:` must be indented under the `choose:`, and the sql must be indented under them".to_string()));
                     }
                 }
             }
@@ -274,17 +316,17 @@ impl NodeType {
         } else if trim_express.starts_with(BindNode::default_name())
             || trim_express.starts_with(BindNode::name())
         {
-            let express;
-            if trim_express.starts_with(BindNode::default_name()) {
-                express = trim_express[BindNode::default_name().len()..].trim();
+            let express = if trim_express.starts_with(BindNode::default_name()) {
+                trim_express[BindNode::default_name().len()..].trim()
             } else {
-                express = trim_express[BindNode::name().len()..].trim();
-            }
+                trim_express[BindNode::name().len()..].trim()
+            };
             let name_value: Vec<&str> = express.split("=").collect();
             if name_value.len() != 2 {
-                return Err(Error::from(
-                    "[rbatis] parser bind express fail:".to_string() + trim_express,
-                ));
+                return Err(Error::from(format!(
+                    "[rbatis] parser bind express fail: {}\n  hint: for example `bind pattern = '%' + name + '%':`",
+                    trim_express
+                )));
             }
             return Ok(NodeType::NBind(BindNode {
                 name: name_value[0].to_owned(),
@@ -298,9 +340,10 @@ impl NodeType {
             return Ok(NodeType::NContinue(ContinueNode {}));
         } else {
             // unkonw tag
-            return Err(Error::from(
-                "[rbatis] unknow tag: ".to_string() + source_str,
-            ));
+            Err(Error::from(format!(
+                "[rbatis] unknow tag: {}\n  hint: the line ends with `:` is the node(if, for, trim, choose, when, otherwise, bind, set, where, continue), the sql ends with `:` must be in the backquote, for example `` `a:` ``",
+                source_str.trim()
+            )))
         }
     }
 }
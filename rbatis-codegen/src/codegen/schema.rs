@@ -0,0 +1,489 @@
+//! the schema snapshot of database, it's used to check the table and column names
+//! of the static sql at compile time(`schema_check` feature of rbatis).
+//!
+//! the snapshot is a json file of `{table: {column: type}}`, for example:
+//! ```json
+//! {
+//!   "biz_activity": {"id": "varchar(50)", "name": "varchar(255)", "status": "int"}
+//! }
+//! ```
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::codegen::loader_html::{load_html, Element};
+use crate::codegen::parser_html::remove_extra;
+use crate::codegen::parser_pysql::ParsePySql;
+use crate::codegen::syntax_tree_pysql::{to_html, NodeType};
+use crate::error::Error;
+
+const KEYWORDS: &[&str] = &[
+    "select",
+    "from",
+    "where",
+    "and",
+    "or",
+    "not",
+    "in",
+    "is",
+    "null",
+    "like",
+    "ilike",
+    "between",
+    "exists",
+    "as",
+    "on",
+    "using",
+    "join",
+    "left",
+    "right",
+    "inner",
+    "outer",
+    "full",
+    "cross",
+    "natural",
+    "group",
+    "by",
+    "order",
+    "asc",
+    "desc",
+    "limit",
+    "offset",
+    "fetch",
+    "next",
+    "rows",
+    "row",
+    "only",
+    "top",
+    "having",
+    "distinct",
+    "union",
+    "all",
+    "any",
+    "some",
+    "insert",
+    "into",
+    "values",
+    "value",
+    "update",
+    "set",
+    "delete",
+    "case",
+    "when",
+    "then",
+    "else",
+    "end",
+    "true",
+    "false",
+    "returning",
+    "output",
+    "inserted",
+    "deleted",
+    "with",
+    "recursive",
+    "interval",
+    "escape",
+    "default",
+    "collate",
+    "regexp",
+    "duplicate",
+    "key",
+    "ignore",
+    "replace",
+    "conflict",
+    "do",
+    "nothing",
+    "for",
+    "share",
+    "nowait",
+    "skip",
+    "locked",
+    "current_timestamp",
+    "current_date",
+    "current_time",
+    "current_user",
+    "session_user",
+    "localtime",
+    "localtimestamp",
+    "sql",
+    "nulls",
+    "first",
+    "last",
+    "over",
+    "partition",
+    "window",
+    "filter",
+    "within",
+    "range",
+    "preceding",
+    "following",
+    "unbounded",
+    "current",
+    "ties",
+    "lateral",
+    "intersect",
+    "except",
+    "minus",
+    "similar",
+    "to",
+    "at",
+    "zone",
+    "both",
+    "leading",
+    "trailing",
+    "unknown",
+    "year",
+    "month",
+    "week",
+    "day",
+    "hour",
+    "minute",
+    "second",
+    "microsecond",
+    "quarter",
+    "epoch",
+    "dow",
+    "doy",
+    "date",
+    "time",
+    "timestamp",
+    "timestamptz",
+    "int",
+    "integer",
+    "bigint",
+    "smallint",
+    "signed",
+    "unsigned",
+    "numeric",
+    "decimal",
+    "real",
+    "float",
+    "double",
+    "precision",
+    "char",
+    "varchar",
+    "text",
+    "boolean",
+    "bool",
+    "json",
+    "jsonb",
+    "uuid",
+];
+
+/// the tables and columns(`{table: {column: type}}`) of database
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    pub tables: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Schema {
+    /// parse the json of `{table: {column: type}}`
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let tables: BTreeMap<String, BTreeMap<String, String>> = serde_json::from_str(json)
+            .map_err(|e| Error::from(format!("[rbatis] parse schema fail: {}", e)))?;
+        let tables = tables
+            .into_iter()
+            .map(|(table, columns)| {
+                let columns = columns
+                    .into_iter()
+                    .map(|(k, v)| (k.to_lowercase(), v))
+                    .collect();
+                (table.to_lowercase(), columns)
+            })
+            .collect();
+        Ok(Self { tables })
+    }
+
+    /// load the schema json file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let json = std::fs::read_to_string(path.as_ref()).map_err(|e| {
+            Error::from(format!(
+                "[rbatis] read schema file {} fail: {}",
+                path.as_ref().display(),
+                e
+            ))
+        })?;
+        Self::from_json(&json)
+    }
+
+    /// the columns of table, the name can be `schema.table`
+    pub fn table(&self, name: &str) -> Option<&BTreeMap<String, String>> {
+        let name = name.to_lowercase();
+        self.tables
+            .get(&name)
+            .or_else(|| self.tables.get(name.rsplit('.').next().unwrap_or_default()))
+    }
+
+    /// check the table and the columns exist
+    pub fn check_table(&self, table: &str, columns: &[&str]) -> Result<(), Error> {
+        let table_columns = self
+            .table(table)
+            .ok_or_else(|| Error::from(format!("[rbatis] table `{}` not in schema", table)))?;
+        for column in columns {
+            if !table_columns.contains_key(&column.to_lowercase()) {
+                return Err(Error::from(format!(
+                    "[rbatis] column `{}` not in table `{}` of schema",
+                    column, table
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// check the static sql of py_sql
+    pub fn check_py_sql(&self, py_sql: &str) -> Result<(), Error> {
+        let nodes = NodeType::parse_pysql(py_sql).map_err(|e| Error::from(e.to_string()))?;
+        self.check_html(&to_html(&nodes, true, "py_sql"))
+    }
+
+    /// check the static sql of html
+    pub fn check_html(&self, html: &str) -> Result<(), Error> {
+        let elements = load_html(html).map_err(|e| Error::from(e.to_string()))?;
+        let mut sql = String::new();
+        static_sql(&elements, &mut sql);
+        self.check_sql(&sql)
+    }
+
+    /// check the tables(`from`/`join`/`update`/`into`) and columns of sql.
+    /// the `#{}`/`${}` is skipped, the columns is not checked if any table is dynamic(or sub query)
+    pub fn check_sql(&self, sql: &str) -> Result<(), Error> {
+        let tokens = tokenize(sql);
+        let is_keyword = |t: &Token| match t {
+            Token::Ident(v) => KEYWORDS.contains(&v.to_lowercase().as_str()),
+            _ => false,
+        };
+        // the alias or table name -> table
+        let mut tables: BTreeMap<String, String> = BTreeMap::new();
+        // the column alias
+        let mut aliases = BTreeSet::new();
+        // the index of table and alias tokens
+        let mut names = BTreeSet::new();
+        let mut is_dynamic = false;
+        // the parens, true if it's the args of function, for example `extract(year from t)`
+        let mut parens = vec![];
+        let mut i = 0;
+        while i < tokens.len() {
+            let in_function = parens.last().copied().unwrap_or_default();
+            let is_table = match &tokens[i] {
+                Token::Punct('(') => {
+                    let is_function = match i.checked_sub(1).map(|i| &tokens[i]) {
+                        Some(t @ Token::Ident(_)) => !is_keyword(t),
+                        _ => false,
+                    };
+                    parens.push(is_function);
+                    false
+                }
+                Token::Punct(')') => {
+                    parens.pop();
+                    false
+                }
+                Token::Ident(v) => match v.to_lowercase().as_str() {
+                    "from" | "join" | "into" => !in_function,
+                    // not `on duplicate key update` or `for update`
+                    "update" => match i.checked_sub(1).map(|i| &tokens[i]) {
+                        Some(Token::Ident(k)) => {
+                            !k.eq_ignore_ascii_case("key") && !k.eq_ignore_ascii_case("for")
+                        }
+                        _ => true,
+                    },
+                    "as" => {
+                        if let Some(Token::Ident(v)) = tokens.get(i + 1) {
+                            names.insert(i + 1);
+                            aliases.insert(v.to_lowercase());
+                        }
+                        false
+                    }
+                    _ => false,
+                },
+                _ => false,
+            };
+            i += 1;
+            if !is_table {
+                continue;
+            }
+            loop {
+                match tokens.get(i) {
+                    Some(Token::Ident(table)) if !is_keyword(&tokens[i]) => {
+                        if self.table(table).is_none() {
+                            return Err(Error::from(format!(
+                                "[rbatis] table `{}` not in schema",
+                                table
+                            )));
+                        }
+                        names.insert(i);
+                        let table = table.to_lowercase();
+                        let short = table.rsplit('.').next().unwrap_or_default().to_string();
+                        tables.insert(short, table.clone());
+                        i += 1;
+                        if let Some(Token::Ident(k)) = tokens.get(i) {
+                            if k.eq_ignore_ascii_case("as") {
+                                i += 1;
+                            }
+                        }
+                        match tokens.get(i) {
+                            Some(Token::Ident(alias)) if !is_keyword(&tokens[i]) => {
+                                names.insert(i);
+                                tables.insert(alias.to_lowercase(), table);
+                                i += 1;
+                            }
+                            _ => {}
+                        }
+                    }
+                    Some(Token::Ident(_)) | None => break,
+                    _ => {
+                        is_dynamic = true;
+                        break;
+                    }
+                }
+                if let Some(Token::Punct(',')) = tokens.get(i) {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+        }
+        let in_tables = tables.values().collect::<BTreeSet<_>>();
+        for (i, token) in tokens.iter().enumerate() {
+            let name = match token {
+                Token::Ident(v) if !names.contains(&i) && !is_keyword(token) => v.to_lowercase(),
+                _ => continue,
+            };
+            // the function
+            if let Some(Token::Punct('(')) = tokens.get(i + 1) {
+                continue;
+            }
+            let (table, column) = match name.rsplit_once('.') {
+                Some((table, column)) => (Some(table), column),
+                None => (None, name.as_str()),
+            };
+            if column == "*" {
+                continue;
+            }
+            match table {
+                Some(table) => {
+                    // the alias of sub query is not checked
+                    if let Some(table) = tables.get(table) {
+                        self.check_table(table, &[column])?;
+                    }
+                }
+                None => {
+                    if is_dynamic || aliases.contains(column) || tables.contains_key(column) {
+                        continue;
+                    }
+                    let exist = if in_tables.is_empty() {
+                        self.tables.values().any(|t| t.contains_key(column))
+                    } else {
+                        in_tables.iter().any(|t| {
+                            self.table(t)
+                                .map(|t| t.contains_key(column))
+                                .unwrap_or_default()
+                        })
+                    };
+                    if !exist {
+                        return Err(Error::from(format!(
+                            "[rbatis] column `{}` not in table(s) {:?} of schema",
+                            column, in_tables
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// the static text of elements
+fn static_sql(elements: &[Element], sql: &mut String) {
+    for x in elements {
+        if x.tag.is_empty() {
+            sql.push(' ');
+            sql.push_str(&remove_extra(&x.data));
+        } else {
+            static_sql(&x.childs, sql);
+        }
+    }
+}
+
+enum Token {
+    /// the identifier, or the `table.column`
+    Ident(String),
+    Punct(char),
+    /// the number, string, `?`, `#{}` and `${}`
+    Value,
+}
+
+fn tokenize(sql: &str) -> Vec<Token> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if (c == '#' || c == '$') && chars.get(i + 1) == Some(&'{') {
+            while i < chars.len() && chars[i] != '}' {
+                i += 1;
+            }
+            i += 1;
+            tokens.push(Token::Value);
+        } else if c == '&'
+            && chars[i + 1..]
+                .iter()
+                .take_while(|c| c.is_alphanumeric())
+                .count()
+                > 0
+        {
+            // the html entity, for example `&lt;`
+            i += 1;
+            while i < chars.len() && chars[i].is_alphanumeric() {
+                i += 1;
+            }
+            if chars.get(i) == Some(&';') {
+                i += 1;
+            }
+            tokens.push(Token::Punct('&'));
+        } else if c == '\'' {
+            i += 1;
+            while i < chars.len() && chars[i] != '\'' {
+                i += 1;
+            }
+            i += 1;
+            tokens.push(Token::Value);
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Value);
+        } else if c.is_alphabetic() || c == '_' || c == '"' || c == '`' || c == '[' {
+            let mut ident = String::new();
+            while i < chars.len() {
+                let c = chars[i];
+                if c == '"' || c == '`' || c == '[' {
+                    let close = if c == '[' { ']' } else { c };
+                    i += 1;
+                    while i < chars.len() && chars[i] != close {
+                        ident.push(chars[i]);
+                        i += 1;
+                    }
+                    i += 1;
+                } else if c.is_alphanumeric()
+                    || c == '_'
+                    || c == '.'
+                    || (c == '*' && ident.ends_with('.'))
+                {
+                    ident.push(c);
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Ident(ident));
+        } else {
+            tokens.push(match c {
+                '?' => Token::Value,
+                c => Token::Punct(c),
+            });
+            i += 1;
+        }
+    }
+    tokens
+}
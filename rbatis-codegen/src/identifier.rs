@@ -0,0 +1,99 @@
+//! the identifier(table and column name) quoting of database, selected by the driver type:
+//! * mysql: `` `name` ``
+//! * postgres/sqlite: `"name"`
+//! * mssql: `[name]`
+//! * the other driver type(or empty) is not quoted
+//!
+//! the `${name.quote()}` of py_sql/html quote the value by the driver type of the `quote_driver` arg,
+//! it's the driver type of the executor if the arg is not exists.
+//! the value of `${name.quote()}` must be the identifier(the quoted name is not quoted again) or the number
+//! whatever the sql policy, the other value is rejected.
+//! ```rust
+//! use rbatis_codegen::identifier::{quote_columns, quote_identifier};
+//! assert_eq!(quote_identifier("postgres", "order"), "\"order\"");
+//! assert_eq!(quote_identifier("mysql", "public.order"), "`public`.`order`");
+//! assert_eq!(quote_columns("mssql", "id,type_name as name"), "[id],[type_name] as [name]");
+//! ```
+use std::borrow::Borrow;
+
+use rbs::Value;
+
+use crate::error::Error;
+use crate::sql_policy::is_identifier;
+
+/// the (open, close) quote char of driver type, None if it is not quoted
+pub fn quote_chars(driver_type: &str) -> Option<(char, char)> {
+    match driver_type {
+        "mysql" => Some(('`', '`')),
+        "postgres" | "sqlite" => Some(('"', '"')),
+        "mssql" => Some(('[', ']')),
+        _ => None,
+    }
+}
+
+/// quote the identifier(or `schema.table`) by the driver type, the other value is not changed
+pub fn quote_identifier(driver_type: &str, name: &str) -> String {
+    match quote_chars(driver_type) {
+        Some((open, close)) if is_plain_identifier(name) => name
+            .split('.')
+            .map(|part| format!("{}{}{}", open, part, close))
+            .collect::<Vec<_>>()
+            .join("."),
+        _ => name.to_string(),
+    }
+}
+
+/// quote the select columns, for example `id,type_name as name`
+pub fn quote_columns(driver_type: &str, columns: &str) -> String {
+    if quote_chars(driver_type).is_none() {
+        return columns.to_string();
+    }
+    columns
+        .split(',')
+        .map(|column| {
+            let column = column.trim();
+            match column.to_ascii_lowercase().find(" as ") {
+                Some(i) => format!(
+                    "{} as {}",
+                    quote_identifier(driver_type, column[..i].trim()),
+                    quote_identifier(driver_type, column[i + 4..].trim())
+                ),
+                None => quote_identifier(driver_type, column),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// the sql of `${value.quote()}`, Err if the value is not the identifier
+pub fn quote_sql<V: Borrow<Value>>(value: V, driver_type: &str) -> Result<String, Error> {
+    match value.borrow() {
+        Value::String(name) if is_identifier(name) => Ok(quote_identifier(driver_type, name)),
+        // the number, for example `where 1 = ?`
+        Value::String(name) if !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()) => {
+            Ok(name.to_string())
+        }
+        value => Err(Error::from(format!(
+            "[rbatis] the ${{name.quote()}} value {} is not the table or column name",
+            value
+        ))),
+    }
+}
+
+/// the sql of `${value.quote()}` for the generated code, the driver type is the value of `quote_driver`
+pub fn quote_value<V: Borrow<Value>, D: Borrow<Value>>(
+    value: V,
+    driver_type: D,
+) -> Result<String, Error> {
+    quote_sql(value, driver_type.borrow().as_str().unwrap_or_default())
+}
+
+/// the unquoted identifier, for example `name`, `t.name`
+fn is_plain_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name.split('.').all(|part| {
+            let mut chars = part.chars();
+            matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+                && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        })
+}
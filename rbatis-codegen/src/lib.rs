@@ -13,5 +13,7 @@ pub mod ops_not;
 pub mod ops_rem;
 pub mod ops_sub;
 pub mod ops_xor;
+pub mod sql_policy;
+pub mod identifier;
 
 pub use codegen::{rb_html, rb_py};
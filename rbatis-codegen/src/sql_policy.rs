@@ -0,0 +1,123 @@
+//! the policy of the `${}` raw substitution.
+//!
+//! the `${}` value is spliced into the sql without escape, so it's checked by the policy:
+//! * `SqlPolicy::Raw`(default): splice the value as it is
+//! * `SqlPolicy::Reject`: reject the string value which contains the quote, comment or statement separator
+//! * `SqlPolicy::Identifier`: the string value must be the identifier, for example `name`, `t.name`, `"name"`
+//!
+//! the `${expr.raw()}` is not checked by the policy, use it for the trusted sql fragment.
+//! ```rust
+//! use rbatis_codegen::sql_policy::{set_sql_policy, to_sql, SqlPolicy};
+//! use rbs::Value;
+//! set_sql_policy(SqlPolicy::Identifier);
+//! assert_eq!(to_sql(&Value::from("t.name")).unwrap(), "t.name");
+//! assert!(to_sql(&Value::from("name;drop table t")).is_err());
+//! set_sql_policy(SqlPolicy::Raw);
+//! ```
+use std::borrow::Borrow;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use rbs::Value;
+
+use crate::error::Error;
+use crate::ops::AsProxy;
+
+/// the policy of the `${}` value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlPolicy {
+    /// splice the value as it is
+    Raw,
+    /// reject the string which contains the quote(`'`, `"`, `` ` ``), comment(`--`, `/*`, `#`) or statement separator(`;`)
+    Reject,
+    /// the string must be the identifier(or `table.column`, or quoted by `"`, `` ` ``, `[]`)
+    Identifier,
+}
+
+static POLICY: AtomicU8 = AtomicU8::new(0);
+
+/// set the policy of `${}`, it's global
+pub fn set_sql_policy(policy: SqlPolicy) {
+    let v = match policy {
+        SqlPolicy::Raw => 0,
+        SqlPolicy::Reject => 1,
+        SqlPolicy::Identifier => 2,
+    };
+    POLICY.store(v, Ordering::SeqCst);
+}
+
+/// the policy of `${}`
+pub fn sql_policy() -> SqlPolicy {
+    match POLICY.load(Ordering::SeqCst) {
+        1 => SqlPolicy::Reject,
+        2 => SqlPolicy::Identifier,
+        _ => SqlPolicy::Raw,
+    }
+}
+
+/// the sql of the `${}` value, Err if it's rejected by the policy
+pub fn to_sql<V: Borrow<Value>>(value: V) -> Result<String, Error> {
+    let value = value.borrow();
+    let policy = sql_policy();
+    let s = match value {
+        Value::String(s) => s,
+        Value::Null
+        | Value::Bool(_)
+        | Value::I32(_)
+        | Value::I64(_)
+        | Value::U32(_)
+        | Value::U64(_)
+        | Value::F32(_)
+        | Value::F64(_) => return Ok(value.as_sql()),
+        _ if policy == SqlPolicy::Identifier => {
+            return Err(Error::from(format!(
+                "[rbatis] the ${{}} value {} is not identifier",
+                value
+            )));
+        }
+        _ => return Ok(value.as_sql()),
+    };
+    let pass = match policy {
+        SqlPolicy::Raw => true,
+        SqlPolicy::Reject => !is_dangerous(s),
+        SqlPolicy::Identifier => is_identifier(s),
+    };
+    if pass {
+        Ok(s.to_string())
+    } else {
+        Err(Error::from(format!(
+            "[rbatis] the ${{}} value `{}` is rejected by the sql policy {:?}, use ${{expr.raw()}} if it's trusted",
+            s, policy
+        )))
+    }
+}
+
+/// the string contains the quote, comment or statement separator
+pub fn is_dangerous(s: &str) -> bool {
+    s.contains(['\'', '"', '`', ';', '#', '\\'])
+        || s.contains("--")
+        || s.contains("/*")
+        || s.contains("*/")
+}
+
+/// the identifier, for example `name`, `t.name`, `"name"`, `` `name` ``, `[name]`
+pub fn is_identifier(s: &str) -> bool {
+    !s.is_empty()
+        && s.split('.').all(|part| {
+            let quoted = [('"', '"'), ('`', '`'), ('[', ']')]
+                .iter()
+                .find(|(open, close)| {
+                    part.len() > 2 && part.starts_with(*open) && part.ends_with(*close)
+                });
+            match quoted {
+                Some((open, close)) => {
+                    let inner = &part[1..part.len() - 1];
+                    !inner.contains(*open) && !inner.contains(*close) && !is_dangerous(inner)
+                }
+                None => {
+                    let mut chars = part.chars();
+                    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+                        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+                }
+            }
+        })
+}
@@ -2,11 +2,13 @@
 extern crate proc_macro;
 extern crate rbatis_codegen;
 
-use syn::{parse_macro_input, AttributeArgs, ItemFn};
+use syn::{parse_macro_input, AttributeArgs, DeriveInput, ItemFn};
 
 use crate::macros::html_sql_impl::impl_macro_html_sql;
 use crate::macros::py_sql_impl::impl_macro_py_sql;
+use crate::macros::schema_check::{impl_schema_check_table, TableArgs};
 use crate::macros::sql_impl::impl_macro_sql;
+use crate::macros::table_impl::impl_table;
 use crate::proc_macro::TokenStream;
 
 mod macros;
@@ -122,12 +124,52 @@ pub fn html_sql(args: TokenStream, func: TokenStream) -> TokenStream {
 }
 
 /// proxy rbatis_codegen rb_py
+/// the fn return `(String, Vec<rbs::Value>)`, declare `-> Result<(String, Vec<rbs::Value>), rbatis_codegen::error::Error>` to get the sql policy error
 #[proc_macro_attribute]
 pub fn rb_py(args: TokenStream, func: TokenStream) -> TokenStream {
     rbatis_codegen::rb_py(args, func)
 }
 /// proxy rbatis_codegen rb_html
+/// the fn return `(String, Vec<rbs::Value>)`, declare `-> Result<(String, Vec<rbs::Value>), rbatis_codegen::error::Error>` to get the sql policy error
 #[proc_macro_attribute]
 pub fn rb_html(args: TokenStream, func: TokenStream) -> TokenStream {
     rbatis_codegen::rb_html(args, func)
 }
+
+/// impl rbatis::table::Table, the crud macros use the TableMeta(table name,columns,primary key)
+/// the table and column names are quoted by the driver type, `#[table(no_quote)]` to disable it.
+/// for example:
+///
+/// #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, rbatis::Table)]
+/// #[table(name = "biz_activity")]
+/// pub struct BizActivity {
+///     #[column(pk, auto_increment)]
+///     pub id: Option<i64>,
+///     #[column(name = "activity_name")]
+///     pub name: Option<String>,
+///     #[column(insert_default)]
+///     pub create_time: Option<String>,
+///     #[column(skip)]
+///     pub remark: Option<String>,
+/// }
+///
+#[proc_macro_derive(Table, attributes(table, column))]
+pub fn table(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match impl_table(&input) {
+        Ok(v) => v.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// check the default table name(snake name of the type) of the crud macros by the schema snapshot,
+/// it's empty if the `schema_check` feature is disabled
+#[doc(hidden)]
+#[proc_macro]
+pub fn schema_check_table(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as TableArgs);
+    match impl_schema_check_table(&args) {
+        Ok(v) => v.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
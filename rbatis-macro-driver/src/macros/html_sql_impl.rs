@@ -5,7 +5,7 @@ use std::fs::File;
 use std::io::Read;
 use syn::{AttributeArgs, FnArg, ItemFn, Lit, NestedMeta};
 
-use crate::macros::py_sql_impl;
+use crate::macros::{py_sql_impl, schema_check};
 use crate::proc_macro::TokenStream;
 use crate::util::{find_fn_body, find_return_type, get_fn_args, is_query, is_rbatis_ref};
 
@@ -32,6 +32,7 @@ pub(crate) fn impl_macro_html_sql(target_fn: &ItemFn, args: &AttributeArgs) -> T
         }
     }
     let mut sql_ident = quote!();
+    let mut sql_span = Span::call_site();
     if args.len() >= 1 {
         if rbatis_name.is_empty() {
             panic!("[rbatis] you should add rbatis ref param  rb:&Rbatis  or rb: &mut Executor<'_,'_>  on '{}()'!", target_fn.sig.ident);
@@ -43,6 +44,7 @@ pub(crate) fn impl_macro_html_sql(target_fn: &ItemFn, args: &AttributeArgs) -> T
                 NestedMeta::Lit(l) => match l {
                     Lit::Str(v) => {
                         s = s + v.value().as_str();
+                        sql_span = v.span();
                     }
                     Lit::ByteStr(_) => {}
                     Lit::Byte(_) => {}
@@ -54,7 +56,7 @@ pub(crate) fn impl_macro_html_sql(target_fn: &ItemFn, args: &AttributeArgs) -> T
                 },
             }
         }
-        sql_ident = quote!(#s);
+        sql_ident = syn::LitStr::new(&s, sql_span).to_token_stream();
     } else {
         panic!("[rbatis] Incorrect macro parameter length!");
     }
@@ -67,17 +69,50 @@ pub(crate) fn impl_macro_html_sql(target_fn: &ItemFn, args: &AttributeArgs) -> T
             .to_string();
     }
     if file_name.ends_with(".html") {
+        let compile_error = |msg: String| -> TokenStream {
+            syn::Error::new(sql_span, msg).to_compile_error().into()
+        };
         let mut html_data = String::new();
-        let mut f = File::open(file_name.as_str())
-            .expect(&format!("File Name = '{}' does not exist", file_name));
-        f.read_to_string(&mut html_data)
-            .expect(&format!("{} read_to_string fail", file_name));
-        let mut htmls = rbatis_codegen::codegen::parser_html::load_mapper_map(&html_data)
-            .expect("load html content fail");
-        let token = htmls.remove(&func_name_ident.to_string()).expect("");
+        let read =
+            File::open(file_name.as_str()).and_then(|mut f| f.read_to_string(&mut html_data));
+        if let Err(e) = read {
+            return compile_error(format!(
+                "[rbatis] read html file '{}' fail: {}",
+                file_name, e
+            ));
+        }
+        let mut htmls = match rbatis_codegen::codegen::parser_html::check_html(&html_data)
+            .and_then(|_| rbatis_codegen::codegen::parser_html::load_mapper_map(&html_data))
+        {
+            Ok(v) => v,
+            Err(e) => return compile_error(format!("{} (file: {})", e, file_name)),
+        };
+        let token = match htmls.remove(&func_name_ident.to_string()) {
+            Some(v) => v,
+            None => {
+                return compile_error(format!(
+                    "[rbatis] html file '{}' not find the element id=\"{}\"\n  hint: the id of <select>/<update>/<insert>/<delete> must be the fn name",
+                    file_name, func_name_ident
+                ))
+            }
+        };
         let token = format!("{}", token);
-        sql_ident = token.to_token_stream();
+        sql_ident = syn::LitStr::new(&token, sql_span).to_token_stream();
+    }
+    let html = sql_ident.to_string();
+    let html = syn::parse_str::<syn::LitStr>(&html)
+        .map(|v| v.value())
+        .unwrap_or(html);
+    // the html file is checked when it's loaded
+    if !file_name.ends_with(".html") {
+        if let Err(e) = rbatis_codegen::codegen::parser_html::check_html(&html) {
+            return syn::Error::new(sql_span, e).to_compile_error().into();
+        }
     }
+    let schema_track = match schema_check::check_html(&html, sql_span) {
+        Ok(v) => v,
+        Err(e) => return e.to_compile_error().into(),
+    };
     let func_args_stream = target_fn.sig.inputs.to_token_stream();
     let fn_body = find_fn_body(target_fn);
     let is_async = target_fn.sig.asyncness.is_some();
@@ -113,7 +148,7 @@ pub(crate) fn impl_macro_html_sql(target_fn: &ItemFn, args: &AttributeArgs) -> T
     }
     let gen_target_method = quote! {
         #[rbatis::rb_html(#sql_ident)]
-        pub fn impl_html_sql(arg: &rbs::Value, _tag: char) {}
+        pub fn impl_html_sql(arg: &rbs::Value, _tag: char) -> Result<(String, Vec<rbs::Value>), rbatis_codegen::error::Error> {}
     };
     let gen_target_macro_arg = quote! {
         #sql_ident
@@ -144,14 +179,20 @@ pub(crate) fn impl_macro_html_sql(target_fn: &ItemFn, args: &AttributeArgs) -> T
     return quote! {
        pub async fn #func_name_ident #generic(#func_args_stream) -> #return_ty {
          #include_data
+         #schema_track
          let mut rb_arg_map = rbs::value::map::ValueMap::new();
          #sql_args_gen
          #fn_body
          use rbatis::executor::{RbatisRef};
          let driver_type = #rbatis_ident.rbatis_ref().driver_type()?;
+         // the driver type of `${name.quote()}`
+         if rb_arg_map["quote_driver"].is_null() {
+             rb_arg_map.insert("quote_driver".into(), driver_type.into());
+         }
          use rbatis::rbatis_codegen;
          #gen_func
-         let (mut sql,rb_args) = impl_html_sql(&rbs::Value::Map(rb_arg_map),'?');
+         let (mut sql,rb_args) = impl_html_sql(&rbs::Value::Map(rb_arg_map),'?')
+             .map_err(|e| rbatis::Error::new(rbatis::ErrorKind::SqlRejected, &e).with_source(e))?;
          #call_method
        }
     }
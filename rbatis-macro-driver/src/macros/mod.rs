@@ -1,3 +1,5 @@
 pub mod html_sql_impl;
 pub mod py_sql_impl;
+pub mod schema_check;
 pub mod sql_impl;
+pub mod table_impl;
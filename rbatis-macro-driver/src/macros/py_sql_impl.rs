@@ -3,6 +3,7 @@ use quote::quote;
 use quote::ToTokens;
 use syn::{AttributeArgs, FnArg, ItemFn, Lit, NestedMeta, Pat};
 
+use crate::macros::schema_check;
 use crate::proc_macro::TokenStream;
 use crate::util::{find_fn_body, find_return_type, get_fn_args, is_query, is_rbatis_ref};
 
@@ -30,6 +31,8 @@ pub(crate) fn impl_macro_py_sql(target_fn: &ItemFn, args: &AttributeArgs) -> Tok
     }
 
     let mut sql_ident = quote!();
+    let mut sql_span = Span::call_site();
+    let mut schema_track = quote!();
     if args.len() >= 1 {
         if rbatis_name.is_empty() {
             panic!("[rbatis] you should add rbatis ref param  rb:&Rbatis  or rb: &mut Executor<'_,'_>  on '{}()'!", target_fn.sig.ident);
@@ -41,6 +44,7 @@ pub(crate) fn impl_macro_py_sql(target_fn: &ItemFn, args: &AttributeArgs) -> Tok
                 NestedMeta::Lit(l) => match l {
                     Lit::Str(v) => {
                         s = s + v.value().as_str();
+                        sql_span = v.span();
                     }
                     Lit::ByteStr(_) => {}
                     Lit::Byte(_) => {}
@@ -52,7 +56,11 @@ pub(crate) fn impl_macro_py_sql(target_fn: &ItemFn, args: &AttributeArgs) -> Tok
                 },
             }
         }
-        sql_ident = quote!(#s);
+        schema_track = match schema_check::check_py_sql(&s, sql_span) {
+            Ok(v) => v,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        sql_ident = syn::LitStr::new(&s, sql_span).to_token_stream();
     } else {
         panic!("[rbatis] Incorrect macro parameter length!");
     }
@@ -91,7 +99,7 @@ pub(crate) fn impl_macro_py_sql(target_fn: &ItemFn, args: &AttributeArgs) -> Tok
     }
     let gen_target_method = quote! {
         #[rbatis::rb_py(#sql_ident)]
-        pub fn do_py_sql(arg: &rbs::Value, _tag: char) {}
+        pub fn do_py_sql(arg: &rbs::Value, _tag: char) -> Result<(String, Vec<rbs::Value>), rbatis_codegen::error::Error> {}
     };
     let gen_target_macro_arg = quote! {
         #sql_ident
@@ -103,14 +111,20 @@ pub(crate) fn impl_macro_py_sql(target_fn: &ItemFn, args: &AttributeArgs) -> Tok
     //gen rust code templete
     return quote! {
        pub async fn #func_name_ident #generic(#func_args_stream) -> #return_ty {
+         #schema_track
          let mut rb_arg_map = rbs::value::map::ValueMap::new();
          #sql_args_gen
          #fn_body
          use rbatis::executor::{RbatisRef};
          let driver_type = #rbatis_ident.rbatis_ref().driver_type()?;
+         // the driver type of `${name.quote()}`
+         if rb_arg_map["quote_driver"].is_null() {
+             rb_arg_map.insert("quote_driver".into(), driver_type.into());
+         }
          use rbatis::rbatis_codegen;
          #gen_func
-         let (mut sql,rb_args) = do_py_sql(&rbs::Value::Map(rb_arg_map), '?');
+         let (mut sql,rb_args) = do_py_sql(&rbs::Value::Map(rb_arg_map), '?')
+             .map_err(|e| rbatis::Error::new(rbatis::ErrorKind::SqlRejected, &e).with_source(e))?;
          #call_method
        }
     }
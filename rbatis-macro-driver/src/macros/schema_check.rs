@@ -0,0 +1,168 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use rbatis_codegen::codegen::schema::Schema;
+use syn::parse::{Parse, ParseStream};
+use syn::{Expr, LitStr, Token, Type};
+
+use crate::macros::table_impl::{rbatis_path, to_snake_name};
+
+/// the schema snapshot file is the env `RBATIS_SCHEMA`, or `rbatis_schema.json`.
+/// the relative path is relative to the dir of Cargo.toml
+const SCHEMA_ENV: &str = "RBATIS_SCHEMA";
+const SCHEMA_FILE: &str = "rbatis_schema.json";
+
+/// the parsed schema of (path, modified time)
+type CachedSchema = (PathBuf, Option<SystemTime>, Rc<Schema>);
+
+thread_local! {
+    /// the macros of one crate parse the schema file once
+    static SCHEMA_CACHE: RefCell<Option<CachedSchema>> = const { RefCell::new(None) };
+}
+
+/// load the schema snapshot and the `include_bytes!` of it(so cargo rebuild when the file changed),
+/// None if the `schema_check` feature is disabled
+fn load_schema(span: Span) -> syn::Result<Option<(Rc<Schema>, TokenStream)>> {
+    if !cfg!(feature = "schema_check") {
+        return Ok(None);
+    }
+    let file = std::env::var(SCHEMA_ENV).unwrap_or_else(|_| SCHEMA_FILE.to_string());
+    let mut path = PathBuf::from(&file);
+    if path.is_relative() {
+        if let Ok(dir) = std::env::var("CARGO_MANIFEST_DIR") {
+            path = PathBuf::from(dir).join(file);
+        }
+    }
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+    let schema = SCHEMA_CACHE.with(|cache| -> syn::Result<Rc<Schema>> {
+        let mut cache = cache.borrow_mut();
+        if let Some((p, m, schema)) = cache.as_ref() {
+            if p == &path && m == &modified {
+                return Ok(schema.clone());
+            }
+        }
+        let schema =
+            Rc::new(Schema::load(&path).map_err(|e| syn::Error::new(span, e.to_string()))?);
+        *cache = Some((path.clone(), modified, schema.clone()));
+        Ok(schema)
+    })?;
+    let path = LitStr::new(&path.to_string_lossy(), span);
+    let track = quote! {
+        const _: &[u8] = include_bytes!(#path);
+    };
+    Ok(Some((schema, track)))
+}
+
+/// check the static sql of py_sql, return the tracking include of the schema file
+pub(crate) fn check_py_sql(py_sql: &str, span: Span) -> syn::Result<TokenStream> {
+    match load_schema(span)? {
+        Some((schema, track)) => schema
+            .check_py_sql(py_sql)
+            .map(|_| track)
+            .map_err(|e| syn::Error::new(span, e.to_string())),
+        None => Ok(quote! {}),
+    }
+}
+
+/// check the static sql of html, return the tracking include of the schema file
+pub(crate) fn check_html(html: &str, span: Span) -> syn::Result<TokenStream> {
+    match load_schema(span)? {
+        Some((schema, track)) => schema
+            .check_html(html)
+            .map(|_| track)
+            .map_err(|e| syn::Error::new(span, e.to_string())),
+        None => Ok(quote! {}),
+    }
+}
+
+/// check the table(span) and the columns(name, span) of `#[derive(Table)]`,
+/// return the tracking include of the schema file
+pub(crate) fn check_table(
+    table: &str,
+    span: Span,
+    columns: &[(&str, Span)],
+) -> syn::Result<TokenStream> {
+    let (schema, track) = match load_schema(span)? {
+        Some(v) => v,
+        None => return Ok(quote! {}),
+    };
+    let table_columns = schema.table(table).ok_or_else(|| {
+        syn::Error::new(span, format!("[rbatis] table `{}` not in schema", table))
+    })?;
+    for (column, span) in columns {
+        if !table_columns.contains_key(&column.to_lowercase()) {
+            return Err(syn::Error::new(
+                *span,
+                format!(
+                    "[rbatis] column `{}` not in table `{}` of schema",
+                    column, table
+                ),
+            ));
+        }
+    }
+    Ok(track)
+}
+
+/// the args `Type` or `Type, table_name` of `schema_check_table!`
+pub(crate) struct TableArgs {
+    table: Type,
+    table_name: Option<Expr>,
+}
+
+impl Parse for TableArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let table = input.parse()?;
+        let mut table_name = None;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            table_name = Some(input.parse()?);
+        }
+        Ok(Self { table, table_name })
+    }
+}
+
+/// check the default table name(the snake name of type) of the crud macros.
+/// the type of `#[derive(Table)]` is checked by the derive, and the table name expr is not checked,
+/// so the snake name is checked only if the type is not `Table`(by a const probe).
+pub(crate) fn impl_schema_check_table(args: &TableArgs) -> syn::Result<TokenStream> {
+    if args.table_name.is_some() {
+        return Ok(quote! {});
+    }
+    let mut ty = &args.table;
+    while let Type::Group(g) = ty {
+        ty = &g.elem;
+    }
+    let span = Span::call_site();
+    let (schema, track) = match load_schema(span)? {
+        Some(v) => v,
+        None => return Ok(quote! {}),
+    };
+    let type_name = ty.to_token_stream().to_string().replace(' ', "");
+    let table_name = to_snake_name(&type_name);
+    if schema.table(&table_name).is_some() {
+        return Ok(track);
+    }
+    let message = LitStr::new(
+        &format!("[rbatis] table `{}` not in schema", table_name),
+        span,
+    );
+    let rbatis = rbatis_path();
+    Ok(quote! {
+        #track
+        #[allow(dead_code)]
+        const _: () = {
+            struct SchemaProbe<T>(core::marker::PhantomData<T>);
+            trait NotTable {
+                const CHECK: () = panic!(#message);
+            }
+            impl<T> NotTable for SchemaProbe<T> {}
+            impl<T: #rbatis::table::Table> SchemaProbe<T> {
+                const CHECK: () = ();
+            }
+            SchemaProbe::<#ty>::CHECK
+        };
+    })
+}
@@ -0,0 +1,246 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use crate::macros::schema_check;
+use syn::{Attribute, Data, DeriveInput, Fields, Lit, Meta, NestedMeta};
+
+struct Column {
+    name: String,
+    field: String,
+    skip: bool,
+    pk: bool,
+    auto_increment: bool,
+    insert_default: bool,
+    span: Span,
+}
+
+/// impl rbatis::table::Table for the struct
+pub(crate) fn impl_table(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let mut table_name = to_snake_name(&ident.to_string());
+    let mut table_span = ident.span();
+    let mut quote = true;
+    for meta in parse_attrs(&input.attrs, "table")? {
+        match meta {
+            NestedMeta::Meta(Meta::NameValue(v)) if v.path.is_ident("name") => {
+                table_name = lit_str(&v.lit)?;
+                table_span = v.lit.span();
+            }
+            NestedMeta::Meta(Meta::Path(p)) if p.is_ident("no_quote") => quote = false,
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "[rbatis] unknown table attribute, expected `name = \"...\"` or `no_quote`",
+                ));
+            }
+        }
+    }
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(f) => &f.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "[rbatis] #[derive(Table)] only support the struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "[rbatis] #[derive(Table)] only support struct",
+            ))
+        }
+    };
+    // the key of serialized value is the serde name, renamed by `rename_all` of struct or `rename` of field
+    let mut rename_all = None;
+    for meta in parse_attrs(&input.attrs, "serde")? {
+        if let NestedMeta::Meta(Meta::NameValue(v)) = meta {
+            if v.path.is_ident("rename_all") {
+                rename_all = Some((lit_str(&v.lit)?, v.lit));
+            }
+        }
+    }
+    let mut columns = vec![];
+    for f in fields {
+        let field_ident = f.ident.as_ref().expect("named field");
+        let mut field = field_ident.to_string().trim_start_matches("r#").to_string();
+        if let Some((rule, lit)) = &rename_all {
+            field = rename_field(&field, rule).ok_or_else(|| {
+                syn::Error::new_spanned(
+                    lit,
+                    format!("[rbatis] unknown serde rename_all rule `{}`", rule),
+                )
+            })?;
+        }
+        for meta in parse_attrs(&f.attrs, "serde")? {
+            if let NestedMeta::Meta(Meta::NameValue(v)) = meta {
+                if v.path.is_ident("rename") {
+                    field = lit_str(&v.lit)?;
+                }
+            }
+        }
+        let mut column = Column {
+            name: field.clone(),
+            field,
+            skip: false,
+            pk: false,
+            auto_increment: false,
+            insert_default: false,
+            span: field_ident.span(),
+        };
+        for meta in parse_attrs(&f.attrs, "column")? {
+            match meta {
+                NestedMeta::Meta(Meta::NameValue(v)) if v.path.is_ident("name") => {
+                    column.name = lit_str(&v.lit)?;
+                    column.span = v.lit.span();
+                }
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("skip") => column.skip = true,
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("pk") => column.pk = true,
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("auto_increment") => {
+                    column.auto_increment = true
+                }
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("insert_default") => {
+                    column.insert_default = true
+                }
+                other => {
+                    return Err(syn::Error::new_spanned(
+                        other,
+                        "[rbatis] unknown column attribute, expected `name = \"...\"`, `skip`, `pk`, `auto_increment` or `insert_default`",
+                    ));
+                }
+            }
+        }
+        columns.push(column);
+    }
+    let check_columns = columns
+        .iter()
+        .filter(|c| !c.skip)
+        .map(|c| (c.name.as_str(), c.span))
+        .collect::<Vec<_>>();
+    let schema_track = schema_check::check_table(&table_name, table_span, &check_columns)?;
+    let rbatis = rbatis_path();
+    let columns = columns.iter().map(|c| {
+        let Column {
+            name,
+            field,
+            skip,
+            pk,
+            auto_increment,
+            insert_default,
+            ..
+        } = c;
+        quote! {
+            #rbatis::table::ColumnMeta {
+                name: #name,
+                field: #field,
+                skip: #skip,
+                pk: #pk,
+                auto_increment: #auto_increment,
+                insert_default: #insert_default,
+            }
+        }
+    });
+    let meta_ident = Ident::new(
+        &format!(
+            "{}_TABLE_META",
+            to_snake_name(&ident.to_string()).to_uppercase()
+        ),
+        Span::call_site(),
+    );
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #schema_track
+        impl #impl_generics #rbatis::table::Table for #ident #ty_generics #where_clause {
+            fn table_meta() -> &'static #rbatis::table::TableMeta {
+                static #meta_ident: #rbatis::table::TableMeta = #rbatis::table::TableMeta {
+                    name: #table_name,
+                    columns: &[#(#columns),*],
+                    quote: #quote,
+                };
+                &#meta_ident
+            }
+        }
+    })
+}
+
+/// the path of rbatis crate, resolved at the call site the same as the code of `py_sql`/`html_sql`,
+/// so the crate renamed in Cargo.toml can be used by `use xxx as rbatis;`
+pub(crate) fn rbatis_path() -> Ident {
+    Ident::new("rbatis", Span::call_site())
+}
+
+/// the serde name of the field by the `rename_all` rule, None if the rule is unknown
+fn rename_field(field: &str, rule: &str) -> Option<String> {
+    let pascal = || {
+        field
+            .split('_')
+            .map(|v| {
+                let mut chars = v.chars();
+                match chars.next() {
+                    Some(c) => c.to_ascii_uppercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            })
+            .collect::<String>()
+    };
+    Some(match rule {
+        "lowercase" | "snake_case" => field.to_string(),
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => field.to_ascii_uppercase(),
+        "PascalCase" => pascal(),
+        "camelCase" => {
+            let pascal = pascal();
+            let mut chars = pascal.chars();
+            match chars.next() {
+                Some(c) => c.to_ascii_lowercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        }
+        "kebab-case" => field.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field.to_ascii_uppercase().replace('_', "-"),
+        _ => return None,
+    })
+}
+
+/// the nested meta of `#[name(...)]`
+fn parse_attrs(attrs: &[Attribute], name: &str) -> syn::Result<Vec<NestedMeta>> {
+    let mut metas = vec![];
+    for attr in attrs {
+        if !attr.path.is_ident(name) {
+            continue;
+        }
+        match attr.parse_meta()? {
+            Meta::List(list) => metas.extend(list.nested),
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    format!("[rbatis] expected #[{}(...)]", name),
+                ))
+            }
+        }
+    }
+    Ok(metas)
+}
+
+fn lit_str(lit: &Lit) -> syn::Result<String> {
+    match lit {
+        Lit::Str(s) => Ok(s.value()),
+        _ => Err(syn::Error::new_spanned(lit, "[rbatis] expected string")),
+    }
+}
+
+/// same as rbatis::utils::string_util::to_snake_name
+pub(crate) fn to_snake_name(name: &str) -> String {
+    let len = name.len();
+    let mut new_name = String::with_capacity(name.len());
+    for (index, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if index != 0 && (index + 1) != len {
+                new_name.push('_');
+            }
+            new_name.push(c.to_ascii_lowercase());
+        } else {
+            new_name.push(c);
+        }
+    }
+    new_name
+}
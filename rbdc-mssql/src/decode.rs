@@ -1,3 +1,4 @@
+use crate::error::to_error;
 use chrono::{FixedOffset, NaiveDateTime, Utc};
 use rbdc::datetime::DateTime;
 use rbdc::Error;
@@ -62,7 +63,7 @@ impl Decode for Value {
                             Some(v) => Value::String(v.to_string()).into_ext("Decimal"),
                         },
                         Err(e) => {
-                            return Err(Error::from(e.to_string()));
+                            return Err(to_error(e));
                         }
                     }
                 }
@@ -84,7 +85,7 @@ impl Decode for Value {
                             )),
                         },
                         Err(e) => {
-                            return Err(Error::from(e.to_string()));
+                            return Err(to_error(e));
                         }
                     }
                 }
@@ -102,7 +103,7 @@ impl Decode for Value {
                             )),
                         },
                         Err(e) => {
-                            return Err(Error::from(e.to_string()));
+                            return Err(to_error(e));
                         }
                     }
                 }
@@ -118,7 +119,7 @@ impl Decode for Value {
                             Some(v) => Value::String(v.to_string()).into_ext("Time"),
                         },
                         Err(e) => {
-                            return Err(Error::from(e.to_string()));
+                            return Err(to_error(e));
                         }
                     }
                 }
@@ -134,7 +135,7 @@ impl Decode for Value {
                             Some(v) => Value::String(v.to_string()).into_ext("Date"),
                         },
                         Err(e) => {
-                            return Err(Error::from(e.to_string()));
+                            return Err(to_error(e));
                         }
                     }
                 }
@@ -152,7 +153,7 @@ impl Decode for Value {
                             )),
                         },
                         Err(e) => {
-                            return Err(Error::from(e.to_string()));
+                            return Err(to_error(e));
                         }
                     }
                 }
@@ -170,7 +171,7 @@ impl Decode for Value {
                             )),
                         },
                         Err(e) => {
-                            return Err(Error::from(e.to_string()));
+                            return Err(to_error(e));
                         }
                     }
                 }
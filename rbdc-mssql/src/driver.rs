@@ -1,3 +1,4 @@
+use crate::error::to_error;
 use crate::{MssqlConnectOptions, MssqlConnection};
 use futures_core::future::BoxFuture;
 use rbdc::db::{ConnectOptions, Connection, Driver, Placeholder};
@@ -15,7 +16,7 @@ impl Driver for MssqlDriver {
     fn connect(&self, url: &str) -> BoxFuture<Result<Box<dyn Connection>, Error>> {
         let url = url.to_owned();
         Box::pin(async move {
-            let cfg = Config::from_jdbc_string(&url).map_err(|e| Error::from(e.to_string()))?;
+            let cfg = Config::from_jdbc_string(&url).map_err(to_error)?;
             let conn = MssqlConnection::establish(&cfg).await?;
             Ok(Box::new(conn) as Box<dyn Connection>)
         })
@@ -0,0 +1,87 @@
+use rbdc::{Error, ErrorKind};
+use tiberius::error::{Error as MssqlError, IoErrorKind};
+
+/// error kind of the sql server error number
+pub fn error_kind(code: u32, message: &str) -> ErrorKind {
+    match code {
+        2627 | 2601 => ErrorKind::UniqueViolation,
+        547 => {
+            if message.contains("CHECK") {
+                ErrorKind::CheckViolation
+            } else {
+                ErrorKind::ForeignKeyViolation
+            }
+        }
+        515 => ErrorKind::NotNullViolation,
+        1205 => ErrorKind::Deadlock,
+        1222 => ErrorKind::LockTimeout,
+        2714 | 1913 => ErrorKind::AlreadyExists,
+        _ => ErrorKind::Database,
+    }
+}
+
+/// constraint name parsed from message, for example `Violation of UNIQUE KEY constraint 'UQ_name'.`
+fn constraint(message: &str) -> Option<&str> {
+    let begin = message.find("constraint ")? + "constraint ".len();
+    let quote = message[begin..].chars().next()?;
+    if quote != '\'' && quote != '"' {
+        return None;
+    }
+    let begin = begin + 1;
+    let len = message[begin..].find(quote)?;
+    Some(&message[begin..begin + len])
+}
+
+/// convert tiberius error to rbdc::Error
+pub fn to_error(e: MssqlError) -> Error {
+    match &e {
+        MssqlError::Server(token) => {
+            let kind = error_kind(token.code(), token.message());
+            let mut err = Error::new(kind, &e).with_code(token.code());
+            if kind != ErrorKind::Database {
+                if let Some(name) = constraint(token.message()) {
+                    err = err.with_constraint(name);
+                }
+            }
+            err.with_source(e)
+        }
+        MssqlError::Io { kind, .. } => {
+            let kind = match kind {
+                IoErrorKind::ConnectionReset
+                | IoErrorKind::ConnectionAborted
+                | IoErrorKind::NotConnected
+                | IoErrorKind::BrokenPipe
+                | IoErrorKind::UnexpectedEof => ErrorKind::ConnectionClosed,
+                _ => ErrorKind::Io,
+            };
+            Error::new(kind, &e).with_source(e)
+        }
+        MssqlError::Conversion(_)
+        | MssqlError::Utf8
+        | MssqlError::Utf16
+        | MssqlError::ParseInt(_) => Error::new(ErrorKind::Decode, &e).with_source(e),
+        MssqlError::Protocol(_) | MssqlError::Encoding(_) => {
+            Error::new(ErrorKind::Protocol, &e).with_source(e)
+        }
+        _ => Error::from(e.to_string()),
+    }
+}
+
+#[test]
+fn test_constraint() {
+    assert_eq!(
+        constraint("Violation of UNIQUE KEY constraint 'UQ_name'. Cannot insert duplicate key."),
+        Some("UQ_name")
+    );
+    assert_eq!(
+        constraint("The INSERT statement conflicted with the FOREIGN KEY constraint \"FK_user\"."),
+        Some("FK_user")
+    );
+    assert_eq!(
+        error_kind(
+            547,
+            "The INSERT statement conflicted with the CHECK constraint \"CK_age\"."
+        ),
+        ErrorKind::CheckViolation
+    );
+}
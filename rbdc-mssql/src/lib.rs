@@ -3,10 +3,12 @@ pub extern crate tiberius;
 pub mod decode;
 pub mod driver;
 pub mod encode;
+pub mod error;
 
 use crate::decode::Decode;
 use crate::driver::MssqlDriver;
 use crate::encode::Encode;
+use crate::error::to_error;
 use futures_core::future::BoxFuture;
 use futures_core::Stream;
 use rbdc::db::{ConnectOptions, Connection, ExecResult, MetaData, Placeholder, Row};
@@ -26,13 +28,11 @@ impl MssqlConnection {
     /// let cfg = Config::from_jdbc_string(url).map_err(|e| Error::from(e.to_owned()))?;
     pub async fn establish(cfg: &Config) -> Result<Self, Error> {
         // let cfg = Config::from_jdbc_string(url).map_err(|e| Error::from(e.to_owned()))?;
-        let tcp = TcpStream::connect(cfg.get_addr())
-            .await
-            .map_err(|e| Error::from(e.to_string()))?;
+        let tcp = TcpStream::connect(cfg.get_addr()).await?;
         tcp.set_nodelay(true).unwrap();
         let c = Client::connect(cfg.clone(), tcp.compat_write())
             .await
-            .map_err(|e| Error::from(e.to_string()))?;
+            .map_err(to_error)?;
         Ok(Self { inner: c })
     }
 }
@@ -43,17 +43,13 @@ pub struct MssqlConnectOptions(pub Config);
 impl ConnectOptions for MssqlConnectOptions {
     fn connect(&self) -> BoxFuture<Result<Box<dyn Connection>, Error>> {
         Box::pin(async move {
-            let v = MssqlConnection::establish(&self.0)
-                .await
-                .map_err(|e| Error::from(e.to_string()))?;
+            let v = MssqlConnection::establish(&self.0).await?;
             Ok(Box::new(v) as Box<dyn Connection>)
         })
     }
 
     fn set_uri(&mut self, url: &str) -> Result<(), Error> {
-        *self = MssqlConnectOptions(
-            Config::from_jdbc_string(url).map_err(|e| Error::from(e.to_string()))?,
-        );
+        *self = MssqlConnectOptions(Config::from_jdbc_string(url).map_err(to_error)?);
         Ok(())
     }
 
@@ -107,15 +103,9 @@ impl Connection for MssqlConnection {
             for x in params {
                 x.encode(&mut q)?;
             }
-            let v = q
-                .query(&mut self.inner)
-                .await
-                .map_err(|e| Error::from(e.to_string()))?;
+            let v = q.query(&mut self.inner).await.map_err(to_error)?;
             let mut results = Vec::with_capacity(v.size_hint().0);
-            let s = v
-                .into_results()
-                .await
-                .map_err(|e| Error::from(e.to_string()))?;
+            let s = v.into_results().await.map_err(to_error)?;
             for item in s {
                 for r in item {
                     let mut columns = Vec::with_capacity(r.columns().len());
@@ -148,10 +138,7 @@ impl Connection for MssqlConnection {
             for x in params {
                 x.encode(&mut q)?;
             }
-            let v = q
-                .execute(&mut self.inner)
-                .await
-                .map_err(|e| Error::from(e.to_string()))?;
+            let v = q.execute(&mut self.inner).await.map_err(to_error)?;
             Ok(ExecResult {
                 rows_affected: {
                     let mut rows_affected = 0;
@@ -172,10 +159,7 @@ impl Connection for MssqlConnection {
     fn ping(&mut self) -> BoxFuture<Result<(), rbdc::Error>> {
         //TODO While 'select 1' can temporarily solve the problem of checking that the connection is valid, it looks ugly.Better replace it with something better way
         Box::pin(async move {
-            self.inner
-                .query("select 1", &[])
-                .await
-                .map_err(|e| Error::from(e.to_string()))?;
+            self.inner.query("select 1", &[]).await.map_err(to_error)?;
             Ok(())
         })
     }
@@ -7,7 +7,7 @@ use futures_core::stream::BoxStream;
 use futures_util::{FutureExt, StreamExt, TryStreamExt};
 use rbdc::common::StatementCache;
 use rbdc::db::{Connection, ExecResult, Row};
-use rbdc::Error;
+use rbdc::{try_stream, Error};
 use rbs::Value;
 use std::fmt::{self, Debug, Formatter};
 use std::ops::{Deref, DerefMut};
@@ -148,6 +148,38 @@ impl Connection for MySqlConnection {
         })
     }
 
+    fn get_rows_stream(
+        &mut self,
+        sql: &str,
+        params: Vec<Value>,
+    ) -> BoxStream<'_, Result<Box<dyn Row>, Error>> {
+        let sql = sql.to_owned();
+        Box::pin(try_stream! {
+            let mut many = {
+                if params.is_empty() {
+                    self.fetch_many(MysqlQuery {
+                        statement: Either::Left(sql),
+                        arguments: params,
+                        persistent: false,
+                    })
+                } else {
+                    let stmt = self.prepare_with(&sql, &[]).await?;
+                    self.fetch_many(MysqlQuery {
+                        statement: Either::Right(stmt),
+                        arguments: params,
+                        persistent: true,
+                    })
+                }
+            };
+            while let Some(step) = many.try_next().await? {
+                if let Either::Right(row) = step {
+                    r#yield!(Box::new(row) as Box<dyn Row>);
+                }
+            }
+            Ok(())
+        })
+    }
+
     fn exec(&mut self, sql: &str, params: Vec<Value>) -> BoxFuture<Result<ExecResult, Error>> {
         let sql = sql.to_owned();
         Box::pin(async move {
@@ -1,4 +1,5 @@
 use crate::protocol::response::ErrPacket;
+use rbdc::ErrorKind;
 use std::error::Error;
 use std::fmt::{self, Debug, Display, Formatter};
 
@@ -65,8 +66,66 @@ impl MySqlDatabaseError {
     }
 }
 
+impl MySqlDatabaseError {
+    /// error kind of the error number, or SQLSTATE if the number is unknown
+    pub fn kind(&self) -> ErrorKind {
+        match self.number() {
+            1062 | 1586 => ErrorKind::UniqueViolation,
+            1216 | 1217 | 1451 | 1452 => ErrorKind::ForeignKeyViolation,
+            1048 | 1364 => ErrorKind::NotNullViolation,
+            3819 => ErrorKind::CheckViolation,
+            1213 => ErrorKind::Deadlock,
+            1205 => ErrorKind::LockTimeout,
+            1050 | 1061 => ErrorKind::AlreadyExists,
+            _ => match self.code() {
+                Some(code) => ErrorKind::from_sql_state(code),
+                None => ErrorKind::Database,
+            },
+        }
+    }
+
+    /// constraint name parsed from the message of unique or foreign key violation
+    pub fn constraint(&self) -> Option<&str> {
+        let (start, end) = match self.kind() {
+            ErrorKind::UniqueViolation => ("for key '", '\''),
+            ErrorKind::ForeignKeyViolation => ("CONSTRAINT `", '`'),
+            _ => return None,
+        };
+        let message = self.message();
+        let begin = message.find(start)? + start.len();
+        let len = message[begin..].find(end)?;
+        Some(&message[begin..begin + len])
+    }
+}
+
 impl From<MySqlDatabaseError> for rbdc::Error {
     fn from(arg: MySqlDatabaseError) -> Self {
-        rbdc::Error::E(arg.to_string())
+        let mut err = rbdc::Error::new(arg.kind(), &arg).with_code(arg.number());
+        if let Some(constraint) = arg.constraint() {
+            err = err.with_constraint(constraint);
+        }
+        err.with_source(arg)
     }
 }
+
+#[test]
+fn test_error_kind_constraint() {
+    let e = MySqlDatabaseError(ErrPacket {
+        error_code: 1062,
+        sql_state: Some("23000".to_string()),
+        error_message: "Duplicate entry '1' for key 'biz_activity.PRIMARY'".to_string(),
+    });
+    let e = rbdc::Error::from(e);
+    assert_eq!(e.kind(), ErrorKind::UniqueViolation);
+    assert_eq!(e.code(), Some("1062"));
+    assert_eq!(e.constraint(), Some("biz_activity.PRIMARY"));
+
+    let e = MySqlDatabaseError(ErrPacket {
+        error_code: 1452,
+        sql_state: Some("23000".to_string()),
+        error_message: "Cannot add or update a child row: a foreign key constraint fails (`test`.`orders`, CONSTRAINT `fk_user` FOREIGN KEY (`user_id`) REFERENCES `user` (`id`))".to_string(),
+    });
+    let e = rbdc::Error::from(e);
+    assert_eq!(e.kind(), ErrorKind::ForeignKeyViolation);
+    assert_eq!(e.constraint(), Some("fk_user"));
+}
@@ -16,6 +16,7 @@ use rbdc::common::StatementCache;
 use rbdc::db::{Connection, ExecResult, Placeholder, Row};
 use rbdc::ext::ustr::UStr;
 use rbdc::io::Decode;
+use rbdc::try_stream;
 use rbdc::Error;
 use rbs::Value;
 use std::collections::HashMap;
@@ -229,6 +230,42 @@ impl Connection for PgConnection {
         })
     }
 
+    fn get_rows_stream(
+        &mut self,
+        sql: &str,
+        params: Vec<Value>,
+    ) -> BoxStream<'_, Result<Box<dyn Row>, Error>> {
+        let sql = PgDriver {}.exchange(sql);
+        Box::pin(try_stream! {
+            let mut many = {
+                if params.is_empty() {
+                    self.fetch_many(PgQuery {
+                        statement: Either::Left(sql),
+                        arguments: params,
+                        persistent: false,
+                    })
+                } else {
+                    let mut types = Vec::with_capacity(params.len());
+                    for x in &params {
+                        types.push(x.type_info());
+                    }
+                    let stmt = self.prepare_with(sql, &types).await?;
+                    self.fetch_many(PgQuery {
+                        statement: Either::Right(stmt),
+                        arguments: params,
+                        persistent: true,
+                    })
+                }
+            };
+            while let Some(step) = many.try_next().await? {
+                if let Either::Right(row) = step {
+                    r#yield!(Box::new(row) as Box<dyn Row>);
+                }
+            }
+            Ok(())
+        })
+    }
+
     fn exec(&mut self, sql: &str, params: Vec<Value>) -> BoxFuture<Result<ExecResult, Error>> {
         let sql = PgDriver {}.exchange(sql);
         Box::pin(async move {
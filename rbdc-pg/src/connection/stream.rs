@@ -8,6 +8,7 @@ use futures_util::SinkExt;
 use log::Level;
 use rbdc::err_protocol;
 
+use crate::error::PgDatabaseError;
 use crate::message::*;
 use crate::options::PgConnectOptions;
 use rbdc::error::Error;
@@ -100,7 +101,7 @@ impl PgStream {
             match message.format {
                 MessageFormat::ErrorResponse => {
                     // An error returned from the database server.
-                    return Err(Error::E(format!("db:{:?}", message.decode::<Notice>()?)));
+                    return Err(PgDatabaseError(message.decode::<Notice>()?).into());
                 }
 
                 MessageFormat::NotificationResponse => {
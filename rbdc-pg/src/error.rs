@@ -2,6 +2,7 @@ use std::error::Error;
 use std::fmt::{self, Debug, Display, Formatter};
 
 use atoi::atoi;
+use rbdc::ErrorKind;
 
 use crate::message::{Notice, PgSeverity};
 
@@ -158,3 +159,17 @@ impl Display for PgDatabaseError {
 }
 
 impl Error for PgDatabaseError {}
+
+impl From<PgDatabaseError> for rbdc::Error {
+    fn from(e: PgDatabaseError) -> Self {
+        let mut err = rbdc::Error::new(
+            ErrorKind::from_sql_state(e.code()),
+            format!("db: {} (SQLSTATE {})", e.message(), e.code()),
+        )
+        .with_code(e.code());
+        if let Some(constraint) = e.constraint() {
+            err = err.with_constraint(constraint);
+        }
+        err.with_source(e)
+    }
+}
@@ -4,7 +4,12 @@ use std::fmt::{self, Display, Formatter};
 use std::os::raw::c_int;
 use std::{borrow::Cow, str::from_utf8_unchecked};
 
-use libsqlite3_sys::{sqlite3, sqlite3_errmsg, sqlite3_extended_errcode};
+use libsqlite3_sys::{
+    sqlite3, sqlite3_errmsg, sqlite3_extended_errcode, SQLITE_BUSY, SQLITE_CONSTRAINT_CHECK,
+    SQLITE_CONSTRAINT_FOREIGNKEY, SQLITE_CONSTRAINT_NOTNULL, SQLITE_CONSTRAINT_PRIMARYKEY,
+    SQLITE_CONSTRAINT_UNIQUE, SQLITE_LOCKED,
+};
+use rbdc::ErrorKind;
 
 // Error Codes And Messages
 // https://www.sqlite.org/c3ref/errcode.html
@@ -71,8 +76,34 @@ impl SqliteError {
     }
 }
 
+impl SqliteError {
+    /// error kind of the extended result code
+    pub fn kind(&self) -> ErrorKind {
+        match self.code {
+            SQLITE_CONSTRAINT_UNIQUE | SQLITE_CONSTRAINT_PRIMARYKEY => ErrorKind::UniqueViolation,
+            SQLITE_CONSTRAINT_FOREIGNKEY => ErrorKind::ForeignKeyViolation,
+            SQLITE_CONSTRAINT_NOTNULL => ErrorKind::NotNullViolation,
+            SQLITE_CONSTRAINT_CHECK => ErrorKind::CheckViolation,
+            _ => match self.code & 0xff {
+                SQLITE_BUSY | SQLITE_LOCKED => ErrorKind::LockTimeout,
+                _ => {
+                    if self.message.contains("already exists")
+                        || self.message.contains("duplicate column")
+                    {
+                        ErrorKind::AlreadyExists
+                    } else {
+                        ErrorKind::Database
+                    }
+                }
+            },
+        }
+    }
+}
+
 impl From<SqliteError> for rbdc::Error {
     fn from(e: SqliteError) -> Self {
-        Self::from(e.to_string())
+        rbdc::Error::new(e.kind(), &e)
+            .with_code(e.code)
+            .with_source(e)
     }
 }
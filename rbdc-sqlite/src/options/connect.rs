@@ -8,6 +8,7 @@ use futures_util::FutureExt;
 use futures_util::{StreamExt, TryStreamExt};
 use rbdc::db::{Connection, ExecResult, Row};
 use rbdc::error::Error;
+use rbdc::try_stream;
 use rbs::Value;
 use std::fmt::Write;
 
@@ -91,6 +92,38 @@ impl Connection for SqliteConnection {
         })
     }
 
+    fn get_rows_stream(
+        &mut self,
+        sql: &str,
+        params: Vec<Value>,
+    ) -> BoxStream<'_, Result<Box<dyn Row>, Error>> {
+        let sql = sql.to_owned();
+        Box::pin(try_stream! {
+            let mut many = {
+                if params.is_empty() {
+                    self.fetch_many(SqliteQuery {
+                        statement: Either::Left(sql),
+                        arguments: params,
+                        persistent: false,
+                    })
+                } else {
+                    let stmt = self.prepare_with(&sql, &[]).await?;
+                    self.fetch_many(SqliteQuery {
+                        statement: Either::Right(stmt),
+                        arguments: params,
+                        persistent: true,
+                    })
+                }
+            };
+            while let Some(step) = many.try_next().await? {
+                if let Either::Right(row) = step {
+                    r#yield!(Box::new(row) as Box<dyn Row>);
+                }
+            }
+            Ok(())
+        })
+    }
+
     fn exec(&mut self, sql: &str, params: Vec<Value>) -> BoxFuture<Result<ExecResult, Error>> {
         let sql = sql.to_owned();
         Box::pin(async move {
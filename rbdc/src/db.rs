@@ -1,5 +1,7 @@
 use crate::Error;
 use futures_core::future::BoxFuture;
+use futures_core::stream::BoxStream;
+use futures_util::{StreamExt, TryStreamExt};
 use rbs::value::map::ValueMap;
 use rbs::Value;
 use std::any::Any;
@@ -60,19 +62,39 @@ pub trait Connection: Send {
             let v = v.await?;
             let mut rows = Vec::with_capacity(v.len());
             for mut x in v {
-                let md = x.meta_data();
-                let mut m = ValueMap::with_capacity(md.column_len());
-                for mut i in 0..md.column_len() {
-                    i = md.column_len() - i - 1;
-                    let n = md.column_name(i);
-                    m.insert(Value::String(n), x.get(i)?);
-                }
-                rows.push(Value::Map(m));
+                rows.push(row_to_value(&mut x)?);
             }
             Ok(rows)
         })
     }
 
+    /// Execute a query that is expected to return a result set, yielding rows one by one
+    /// as they are read instead of collecting the whole result set into memory.
+    ///
+    /// the default impl is based on `get_rows`,drivers should override it to stream natively
+    fn get_rows_stream(
+        &mut self,
+        sql: &str,
+        params: Vec<Value>,
+    ) -> BoxStream<'_, Result<Box<dyn Row>, Error>> {
+        let v = self.get_rows(sql, params);
+        futures_util::stream::once(v)
+            .map_ok(|rows| futures_util::stream::iter(rows.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
+
+    /// Execute a query that is expected to return a result set, yielding rows one by one as `Value::Map`
+    fn get_values_stream(
+        &mut self,
+        sql: &str,
+        params: Vec<Value>,
+    ) -> BoxStream<'_, Result<Value, Error>> {
+        self.get_rows_stream(sql, params)
+            .and_then(|mut row| async move { row_to_value(&mut row) })
+            .boxed()
+    }
+
     /// Execute a query that is expected to update some rows.
     fn exec(&mut self, sql: &str, params: Vec<Value>) -> BoxFuture<Result<ExecResult, Error>>;
 
@@ -83,6 +105,18 @@ pub trait Connection: Send {
     fn ping(&mut self) -> BoxFuture<Result<(), Error>>;
 }
 
+/// convert a row into `Value::Map`,the key is column name
+pub fn row_to_value(row: &mut Box<dyn Row>) -> Result<Value, Error> {
+    let md = row.meta_data();
+    let mut m = ValueMap::with_capacity(md.column_len());
+    for mut i in 0..md.column_len() {
+        i = md.column_len() - i - 1;
+        let n = md.column_name(i);
+        m.insert(Value::String(n), row.get(i)?);
+    }
+    Ok(Value::Map(m))
+}
+
 /// Result set from executing a query against a statement
 pub trait Row: 'static + Send + Debug {
     /// get meta data about this result set
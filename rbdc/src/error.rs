@@ -1,18 +1,167 @@
-use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::fmt::{Debug, Display, Formatter};
 use std::num::{ParseFloatError, ParseIntError, TryFromIntError};
 use std::str::Utf8Error;
-use serde::{Serialize,Deserialize};
 
-#[derive(Debug,Serialize,Deserialize)]
+/// the variants maybe added in the future, so match it with `_`(or use `kind()`/`code()`)
+#[derive(Debug, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum Error {
-    E(String)
+    E(String),
+    /// error with kind, database native code and constraint name
+    Detail(ErrorDetail),
+}
+
+/// the kind of error. drivers map the database native error code to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// unclassified error
+    Other,
+    UniqueViolation,
+    ForeignKeyViolation,
+    NotNullViolation,
+    CheckViolation,
+    /// deadlock detected
+    Deadlock,
+    /// could not serialize access due to concurrent update
+    SerializationFailure,
+    /// lock wait timeout or database is busy
+    LockTimeout,
+    /// table,index or other object already exists
+    AlreadyExists,
+    /// other error returned by the database
+    Database,
+    /// wait a connection from pool timeout
+    PoolTimeout,
+    /// the pool is closed
+    PoolClosed,
+    /// connection closed or reset by peer
+    ConnectionClosed,
+    Io,
+    /// decode value fail
+    Decode,
+    /// encode(serialize) value fail
+    Encode,
+    /// unexpected or invalid data received from database
+    Protocol,
+    /// the row is changed by others(optimistic lock version mismatch)
+    ConcurrencyConflict,
+    /// the sql is rejected before execute, for example the `${}` value is rejected by the sql policy
+    SqlRejected,
+}
+
+impl ErrorKind {
+    /// kind from the standard SQLSTATE code, for example postgres `23505`
+    pub fn from_sql_state(code: &str) -> Self {
+        match code {
+            "23505" => ErrorKind::UniqueViolation,
+            "23503" => ErrorKind::ForeignKeyViolation,
+            "23502" => ErrorKind::NotNullViolation,
+            "23514" => ErrorKind::CheckViolation,
+            "40001" => ErrorKind::SerializationFailure,
+            "40P01" => ErrorKind::Deadlock,
+            "55P03" => ErrorKind::LockTimeout,
+            "42P07" | "42710" => ErrorKind::AlreadyExists,
+            _ => {
+                if code.starts_with("08") {
+                    ErrorKind::ConnectionClosed
+                } else {
+                    ErrorKind::Database
+                }
+            }
+        }
+    }
+}
+
+/// structured error
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorDetail {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// database native error code or SQLSTATE.
+    /// for example: postgres `23505`, mysql `1062`, sqlite `2067`, mssql `2627`
+    pub code: Option<String>,
+    /// constraint name, if the database report it
+    pub constraint: Option<String>,
+    #[serde(skip)]
+    pub source: Option<Box<dyn std::error::Error + Send + Sync>>,
 }
 
 impl Error {
+    pub fn new(kind: ErrorKind, message: impl Display) -> Self {
+        Error::Detail(ErrorDetail {
+            kind,
+            message: message.to_string(),
+            code: None,
+            constraint: None,
+            source: None,
+        })
+    }
+
     #[allow(dead_code)]
     #[inline]
     pub fn protocol(err: impl Display) -> Self {
-        Error::E(err.to_string())
+        Error::new(ErrorKind::Protocol, err)
+    }
+
+    /// set database native error code
+    pub fn with_code(self, code: impl Display) -> Self {
+        let mut detail = self.into_detail();
+        detail.code = Some(code.to_string());
+        Error::Detail(detail)
+    }
+
+    /// set constraint name
+    pub fn with_constraint(self, constraint: impl Display) -> Self {
+        let mut detail = self.into_detail();
+        detail.constraint = Some(constraint.to_string());
+        Error::Detail(detail)
+    }
+
+    /// set the source error
+    pub fn with_source(self, source: impl std::error::Error + Send + Sync + 'static) -> Self {
+        let mut detail = self.into_detail();
+        detail.source = Some(Box::new(source));
+        Error::Detail(detail)
+    }
+
+    /// error kind, `Error::E` is `ErrorKind::Other`
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::E(_) => ErrorKind::Other,
+            Error::Detail(d) => d.kind,
+        }
+    }
+
+    /// database native error code or SQLSTATE
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Error::E(_) => None,
+            Error::Detail(d) => d.code.as_deref(),
+        }
+    }
+
+    /// constraint name
+    pub fn constraint(&self) -> Option<&str> {
+        match self {
+            Error::E(_) => None,
+            Error::Detail(d) => d.constraint.as_deref(),
+        }
+    }
+
+    fn into_detail(self) -> ErrorDetail {
+        match self {
+            Error::E(message) => ErrorDetail {
+                kind: ErrorKind::Other,
+                message,
+                code: None,
+                constraint: None,
+                source: None,
+            },
+            Error::Detail(d) => d,
+        }
     }
 }
 
@@ -20,24 +169,49 @@ impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::E(e) => std::fmt::Display::fmt(&e, f),
+            Error::Detail(d) => match &d.code {
+                // the message of some drivers already contains the code
+                Some(code) if !d.message.contains(code.as_str()) => {
+                    write!(f, "{} (code: {})", d.message, code)
+                }
+                _ => std::fmt::Display::fmt(&d.message, f),
+            },
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::E(_) => None,
+            Error::Detail(d) => match &d.source {
+                None => None,
+                Some(e) => Some(e.as_ref()),
+            },
+        }
+    }
+}
 
 impl serde::ser::Error for Error {
     fn custom<T>(msg: T) -> Self
     where
         T: Display,
     {
-        Self::E(msg.to_string())
+        Error::new(ErrorKind::Encode, msg)
     }
 }
 
 impl From<std::io::Error> for Error {
     fn from(arg: std::io::Error) -> Self {
-        Error::E(arg.to_string())
+        let kind = match arg.kind() {
+            std::io::ErrorKind::ConnectionReset
+            | std::io::ErrorKind::ConnectionAborted
+            | std::io::ErrorKind::NotConnected
+            | std::io::ErrorKind::BrokenPipe
+            | std::io::ErrorKind::UnexpectedEof => ErrorKind::ConnectionClosed,
+            _ => ErrorKind::Io,
+        };
+        Error::new(kind, &arg).with_source(arg)
     }
 }
 
@@ -50,7 +224,7 @@ impl From<native_tls::Error> for Error {
 
 impl From<std::str::Utf8Error> for Error {
     fn from(e: Utf8Error) -> Self {
-        Error::E(e.to_string())
+        Error::new(ErrorKind::Decode, e).with_source(e)
     }
 }
 
@@ -67,37 +241,53 @@ impl From<String> for Error {
 
 impl From<ParseIntError> for Error {
     fn from(arg: ParseIntError) -> Self {
-        Error::E(arg.to_string())
+        Error::new(ErrorKind::Decode, &arg).with_source(arg)
     }
 }
 
 impl From<ParseFloatError> for Error {
     fn from(arg: ParseFloatError) -> Self {
-        Error::E(arg.to_string())
+        Error::new(ErrorKind::Decode, &arg).with_source(arg)
     }
 }
 
 impl From<fastdate::error::Error> for Error {
     fn from(arg: fastdate::error::Error) -> Self {
-        Error::E(arg.to_string())
+        Error::new(ErrorKind::Decode, arg)
     }
 }
 
 impl From<TryFromIntError> for Error {
     fn from(e: TryFromIntError) -> Self {
-        Error::from(e.to_string())
+        Error::new(ErrorKind::Decode, e).with_source(e)
     }
 }
 
 impl From<rbs::Error> for Error {
     fn from(arg: rbs::Error) -> Self {
-        Error::from(arg.to_string())
+        Error::new(ErrorKind::Decode, arg)
     }
 }
 
-impl<E: std::fmt::Debug> From<deadpool::managed::PoolError<E>> for Error {
+impl<E: std::fmt::Debug + 'static> From<deadpool::managed::PoolError<E>> for Error {
     fn from(arg: deadpool::managed::PoolError<E>) -> Self {
-        Error::from(format!("{:?}", arg))
+        match arg {
+            deadpool::managed::PoolError::Backend(e) => {
+                // the backend error of `rbdc::pool` is `Error`, keep its kind and code
+                let message = format!("Backend({:?})", e);
+                match (Box::new(e) as Box<dyn Any>).downcast::<Error>() {
+                    Ok(e) => *e,
+                    Err(_) => Error::from(message),
+                }
+            }
+            deadpool::managed::PoolError::Timeout(_) => {
+                Error::new(ErrorKind::PoolTimeout, format!("{:?}", arg))
+            }
+            deadpool::managed::PoolError::Closed => {
+                Error::new(ErrorKind::PoolClosed, format!("{:?}", arg))
+            }
+            _ => Error::from(format!("{:?}", arg)),
+        }
     }
 }
 
@@ -105,10 +295,10 @@ impl<E: std::fmt::Debug> From<deadpool::managed::PoolError<E>> for Error {
 #[macro_export]
 macro_rules! err_protocol {
     ($expr:expr) => {
-        $crate::Error::E($expr.into())
+        $crate::Error::protocol($expr)
     };
 
     ($fmt:expr, $($arg:tt)*) => {
-        $crate::Error::E(format!($fmt, $($arg)*))
+        $crate::Error::protocol(format!($fmt, $($arg)*))
     };
 }
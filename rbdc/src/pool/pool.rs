@@ -6,6 +6,7 @@ use deadpool::managed::{
 };
 use deadpool::Status;
 use futures_core::future::BoxFuture;
+use futures_core::stream::BoxStream;
 use rbs::Value;
 use std::fmt::{Debug, Formatter};
 use std::future::Future;
@@ -292,6 +293,14 @@ impl Connection for Object<ManagerPorxy> {
         self.deref_mut().get_rows(sql, params)
     }
 
+    fn get_rows_stream(
+        &mut self,
+        sql: &str,
+        params: Vec<Value>,
+    ) -> BoxStream<'_, Result<Box<dyn Row>, Error>> {
+        self.deref_mut().get_rows_stream(sql, params)
+    }
+
     fn exec(&mut self, sql: &str, params: Vec<Value>) -> BoxFuture<Result<ExecResult, Error>> {
         self.deref_mut().exec(sql, params)
     }
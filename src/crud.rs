@@ -1,11 +1,31 @@
 ///PySql: gen select*,update*,insert*,delete* ... methods
+///
+/// with the primary keys, also gen select_by_id,select_by_ids,update_by_id,delete_by_id(see `impl_by_id!`)
+/// ```rust
+/// #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+/// pub struct BizOrder{ pub tenant_id: Option<String>, pub order_no: Option<String> }
+/// rbatis::crud!(BizOrder{}, ["tenant_id", "order_no"]);
+/// ```
 #[macro_export]
 macro_rules! crud {
     ($table:ty{}) => {
-        $crate::impl_insert!($table {});
-        $crate::impl_select!($table {});
-        $crate::impl_update!($table {});
-        $crate::impl_delete!($table {});
+        $crate::schema_check_table!($table);
+        $crate::crud!(
+            $table {},
+            $crate::table::table_name($crate::table_meta!($table), stringify!($table))
+        );
+    };
+    ($table:ty{},[$($pk:expr),+ $(,)?]) => {
+        $crate::crud!($table {});
+        $crate::impl_by_id!(
+            $table {},
+            [$($pk),+],
+            $crate::table::table_name($crate::table_meta!($table), stringify!($table))
+        );
+    };
+    ($table:ty{},[$($pk:expr),+ $(,)?],$table_name:expr) => {
+        $crate::crud!($table {}, $table_name);
+        $crate::impl_by_id!($table {}, [$($pk),+], $table_name);
     };
     ($table:ty{},$table_name:expr) => {
         $crate::impl_insert!($table {}, $table_name);
@@ -27,9 +47,10 @@ macro_rules! crud {
 #[macro_export]
 macro_rules! impl_insert {
     ($table:ty{}) => {
+        $crate::schema_check_table!($table);
         $crate::impl_insert!(
             $table {},
-            $crate::utils::string_util::to_snake_name(stringify!($table))
+            $crate::table::table_name($crate::table_meta!($table), stringify!($table))
         );
     };
     ($table:ty{},$table_name:expr) => {
@@ -40,30 +61,27 @@ macro_rules! impl_insert {
                 batch_size: u64,
             ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
                 #[$crate::py_sql(
-                    "`insert into ${table_name} `
+                    "`insert into ${table_name.quote()} `
                     trim ',':
                      for idx,table in tables:
                       if idx == 0:
                          `(`
                          trim ',':
                            for k,v in table:
-                              if k == 'id' && v== null:
-                                 continue:
-                              ${k},
+                              ${k.quote()},
                          `) VALUES `
                       (
                       trim ',':
                        for k,v in table:
-                         if k == 'id' && v== null:
-                            continue:
                          #{v},
                       ),
                     "
                 )]
                 async fn insert_batch(
                     rb: &mut dyn $crate::executor::Executor,
-                    tables: &[$table],
+                    tables: &[rbs::Value],
                     table_name: &str,
+                    quote_driver: &str,
                 ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error>
                 {
                     impled!()
@@ -74,18 +92,22 @@ macro_rules! impl_insert {
                     ));
                 }
                 let table_name = $table_name.to_string();
+                let driver_type = rb.driver_type()?.to_string();
+                let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
                 let mut result = $crate::rbdc::db::ExecResult {
                     rows_affected: 0,
                     last_insert_id: rbs::Value::Null,
                 };
                 let ranges = $crate::sql::Page::<()>::make_ranges(tables.len() as u64, batch_size);
                 for (offset, limit) in ranges {
-                    let exec_result = insert_batch(
-                        rb,
-                        &tables[offset as usize..limit as usize],
-                        table_name.as_str(),
-                    )
-                    .await?;
+                    let rows = $crate::table::insert_rows(
+                        $crate::table_meta!($table),
+                        tables[offset as usize..limit as usize]
+                            .iter()
+                            .map(|v| rbs::to_value!(v))
+                            .collect(),
+                    )?;
+                    let exec_result = insert_batch(rb, &rows, table_name.as_str(), quote_driver).await?;
                     result.rows_affected += exec_result.rows_affected;
                     result.last_insert_id = exec_result.last_insert_id;
                 }
@@ -96,7 +118,146 @@ macro_rules! impl_insert {
                 rb: &mut dyn $crate::executor::Executor,
                 table: &$table,
             ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
-                <$table>::insert_batch(rb, &[table.clone()], 1).await
+                <$table>::insert_batch(rb, std::slice::from_ref(table), 1).await
+            }
+
+            /// insert and return the keys of all rows, the generated keys are written back into the tables without key.
+            /// the key is the `auto_increment` column of `#[derive(Table)]`, or `id`.
+            /// see `rbatis::sql::returning::Returning` for the sql of every driver
+            pub async fn insert_batch_returning(
+                rb: &mut dyn $crate::executor::Executor,
+                tables: &mut [$table],
+                batch_size: u64,
+            ) -> std::result::Result<Vec<rbs::Value>, $crate::rbdc::Error> {
+                #[$crate::py_sql(
+                    "`insert into ${table_name.quote()} `
+                    trim ',':
+                     for idx,table in tables:
+                      if idx == 0:
+                         `(`
+                         trim ',':
+                           for k,v in table:
+                              ${k.quote()},
+                         `) ${output.raw()}VALUES `
+                      (
+                      trim ',':
+                       for k,v in table:
+                         #{v},
+                      ),
+                    `${returning.raw()}`
+                    "
+                )]
+                async fn insert_batch_returning(
+                    rb: &mut dyn $crate::executor::Executor,
+                    tables: &[rbs::Value],
+                    table_name: &str,
+                    quote_driver: &str,
+                    output: &str,
+                    returning: &str,
+                ) -> std::result::Result<Vec<rbs::Value>, $crate::rbdc::Error>
+                {
+                    impled!()
+                }
+                if tables.is_empty() {
+                    return Err($crate::rbdc::Error::from(
+                        "insert can not insert empty array tables!",
+                    ));
+                }
+                let table_name = $table_name.to_string();
+                let driver_type = rb.driver_type()?.to_string();
+                let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                let (key, field) = $crate::table::generated_key($crate::table_meta!($table));
+                let returning = $crate::sql::returning::Returning::new(
+                    &driver_type,
+                    &$crate::rbatis_codegen::identifier::quote_identifier(quote_driver, key),
+                )?;
+                let mut ids = Vec::with_capacity(tables.len());
+                let ranges = $crate::sql::Page::<()>::make_ranges(tables.len() as u64, batch_size);
+                for (offset, limit) in ranges {
+                    let chunk = &mut tables[offset as usize..limit as usize];
+                    let keys: Vec<rbs::Value> = chunk
+                        .iter()
+                        .map(|v| rbs::to_value!(v)[field].clone())
+                        .collect();
+                    let chunk_ids = match &returning {
+                        $crate::sql::returning::Returning::Query { output, returning } => {
+                            let rows = $crate::table::insert_rows(
+                                $crate::table_meta!($table),
+                                chunk.iter().map(|v| rbs::to_value!(v)).collect(),
+                            )?;
+                            insert_batch_returning(rb, &rows, &table_name, quote_driver, output, returning)
+                                .await?
+                                .into_iter()
+                                .map(|row| row[key].clone())
+                                .collect()
+                        }
+                        $crate::sql::returning::Returning::LastInsertId => {
+                            let exec_result = <$table>::insert_batch(rb, chunk, batch_size).await?;
+                            $crate::sql::returning::generated_ids(&keys, &exec_result.last_insert_id)?
+                        }
+                    };
+                    // only write back the generated keys
+                    for ((table, key), id) in chunk.iter_mut().zip(&keys).zip(&chunk_ids) {
+                        if !key.is_null() || id.is_null() {
+                            continue;
+                        }
+                        let mut columns = rbs::to_value!(&*table);
+                        columns[field] = id.clone();
+                        *table = rbs::from_value(columns)?;
+                    }
+                    ids.extend(chunk_ids);
+                }
+                Ok(ids)
+            }
+
+            /// insert, or update the row if the conflict_columns is exists(postgres,sqlite,mysql,mssql)
+            pub async fn insert_or_update_batch(
+                rb: &mut dyn $crate::executor::Executor,
+                tables: &[$table],
+                conflict_columns: &[&str],
+                batch_size: u64,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                if tables.is_empty() {
+                    return Err($crate::rbdc::Error::from(
+                        "insert_or_update can not insert empty array tables!",
+                    ));
+                }
+                let driver_type = rb.driver_type()?.to_string();
+                let table_name = $table_name.to_string();
+                let mut result = $crate::rbdc::db::ExecResult {
+                    rows_affected: 0,
+                    last_insert_id: rbs::Value::Null,
+                };
+                let ranges = $crate::sql::Page::<()>::make_ranges(tables.len() as u64, batch_size);
+                for (offset, limit) in ranges {
+                    let rows = $crate::table::insert_rows(
+                        $crate::table_meta!($table),
+                        tables[offset as usize..limit as usize]
+                            .iter()
+                            .map(|v| rbs::to_value!(v))
+                            .collect(),
+                    )?;
+                    let (sql, args) = $crate::sql::upsert::quote_upsert_sql(
+                        &driver_type,
+                        $crate::table::quote_driver(&driver_type, $crate::table_meta!($table)),
+                        &table_name,
+                        &rows,
+                        conflict_columns,
+                    )?;
+                    let exec_result = rb.exec(&sql, args).await?;
+                    result.rows_affected += exec_result.rows_affected;
+                    result.last_insert_id = exec_result.last_insert_id;
+                }
+                Ok(result)
+            }
+
+            /// insert, or update the row if the conflict_columns is exists
+            pub async fn upsert(
+                rb: &mut dyn $crate::executor::Executor,
+                table: &$table,
+                conflict_columns: &[&str],
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                <$table>::insert_or_update_batch(rb, std::slice::from_ref(table), conflict_columns, 1).await
             }
         }
     };
@@ -112,6 +273,12 @@ macro_rules! impl_insert {
 ///rbatis::impl_select!(BizActivity{select_all_by_id(id:&str,name:&str) => "where id = #{id} and name = #{name}"});
 ///rbatis::impl_select!(BizActivity{select_by_id(id:String) -> Option => "where id = #{id} limit 1"});
 ///
+/// //select columns: the fields of DTO(or the columns of `#[derive(Table)]` DTO), or the columns list
+/// #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+/// pub struct BizActivityId{ pub id: Option<String> }
+///rbatis::impl_select!(BizActivity{select_ids_by_name(name:&str) -> Vec<BizActivityId> => "where name = #{name}"});
+///rbatis::impl_select!(BizActivity{select_id_by_name(name:&str) -> Option => "where name = #{name} limit 1"}, columns = ["id"]);
+///
 /// //use
 /// //BizActivity::select**()
 /// ```
@@ -119,33 +286,49 @@ macro_rules! impl_insert {
 #[macro_export]
 macro_rules! impl_select {
     ($table:ty{}) => {
-        $crate::impl_select!($table{},$crate::utils::string_util::to_snake_name(stringify!($table)));
+        $crate::schema_check_table!($table);
+        $crate::impl_select!($table{},$crate::table::table_name($crate::table_meta!($table), stringify!($table)));
     };
     ($table:ty{},$table_name:expr) => {
         $crate::impl_select!($table{select_all() => ""},$table_name);
-        $crate::impl_select!($table{select_by_column<V:serde::Serialize>(column: &str,column_value: V) -> Vec => "` where ${column} = #{column_value}`"},$table_name);
+        $crate::impl_select!($table{select_by_column<V:serde::Serialize>(column: &str,column_value: V) -> Vec => "` where ${column.quote()} = #{column_value}`"},$table_name);
         $crate::impl_select!($table{select_in_column<V:serde::Serialize>(column: &str,column_values: &[V]) -> Vec =>
-         "` where ${column} in (`
+         "` where ${column.quote()} in (`
           trim ',': for _,item in column_values:
              #{item},
           `)`"},$table_name);
     };
+    ($table:ty{$fn_name:ident $(< $($gkey:ident:$gtype:path $(,)?)* >)? ($($param_key:ident:$param_type:ty $(,)?)*) -> $container:tt<$dto:ty> => $sql:expr}$(,$table_name:expr)?) => {
+        $crate::impl_select!(@impl $table, $dto, $crate::table::dto_columns::<$dto>($crate::table_meta!($dto)), {$fn_name$(<$($gkey:$gtype,)*>)?($($param_key:$param_type,)*) -> $container => $sql}$(,$table_name)?);
+    };
+    ($table:ty{$fn_name:ident $(< $($gkey:ident:$gtype:path $(,)?)* >)? ($($param_key:ident:$param_type:ty $(,)?)*) => $sql:expr}, columns = [$($column:expr),+ $(,)?]$(,$table_name:expr)?) => {
+        $crate::impl_select!($table{$fn_name$(<$($gkey:$gtype,)*>)?($($param_key:$param_type,)*) -> Vec => $sql}, columns = [$($column),+]$(,$table_name)?);
+    };
+    ($table:ty{$fn_name:ident $(< $($gkey:ident:$gtype:path $(,)?)* >)? ($($param_key:ident:$param_type:ty $(,)?)*) -> $container:tt => $sql:expr}, columns = [$($column:expr),+ $(,)?]$(,$table_name:expr)?) => {
+        $crate::impl_select!(@impl $table, $table, [$($column),+].join(","), {$fn_name$(<$($gkey:$gtype,)*>)?($($param_key:$param_type,)*) -> $container => $sql}$(,$table_name)?);
+    };
     ($table:ty{$fn_name:ident $(< $($gkey:ident:$gtype:path $(,)?)* >)? ($($param_key:ident:$param_type:ty $(,)?)*) => $sql:expr}$(,$table_name:expr)?) => {
         $crate::impl_select!($table{$fn_name$(<$($gkey:$gtype,)*>)?($($param_key:$param_type,)*) ->Vec => $sql}$(,$table_name)?);
     };
     ($table:ty{$fn_name:ident $(< $($gkey:ident:$gtype:path $(,)?)* >)? ($($param_key:ident:$param_type:ty $(,)?)*) -> $container:tt => $sql:expr}$(,$table_name:expr)?) => {
+        $crate::impl_select!(@impl $table, $table, $crate::table::select_columns($crate::table_meta!($table)), {$fn_name$(<$($gkey:$gtype,)*>)?($($param_key:$param_type,)*) -> $container => $sql}$(,$table_name)?);
+    };
+    (@impl $table:ty, $dto:ty, $table_column:expr, {$fn_name:ident $(< $($gkey:ident:$gtype:path $(,)?)* >)? ($($param_key:ident:$param_type:ty $(,)?)*) -> $container:tt => $sql:expr}$(,$table_name:expr)?) => {
+        $crate::schema_check_table!($table $(,$table_name)?);
         impl $table{
-            pub async fn $fn_name $(<$($gkey:$gtype,)*>)? (rb: &mut dyn  $crate::executor::Executor,$($param_key:$param_type,)*) -> std::result::Result<$container<$table>,$crate::rbdc::Error>
+            pub async fn $fn_name $(<$($gkey:$gtype,)*>)? (rb: &mut dyn  $crate::executor::Executor,$($param_key:$param_type,)*) -> std::result::Result<$container<$dto>,$crate::rbdc::Error>
             {
-                     #[$crate::py_sql("`select ${table_column} from ${table_name} `",$sql)]
-                     async fn $fn_name$(<$($gkey: $gtype,)*>)?(rb: &mut dyn $crate::executor::Executor,table_column:&str,table_name:&str,$($param_key:$param_type,)*) -> std::result::Result<$container<$table>,$crate::rbdc::Error> {impled!()}
-                     let mut table_column = "*".to_string();
+                     #[$crate::py_sql("`select ${table_column.raw()} from ${table_name.quote()} `",$sql)]
+                     async fn $fn_name$(<$($gkey: $gtype,)*>)?(rb: &mut dyn $crate::executor::Executor,table_column:&str,table_name:&str,quote_driver:&str,$($param_key:$param_type,)*) -> std::result::Result<$container<$dto>,$crate::rbdc::Error> {impled!()}
+                     let driver_type = rb.driver_type()?.to_string();
+                     let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                     let mut table_column = $crate::rbatis_codegen::identifier::quote_columns(quote_driver, &$table_column);
                      let mut table_name = String::new();
                      $(table_name = $table_name.to_string();)?
                      if table_name.is_empty(){
-                         table_name = $crate::utils::string_util::to_snake_name(stringify!($table));
+                         table_name = $crate::table::table_name($crate::table_meta!($table), stringify!($table));
                      }
-                     $fn_name(rb,&table_column,&table_name,$($param_key ,)*).await
+                     $fn_name(rb,&table_column,&table_name,quote_driver,$($param_key ,)*).await
             }
         }
     };
@@ -160,19 +343,20 @@ macro_rules! impl_select {
 #[macro_export]
 macro_rules! impl_update {
     ($table:ty{}) => {
+        $crate::schema_check_table!($table);
         $crate::impl_update!(
             $table{},
-            $crate::utils::string_util::to_snake_name(stringify!($table))
+            $crate::table::table_name($crate::table_meta!($table), stringify!($table))
         );
     };
     ($table:ty{},$table_name:expr) => {
-        $crate::impl_update!($table{update_by_column_value(column: &str,column_value: &rbs::Value) => "`where ${column} = #{column_value}`"},$table_name);
+        $crate::impl_update!($table{update_by_column_value(column: &str,column_value: &rbs::Value) => "`where ${column.quote()} = #{column_value}`"},$table_name);
         impl $table {
             pub async fn update_by_column(
                 rb: &mut dyn $crate::executor::Executor,
                 table: &$table,
                 column: &str) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error>{
-                let columns = rbs::to_value!(table);
+                let columns = $crate::table::to_columns($crate::table_meta!($table), rbs::to_value!(table));
                 let column_value = &columns[column];
                 <$table>::update_by_column_value(rb,table,column,column_value).await
             }
@@ -191,9 +375,26 @@ macro_rules! impl_update {
                     last_insert_id:rbs::Value::Null
                 })
             }
+
+            /// update with `VersionLockIntercept`, write the new version back into the table if the intercept bumped it
+            pub async fn update_by_column_version(
+                rb: &mut dyn $crate::executor::Executor,
+                table: &mut $table,
+                column: &str,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                let (result, version) = $crate::version_lock::VersionLockIntercept::scope(<$table>::update_by_column(rb,table,column)).await;
+                let result = result?;
+                if let (Some(version), Some(lock)) = (version, rb.rbatis_ref().get_intercept::<$crate::version_lock::VersionLockIntercept>()){
+                    let mut row = rbs::to_value!(&*table);
+                    lock.write_version($crate::table_meta!($table), &mut row, version);
+                    *table = rbs::from_value(row)?;
+                }
+                Ok(result)
+            }
         }
     };
     ($table:ty{$fn_name:ident($($param_key:ident:$param_type:ty$(,)?)*) => $sql_where:expr}$(,$table_name:expr)?) => {
+        $crate::schema_check_table!($table $(,$table_name)?);
         impl $table {
             pub async fn $fn_name(
                 rb: &mut dyn $crate::executor::Executor,
@@ -203,16 +404,17 @@ macro_rules! impl_update {
                 if $sql_where.is_empty(){
                     return Err($crate::rbdc::Error::from("sql_where can't be empty!"));
                 }
-                #[$crate::py_sql("`update ${table_name} set `
+                #[$crate::py_sql("`update ${table_name.quote()} set `
                                  trim ',':
                                    for k,v in table:
                                      if k == column || v== null:
                                         continue:
-                                     `${k}=#{v},`
+                                     `${k.quote()}=#{v},`
                                  ` `",$sql_where)]
                   async fn $fn_name(
                       rb: &mut dyn $crate::executor::Executor,
                       table_name: String,
+                      quote_driver: &str,
                       table: &rbs::Value,
                       $($param_key:$param_type,)*
                   ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
@@ -221,10 +423,12 @@ macro_rules! impl_update {
                   let mut table_name = String::new();
                   $(table_name = $table_name.to_string();)?
                   if table_name.is_empty(){
-                      table_name = $crate::utils::string_util::to_snake_name(stringify!($table));
+                      table_name = $crate::table::table_name($crate::table_meta!($table), stringify!($table));
                   }
-                  let table = rbs::to_value!(table);
-                  $fn_name(rb, table_name, &table, $($param_key,)*).await
+                  let table = $crate::table::to_columns($crate::table_meta!($table), rbs::to_value!(table));
+                  let driver_type = rb.driver_type()?.to_string();
+                  let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                  $fn_name(rb, table_name, quote_driver, &table, $($param_key,)*).await
             }
         }
     };
@@ -240,25 +444,75 @@ macro_rules! impl_update {
 #[macro_export]
 macro_rules! impl_delete {
     ($table:ty{}) => {
+        $crate::schema_check_table!($table);
         $crate::impl_delete!(
             $table{},
-            $crate::utils::string_util::to_snake_name(stringify!($table))
+            $crate::table::table_name($crate::table_meta!($table), stringify!($table))
         );
     };
     ($table:ty{},$table_name:expr) => {
-        $crate::impl_delete!($table {delete_by_column<V:serde::Serialize>(column:&str,column_value: V) => "`where ${column} = #{column_value}`"},$table_name);
+        $crate::impl_delete!($table {delete_by_column<V:serde::Serialize>(column:&str,column_value: V) => "`where ${column.quote()} = #{column_value}`"},$table_name);
         $crate::impl_delete!($table {delete_in_column<V:serde::Serialize>(column:&str,column_values: &[V]) =>
-        "`where ${column} in (`
+        "`where ${column.quote()} in (`
           trim ',': for _,item in column_values:
              #{item},
           `)`"},$table_name);
-        $crate::impl_delete!($table {delete_by_column_batch<V:serde::Serialize>(column:&str,column_values: &[V]) => "`where ${column} in (`
+        $crate::impl_delete!($table {delete_by_column_batch<V:serde::Serialize>(column:&str,column_values: &[V]) => "`where ${column.quote()} in (`
                                        trim ',':
                                          for _,v in column_values:
                                             #{v},
                                        `)`"},$table_name);
+        impl $table {
+            /// physical delete, it is not rewrite by `LogicDeleteIntercept`
+            pub async fn delete_by_column_physical<V:serde::Serialize>(
+                rb: &mut dyn $crate::executor::Executor,
+                column: &str,
+                column_value: V,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                #[$crate::py_sql("`/* physical */ delete from ${table_name.quote()} where ${column.quote()} = #{column_value}`")]
+                async fn delete_by_column_physical<V:serde::Serialize>(
+                    rb: &mut dyn $crate::executor::Executor,
+                    table_name: String,
+                    quote_driver: &str,
+                    column: &str,
+                    column_value: V,
+                ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                    impled!()
+                }
+                let table_name = $table_name.to_string();
+                let driver_type = rb.driver_type()?.to_string();
+                let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                delete_by_column_physical(rb, table_name, quote_driver, column, column_value).await
+            }
+
+            /// physical delete, it is not rewrite by `LogicDeleteIntercept`
+            pub async fn delete_in_column_physical<V:serde::Serialize>(
+                rb: &mut dyn $crate::executor::Executor,
+                column: &str,
+                column_values: &[V],
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                #[$crate::py_sql("`/* physical */ delete from ${table_name.quote()} where ${column.quote()} in (`
+                                  trim ',': for _,item in column_values:
+                                     #{item},
+                                  `)`")]
+                async fn delete_in_column_physical<V:serde::Serialize>(
+                    rb: &mut dyn $crate::executor::Executor,
+                    table_name: String,
+                    quote_driver: &str,
+                    column: &str,
+                    column_values: &[V],
+                ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                    impled!()
+                }
+                let table_name = $table_name.to_string();
+                let driver_type = rb.driver_type()?.to_string();
+                let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                delete_in_column_physical(rb, table_name, quote_driver, column, column_values).await
+            }
+        }
     };
     ($table:ty{$fn_name:ident $(< $($gkey:ident:$gtype:path $(,)?)* >)? ($($param_key:ident:$param_type:ty$(,)?)*) => $sql_where:expr}$(,$table_name:expr)?) => {
+        $crate::schema_check_table!($table $(,$table_name)?);
         impl $table {
             pub async fn $fn_name$(<$($gkey:$gtype,)*>)?(
                 rb: &mut dyn $crate::executor::Executor,
@@ -267,10 +521,11 @@ macro_rules! impl_delete {
                 if $sql_where.is_empty(){
                     return Err($crate::rbdc::Error::from("sql_where can't be empty!"));
                 }
-                #[$crate::py_sql("`delete from ${table_name} `",$sql_where)]
+                #[$crate::py_sql("`delete from ${table_name.quote()} `",$sql_where)]
                 async fn $fn_name$(<$($gkey: $gtype,)*>)?(
                     rb: &mut dyn $crate::executor::Executor,
                     table_name: String,
+                    quote_driver: &str,
                     $($param_key:$param_type,)*
                 ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
                     impled!()
@@ -278,16 +533,214 @@ macro_rules! impl_delete {
                 let mut table_name = String::new();
                 $(table_name = $table_name.to_string();)?
                 if table_name.is_empty(){
-                  table_name = $crate::utils::string_util::to_snake_name(stringify!($table));
+                  table_name = $crate::table::table_name($crate::table_meta!($table), stringify!($table));
+                }
+                let driver_type = rb.driver_type()?.to_string();
+                let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                $fn_name(rb, table_name, quote_driver, $($param_key,)*).await
+            }
+        }
+    };
+}
+
+/// PySql: gen select_by_id,select_by_ids,update_by_id,delete_by_id by the primary keys.
+/// the primary keys is the declared keys, or the `#[column(pk)]` of `#[derive(Table)]`, or `id`.
+/// * the id of single key is the value
+/// * the id of composite key is the tuple of key values in order, or the map(struct) of `{column: value}`
+///
+/// ```rust
+/// #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+/// pub struct BizOrder{ pub tenant_id: Option<String>, pub order_no: Option<String> }
+/// rbatis::impl_by_id!(BizOrder{}, ["tenant_id", "order_no"]);
+///
+/// //use
+/// //let order = BizOrder::select_by_id(rb, ("1", "2")).await?;
+/// //let orders = BizOrder::select_by_ids(rb, &[("1", "2"), ("1", "3")]).await?;
+/// ```
+#[macro_export]
+macro_rules! impl_by_id {
+    (@impl $table:ty, $keys:expr, $table_name:expr) => {
+        impl $table {
+            pub async fn select_by_id<V: serde::Serialize>(
+                rb: &mut dyn $crate::executor::Executor,
+                id: V,
+            ) -> std::result::Result<Option<$table>, $crate::rbdc::Error> {
+                #[$crate::py_sql("`select ${table_column.raw()} from ${table_name.quote()} where `
+                                  for _,item in pk_items:
+                                    for k,v in item:
+                                      `${k.raw()}#{v}`
+                                  `)`")]
+                async fn select_by_id(
+                    rb: &mut dyn $crate::executor::Executor,
+                    table_column: &str,
+                    table_name: &str,
+                    quote_driver: &str,
+                    pk_items: &rbs::Value,
+                ) -> std::result::Result<Option<$table>, $crate::rbdc::Error> {
+                    impled!()
+                }
+                let driver_type = rb.driver_type()?.to_string();
+                let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                let keys: Vec<&str> = $keys;
+                let pk_items = $crate::table::quote_pk_condition(quote_driver, &keys, &[rbs::to_value!(id)])?;
+                let table_column = $crate::rbatis_codegen::identifier::quote_columns(quote_driver, &$crate::table::select_columns($crate::table_meta!($table)));
+                let table_name = $table_name.to_string();
+                select_by_id(rb, &table_column, &table_name, quote_driver, &pk_items).await
+            }
+
+            pub async fn select_by_ids<V: serde::Serialize>(
+                rb: &mut dyn $crate::executor::Executor,
+                ids: &[V],
+            ) -> std::result::Result<Vec<$table>, $crate::rbdc::Error> {
+                #[$crate::py_sql("`select ${table_column.raw()} from ${table_name.quote()} where `
+                                  for _,item in pk_items:
+                                    for k,v in item:
+                                      `${k.raw()}#{v}`
+                                  `)`")]
+                async fn select_by_ids(
+                    rb: &mut dyn $crate::executor::Executor,
+                    table_column: &str,
+                    table_name: &str,
+                    quote_driver: &str,
+                    pk_items: &rbs::Value,
+                ) -> std::result::Result<Vec<$table>, $crate::rbdc::Error> {
+                    impled!()
+                }
+                if ids.is_empty() {
+                    return Ok(vec![]);
+                }
+                let driver_type = rb.driver_type()?.to_string();
+                let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                let keys: Vec<&str> = $keys;
+                let ids: Vec<rbs::Value> = ids.iter().map(|v| rbs::to_value!(v)).collect();
+                let pk_items = $crate::table::quote_pk_condition(quote_driver, &keys, &ids)?;
+                let table_column = $crate::rbatis_codegen::identifier::quote_columns(quote_driver, &$crate::table::select_columns($crate::table_meta!($table)));
+                let table_name = $table_name.to_string();
+                select_by_ids(rb, &table_column, &table_name, quote_driver, &pk_items).await
+            }
+
+            /// update the not null columns(except primary keys) by the primary keys of table, it is an error if all of them are null
+            pub async fn update_by_id(
+                rb: &mut dyn $crate::executor::Executor,
+                table: &$table,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                #[$crate::py_sql("`update ${table_name.quote()} set `
+                                  trim ',':
+                                    for k,v in table:
+                                      if v== null:
+                                         continue:
+                                      `${k.quote()}=#{v},`
+                                  ` where `
+                                  for _,item in pk_items:
+                                    for k,v in item:
+                                      `${k.raw()}#{v}`
+                                  `)`")]
+                async fn update_by_id(
+                    rb: &mut dyn $crate::executor::Executor,
+                    table_name: String,
+                    quote_driver: &str,
+                    table: &rbs::Value,
+                    pk_items: &rbs::Value,
+                ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                    impled!()
+                }
+                let driver_type = rb.driver_type()?.to_string();
+                let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                let keys: Vec<&str> = $keys;
+                let mut table = $crate::table::to_columns($crate::table_meta!($table), rbs::to_value!(table));
+                let id = rbs::Value::Array(keys.iter().map(|k| table[*k].clone()).collect());
+                let pk_items = $crate::table::quote_pk_condition(quote_driver, &keys, &[id])?;
+                if let rbs::Value::Map(m) = &mut table {
+                    for k in &keys {
+                        m.rm(k);
+                    }
+                }
+                if table.as_map().map(|m| m.iter().all(|(_, v)| v.is_null())).unwrap_or(true) {
+                    return Err($crate::rbdc::Error::from(format!(
+                        "[rbatis] update_by_id of `{}` has no column to set, the columns except the primary keys are all null",
+                        $table_name
+                    )));
+                }
+                update_by_id(rb, $table_name.to_string(), quote_driver, &table, &pk_items).await
+            }
+
+            pub async fn delete_by_id<V: serde::Serialize>(
+                rb: &mut dyn $crate::executor::Executor,
+                id: V,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                #[$crate::py_sql("`delete from ${table_name.quote()} where `
+                                  for _,item in pk_items:
+                                    for k,v in item:
+                                      `${k.raw()}#{v}`
+                                  `)`")]
+                async fn delete_by_id(
+                    rb: &mut dyn $crate::executor::Executor,
+                    table_name: String,
+                    quote_driver: &str,
+                    pk_items: &rbs::Value,
+                ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                    impled!()
                 }
-                $fn_name(rb, table_name, $($param_key,)*).await
+                let driver_type = rb.driver_type()?.to_string();
+                let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                let keys: Vec<&str> = $keys;
+                let pk_items = $crate::table::quote_pk_condition(quote_driver, &keys, &[rbs::to_value!(id)])?;
+                delete_by_id(rb, $table_name.to_string(), quote_driver, &pk_items).await
+            }
+
+            /// physical delete, it is not rewrite by `LogicDeleteIntercept`
+            pub async fn delete_by_id_physical<V: serde::Serialize>(
+                rb: &mut dyn $crate::executor::Executor,
+                id: V,
+            ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                #[$crate::py_sql("`/* physical */ delete from ${table_name.quote()} where `
+                                  for _,item in pk_items:
+                                    for k,v in item:
+                                      `${k.raw()}#{v}`
+                                  `)`")]
+                async fn delete_by_id_physical(
+                    rb: &mut dyn $crate::executor::Executor,
+                    table_name: String,
+                    quote_driver: &str,
+                    pk_items: &rbs::Value,
+                ) -> std::result::Result<$crate::rbdc::db::ExecResult, $crate::rbdc::Error> {
+                    impled!()
+                }
+                let driver_type = rb.driver_type()?.to_string();
+                let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                let keys: Vec<&str> = $keys;
+                let pk_items = $crate::table::quote_pk_condition(quote_driver, &keys, &[rbs::to_value!(id)])?;
+                delete_by_id_physical(rb, $table_name.to_string(), quote_driver, &pk_items).await
             }
         }
     };
+    ($table:ty{}) => {
+        $crate::schema_check_table!($table);
+        $crate::impl_by_id!(
+            $table{},
+            $crate::table::table_name($crate::table_meta!($table), stringify!($table))
+        );
+    };
+    ($table:ty{},[$($pk:expr),+ $(,)?]) => {
+        $crate::schema_check_table!($table);
+        $crate::impl_by_id!(
+            $table{},
+            [$($pk),+],
+            $crate::table::table_name($crate::table_meta!($table), stringify!($table))
+        );
+    };
+    ($table:ty{},[$($pk:expr),+ $(,)?],$table_name:expr) => {
+        $crate::impl_by_id!(@impl $table, vec![$($pk),+], $table_name);
+    };
+    ($table:ty{},$table_name:expr) => {
+        $crate::impl_by_id!(@impl $table, $crate::table::primary_keys($crate::table_meta!($table)), $table_name);
+    };
 }
 
 /// pysql impl_select_page
-/// If the database does not support the statement `limit ${page_no},${page_size}`,You should include ${page_no} and ${page_size} in SQL
+/// the page sql is selected by the driver type(see `rbatis::sql::page_dialect`),
+/// you can also include ${page_no} and ${page_size} in SQL to implement your own page sql.
+/// the sql with group by/distinct is counted by the sub query `select count(1) as count from (select ...) as rb_count_t`
 ///
 /// ```rust
 /// #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -299,38 +752,66 @@ macro_rules! impl_delete {
 #[macro_export]
 macro_rules! impl_select_page {
     ($table:ty{$fn_name:ident($($param_key:ident:$param_type:ty$(,)?)*) => $where_sql:expr}) => {
+        $crate::schema_check_table!($table);
         $crate::impl_select_page!(
             $table{$fn_name($($param_key:$param_type)*)=> $where_sql},
-            $crate::utils::string_util::to_snake_name(stringify!($table))
+            $crate::table::table_name($crate::table_meta!($table), stringify!($table))
         );
     };
     ($table:ty{$fn_name:ident($($param_key:ident:$param_type:ty$(,)?)*) => $where_sql:expr},$table_name:expr) => {
         impl $table {
+            #[allow(clippy::too_many_arguments)]
             pub async fn $fn_name(
                 rb: &mut dyn $crate::executor::Executor,
                 page_req: &$crate::sql::PageRequest,
                 $($param_key:$param_type,)*
             ) -> std::result::Result<$crate::sql::Page::<$table>, $crate::rbdc::Error> {
                 use $crate::sql::IPageRequest;
-                let mut table_column = "*".to_string();
+                let page_req = &page_req.normalized();
+                let driver_type = rb.driver_type()?.to_string();
+                let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                let mut table_column = $crate::rbatis_codegen::identifier::quote_columns(quote_driver, &$crate::table::select_columns($crate::table_meta!($table)));
                 let mut table_name = $table_name.to_string();
-                let mut total = 0;
-                {
-                   #[$crate::py_sql("`select count(1) as count from ${table_name} `",$where_sql)]
-                   async fn rb_impl_count(rb: &mut dyn $crate::executor::Executor,table_column:&str,table_name: &str,$($param_key:$param_type,)*) -> std::result::Result<u64, $crate::rbdc::Error> {impled!()}
-                   total = rb_impl_count(rb, &table_column,&table_name, $($param_key,)*).await?;
+                #[$crate::py_sql("`select count(1) as count from ${table_name.quote()} `",$where_sql)]
+                async fn rb_impl_count(rb: &mut dyn $crate::executor::Executor,table_column:&str,table_name: &str,quote_driver:&str,$($param_key:$param_type,)*) -> std::result::Result<u64, $crate::rbdc::Error> {impled!()}
+                #[$crate::py_sql("`select count(1) as count from (select ${table_column.raw()} from ${table_name.quote()} `",$where_sql,"`) as rb_count_t`")]
+                async fn rb_impl_count_wrap(rb: &mut dyn $crate::executor::Executor,table_column:&str,table_name: &str,quote_driver:&str,$($param_key:$param_type,)*) -> std::result::Result<u64, $crate::rbdc::Error> {impled!()}
+                // the page sql is not indented, so it is not the child of the where_sql
+                #[$crate::py_sql("`select ${table_column.raw()} from ${table_name.quote()} `",$where_sql,
+                  "\nif !sql.contains('page_no') && !sql.contains('page_size'):\n  if need_order_by && !sql.contains('order by'):\n    ` order by (select null)`\n  `${limit_sql.raw()}`")]
+                async fn rb_impl_select(rb: &mut dyn $crate::executor::Executor,table_column:&str,table_name: &str,quote_driver:&str,page_no:u64,page_size:u64,page_offset:u64,limit_sql:&str,need_order_by:bool,$($param_key:$param_type,)*) -> std::result::Result<Vec<$table>, $crate::rbdc::Error> {impled!()}
+                let is_complex = $crate::sql::page_dialect::is_complex_select(&table_column, $where_sql);
+                let dialect = $crate::sql::page_dialect::page_dialect(rb.driver_type()?);
+                // select page_size + 1 rows to know the next page without count
+                let page_size = if page_req.search_count { page_req.page_size } else { page_req.page_size + 1 };
+                let limit_sql = dialect.page_sql(page_req.offset(), page_size);
+                let need_order_by = dialect.need_order_by();
+                if !page_req.search_count {
+                    let records = rb_impl_select(rb,&table_column,&table_name,quote_driver,page_req.page_no,page_size,page_req.offset(),&limit_sql,need_order_by,$($param_key,)*).await?;
+                    return Ok($crate::sql::Page::<$table>::new_no_count(page_req.page_no, page_req.page_size, records));
                 }
-                //pg,mssql can override this parameter to implement its own limit statement
-                let mut limit_sql = " limit ${page_no},${page_size}".to_string();
-                limit_sql=limit_sql.replace("${page_no}",&page_req.offset().to_string());
-                limit_sql=limit_sql.replace("${page_size}",&page_req.page_size.to_string());
-                let records:Vec<$table>;
-                #[$crate::py_sql("`select ${table_column} from ${table_name} `",$where_sql,"
-                              if !sql.contains('page_no') && !sql.contains('page_size'):
-                                `${limit_sql}`")]
-                async fn rb_impl_select(rb: &mut dyn $crate::executor::Executor,table_column:&str,table_name: &str,page_no:u64,page_size:u64,page_offset:u64,limit_sql:&str,$($param_key:$param_type,)*) -> std::result::Result<Vec<$table>, $crate::rbdc::Error> {impled!()}
-                records = rb_impl_select(rb,&table_column,&table_name,page_req.page_no, page_req.page_size,page_req.offset(),&limit_sql,$($param_key,)*).await?;
-
+                let (total, records) = if page_req.concurrent && rb.is_pool() {
+                    // the count and select run on two connections of the pool
+                    let mut rb_count = $crate::executor::RbatisRef::rbatis_ref(rb).clone();
+                    let mut rb_select = rb_count.clone();
+                    let count = async {
+                        if is_complex {
+                            rb_impl_count_wrap(&mut rb_count, &table_column,&table_name,quote_driver, $($param_key,)*).await
+                        } else {
+                            rb_impl_count(&mut rb_count, &table_column,&table_name,quote_driver, $($param_key,)*).await
+                        }
+                    };
+                    let select = rb_impl_select(&mut rb_select,&table_column,&table_name,quote_driver,page_req.page_no,page_size,page_req.offset(),&limit_sql,need_order_by,$($param_key,)*);
+                    let (total, records) = $crate::sql::join_count(count, select).await;
+                    (total?, records?)
+                } else {
+                    let total = if is_complex {
+                        rb_impl_count_wrap(rb, &table_column,&table_name,quote_driver, $($param_key,)*).await?
+                    } else {
+                        rb_impl_count(rb, &table_column,&table_name,quote_driver, $($param_key,)*).await?
+                    };
+                    (total, rb_impl_select(rb,&table_column,&table_name,quote_driver,page_req.page_no,page_size,page_req.offset(),&limit_sql,need_order_by,$($param_key,)*).await?)
+                };
                 let mut page = $crate::sql::Page::<$table>::new_total(page_req.page_no, page_req.page_size, total);
                 page.records = records;
                 Ok(page)
@@ -339,6 +820,68 @@ macro_rules! impl_select_page {
     };
 }
 
+/// pysql impl_select_cursor_page, the keyset(cursor) page by the ordered unique columns.
+/// the where_sql must not contains `order by` and `limit`, the `where` must be lowercase.
+///
+/// ```rust
+/// #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+/// pub struct BizActivity{}
+/// rbatis::impl_select_cursor_page!(BizActivity{select_cursor_page(name:&str) =>"
+///      if name != '':
+///        `where name = #{name}`"}, ["create_time", "id"], desc);
+/// //use
+/// //let page = BizActivity::select_cursor_page(rb, &CursorPageRequest::new(10), "").await?;
+/// //let next = BizActivity::select_cursor_page(rb, &CursorPageRequest::new(10).cursor(page.next_cursor), "").await?;
+/// ```
+#[macro_export]
+macro_rules! impl_select_cursor_page {
+    ($table:ty{$fn_name:ident($($param_key:ident:$param_type:ty$(,)?)*) => $where_sql:expr}, [$($column:expr),+ $(,)?], $order:ident) => {
+        $crate::schema_check_table!($table);
+        $crate::impl_select_cursor_page!(
+            $table{$fn_name($($param_key:$param_type)*)=> $where_sql}, [$($column),+], $order,
+            $crate::table::table_name($crate::table_meta!($table), stringify!($table))
+        );
+    };
+    ($table:ty{$fn_name:ident($($param_key:ident:$param_type:ty$(,)?)*) => $where_sql:expr}, [$($column:expr),+ $(,)?], $order:ident, $table_name:expr) => {
+        impl $table {
+            #[allow(clippy::too_many_arguments)]
+            pub async fn $fn_name(
+                rb: &mut dyn $crate::executor::Executor,
+                page_req: &$crate::sql::CursorPageRequest,
+                $($param_key:$param_type,)*
+            ) -> std::result::Result<$crate::sql::CursorPage::<$table>, $crate::rbdc::Error> {
+                let driver_type = rb.driver_type()?.to_string();
+                let quote_driver = $crate::table::quote_driver(&driver_type, $crate::table_meta!($table));
+                let table_column = $crate::rbatis_codegen::identifier::quote_columns(quote_driver, &$crate::table::select_columns($crate::table_meta!($table)));
+                let table_name = $table_name.to_string();
+                let columns: Vec<&str> = vec![$($column),+];
+                let quote_columns: Vec<String> = columns.iter().map(|c| $crate::rbatis_codegen::identifier::quote_identifier(quote_driver, c)).collect();
+                let quote_columns: Vec<&str> = quote_columns.iter().map(|c| c.as_str()).collect();
+                let desc = stringify!($order).eq_ignore_ascii_case("desc");
+                let cursor = match &page_req.cursor {
+                    Some(v) => Some($crate::sql::Cursor::decode(v)?),
+                    None => None,
+                };
+                let has_cursor = cursor.is_some();
+                let cursor_items = match &cursor {
+                    Some(v) => $crate::sql::cursor_condition(&quote_columns, desc, v)?,
+                    None => rbs::Value::Array(vec![]),
+                };
+                let backward = cursor.as_ref().map(|v| v.backward).unwrap_or_default();
+                let order_sql = $crate::sql::cursor_order(&quote_columns, desc, backward);
+                let dialect = $crate::sql::page_dialect::page_dialect(rb.driver_type()?);
+                let limit_sql = dialect.page_sql(0, page_req.page_size + 1);
+                // the cursor where is not indented, so it is not the child of the where_sql
+                #[$crate::py_sql("`select ${table_column.raw()} from ${table_name.quote()} `",$where_sql,
+                  "\nif has_cursor:\n  if sql.contains('where '):\n    ` and `\n  if !sql.contains('where '):\n    ` where `\n  for _,item in cursor_items:\n    for k,v in item:\n      `${k.raw()}#{v}`\n  `))`\n` order by ${order_sql.raw()}${limit_sql.raw()}`")]
+                async fn rb_impl_select(rb: &mut dyn $crate::executor::Executor,table_column:&str,table_name: &str,quote_driver:&str,has_cursor:bool,cursor_items:&rbs::Value,order_sql:&str,limit_sql:&str,$($param_key:$param_type,)*) -> std::result::Result<Vec<rbs::Value>, $crate::rbdc::Error> {impled!()}
+                let rows = rb_impl_select(rb,&table_column,&table_name,quote_driver,has_cursor,&cursor_items,&order_sql,&limit_sql,$($param_key,)*).await?;
+                $crate::sql::CursorPage::<$table>::from_rows(rows, $crate::table_meta!($table), &columns, page_req.page_size, cursor.as_ref())
+            }
+        }
+    };
+}
+
 /// impl html_sql select page.
 ///
 /// you must deal with 3 param:
@@ -370,6 +913,7 @@ macro_rules! htmlsql_select_page {
     ($fn_name:ident($($param_key:ident:$param_type:ty$(,)?)*) -> $table:ty => $html_file:expr) => {
             pub async fn $fn_name(rb: &mut dyn $crate::executor::Executor, page_req: &$crate::sql::PageRequest, $($param_key:$param_type,)*) -> std::result::Result<$crate::sql::Page<$table>, $crate::rbdc::Error> {
             use $crate::sql::IPageRequest;
+            let page_req = &page_req.normalized();
             struct Inner{}
             impl Inner{
               #[$crate::html_sql($html_file)]
@@ -377,11 +921,27 @@ macro_rules! htmlsql_select_page {
                  $crate::impled!()
               }
             }
-            let totalValue = Inner::$fn_name(rb, true, page_req.offset(), page_req.page_size, $($param_key,)*).await?;
-            let recordsValue = Inner::$fn_name(rb, false, page_req.offset(), page_req.page_size, $($param_key,)*).await?;
+            if !page_req.search_count {
+                // select page_size + 1 rows to know the next page without count
+                let recordsValue = Inner::$fn_name(rb, false, page_req.offset(), page_req.page_size + 1, $($param_key,)*).await?;
+                let records = rbs::from_value(recordsValue)?;
+                return Ok($crate::sql::Page::<$table>::new_no_count(page_req.page_no, page_req.page_size, records));
+            }
+            let (totalValue, recordsValue) = if page_req.concurrent && rb.is_pool() {
+                // the count and select run on two connections of the pool
+                let mut rb_count = $crate::executor::RbatisRef::rbatis_ref(rb).clone();
+                let mut rb_select = rb_count.clone();
+                let count = Inner::$fn_name(&mut rb_count, true, page_req.offset(), page_req.page_size, $($param_key,)*);
+                let select = Inner::$fn_name(&mut rb_select, false, page_req.offset(), page_req.page_size, $($param_key,)*);
+                let (totalValue, recordsValue) = $crate::sql::join_count(count, select).await;
+                (totalValue?, recordsValue?)
+            } else {
+                (Inner::$fn_name(rb, true, page_req.offset(), page_req.page_size, $($param_key,)*).await?,
+                 Inner::$fn_name(rb, false, page_req.offset(), page_req.page_size, $($param_key,)*).await?)
+            };
             let total =  $crate::decode(totalValue)?;
             let records = rbs::from_value(recordsValue)?;
-            let mut page = $crate::sql::Page::<$table>::new_total(page_req.offset(), page_req.page_size, total);
+            let mut page = $crate::sql::Page::<$table>::new_total(page_req.page_no, page_req.page_size, total);
             page.records = records;
             Ok(page)
          }
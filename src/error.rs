@@ -1,3 +1,5 @@
 pub type Result<T> = std::result::Result<T, Error>;
 
 pub type Error = rbdc::Error;
+
+pub use rbdc::ErrorKind;
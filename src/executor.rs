@@ -1,15 +1,20 @@
 use std::fmt::{Debug, Formatter};
 use std::ops::{Deref, DerefMut};
+use std::time::{Duration, Instant};
 
 use crate::decode::{decode, is_debug_mode};
 use crate::rbatis::Rbatis;
+use crate::intercept::{Intercept, ResultType, SqlParts};
 use crate::snowflake::new_snowflake_id;
-use crate::sql::tx::Tx;
+use crate::sql::tx::{SavePoint, Tx, TxOptions};
 use crate::Error;
-use futures::Future;
+use dark_std::sync::SyncVec;
+use futures::{Future, SinkExt, StreamExt, TryStreamExt};
 use futures_core::future::BoxFuture;
+use futures_core::stream::BoxStream;
 use log::LevelFilter;
 use rbdc::db::{Connection, ExecResult};
+use rbdc::ext::async_stream::TryAsyncStream;
 use rbs::Value;
 use serde::de::DeserializeOwned;
 
@@ -17,6 +22,31 @@ use serde::de::DeserializeOwned;
 pub trait Executor: RbatisRef {
     fn exec(&mut self, sql: &str, args: Vec<Value>) -> BoxFuture<'_, Result<ExecResult, Error>>;
     fn query(&mut self, sql: &str, args: Vec<Value>) -> BoxFuture<'_, Result<Value, Error>>;
+    /// query and yield rows one by one,the whole result set is never held in memory.
+    /// the default impl yield the rows of `query()`, the executors of rbatis override it to stream natively.
+    /// the result is logged and `Intercept::after` is run when the stream end, if the stream is dropped before the end,
+    /// only the log is written on drop(`Intercept::after` is async, it can not run on drop)
+    fn query_stream(&mut self, sql: &str, args: Vec<Value>) -> BoxStream<'_, Result<Value, Error>> {
+        let sql = sql.to_string();
+        Box::pin(TryAsyncStream::new(move |mut sender| async move {
+            let rows = match self.query(&sql, args).await? {
+                Value::Array(rows) => rows,
+                Value::Null => vec![],
+                v => vec![v],
+            };
+            for row in rows {
+                if sender.send(Ok(row)).await.is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        }))
+    }
+
+    /// is the pool(`Rbatis`), every call acquire a connection, so the calls can run concurrently
+    fn is_pool(&self) -> bool {
+        false
+    }
 }
 
 pub trait RbatisRef: Send {
@@ -62,6 +92,18 @@ impl RBatisConnExecutor {
         let v = Executor::query(self, sql, args).await?;
         Ok(decode(v)?)
     }
+
+    /// query and decode rows one by one
+    pub fn query_stream_decode<T>(
+        &mut self,
+        sql: &str,
+        args: Vec<Value>,
+    ) -> BoxStream<'_, Result<T, Error>>
+        where
+            T: DeserializeOwned + Send + 'static,
+    {
+        decode_stream(Executor::query_stream(self, sql, args))
+    }
 }
 
 impl Executor for RBatisConnExecutor {
@@ -72,44 +114,19 @@ impl Executor for RBatisConnExecutor {
     ) -> BoxFuture<'_, Result<ExecResult, Error>> {
         let mut sql = sql.to_string();
         Box::pin(async move {
-            let rb_task_id = new_snowflake_id();
-            let is_prepared = args.len() > 0;
-            for item in self.rbatis_ref().sql_intercepts.iter() {
-                item.do_intercept(self.rbatis_ref(), &mut sql, &mut args, is_prepared)?;
-            }
-            if self.rbatis_ref().log_plugin.is_enable() {
-                let b = Value::Array(args);
-                self.rbatis_ref().log_plugin.do_log(
-                    LevelFilter::Info,
-                    &format!(
-                        "[rbatis] [{}] exec  => `{}` {}",
-                        &rb_task_id,
-                        sql,
-                        &b
-                    ),
-                );
-                args = b.into();
+            if let Some(parts) = split_sql(&self.rb, &sql, &args)? {
+                return exec_parts(self, parts).await;
             }
-            let result = self.conn.exec(&sql, args).await;
-            if self.rbatis_ref().log_plugin.is_enable() {
-                match &result {
-                    Ok(result) => {
-                        self.rbatis_ref().log_plugin.do_log(
-                            LevelFilter::Info,
-                            &format!(
-                                "[rbatis] [{}] exec  <= rows_affected={}",
-                                rb_task_id, result.rows_affected
-                            ),
-                        );
-                    }
-                    Err(e) => {
-                        self.rbatis_ref().log_plugin.do_log(
-                            LevelFilter::Error,
-                            &format!("[rbatis] [{}] exec  <= {}", rb_task_id, e),
-                        );
-                    }
-                }
+            let rb_task_id = new_snowflake_id();
+            let mut result = Ok(ExecResult::default());
+            let (run, after_args) =
+                before_sql(&self.rb, rb_task_id, &mut sql, &mut args, &mut result).await?;
+            let start = Instant::now();
+            if run {
+                result = self.conn.exec(&sql, args).await;
             }
+            let elapsed = start.elapsed();
+            after_sql(&self.rb, rb_task_id, &sql, &after_args, &mut result, elapsed).await?;
             result
         })
     }
@@ -118,54 +135,23 @@ impl Executor for RBatisConnExecutor {
         let mut sql = sql.to_string();
         Box::pin(async move {
             let rb_task_id = new_snowflake_id();
-            let is_prepared = args.len() > 0;
-            for item in self.rbatis_ref().sql_intercepts.iter() {
-                item.do_intercept(self.rbatis_ref(), &mut sql, &mut args, is_prepared)?;
-            }
-            if self.rbatis_ref().log_plugin.is_enable() {
-                let b = Value::Array(args);
-                self.rbatis_ref().log_plugin.do_log(
-                    LevelFilter::Info,
-                    &format!(
-                        "[rbatis] [{}] query => `{}` {}",
-                        rb_task_id,
-                        &sql,
-                        &b
-                    ),
-                );
-                args = b.into();
-            }
-            let mut result = self.conn.get_values(&sql, args).await;
-            if self.rbatis_ref().log_plugin.is_enable() {
-                result = match result {
-                    Ok(result) => {
-                        let result_len = result.len();
-                        let data = Value::Array(result);
-                        if is_debug_mode() {
-                            self.rbatis_ref().log_plugin.do_log(
-                                LevelFilter::Info,
-                                &format!("[rbatis] [{}] query <= len={},rows={}", rb_task_id, result_len, &data),
-                            );
-                        } else {
-                            self.rbatis_ref().log_plugin.do_log(
-                                LevelFilter::Info,
-                                &format!("[rbatis] [{}] query <= len={}", rb_task_id, result_len),
-                            );
-                        }
-                        Ok(data.into())
-                    }
-                    Err(e) => {
-                        self.rbatis_ref().log_plugin.do_log(
-                            LevelFilter::Error,
-                            &format!("[rbatis] [{}] query <= {}", rb_task_id, e),
-                        );
-                        Err(e)
-                    }
-                }
+            let mut result = Ok(vec![]);
+            let (run, after_args) =
+                before_sql(&self.rb, rb_task_id, &mut sql, &mut args, &mut result).await?;
+            let start = Instant::now();
+            if run {
+                result = self.conn.get_values(&sql, args).await;
             }
+            let elapsed = start.elapsed();
+            after_sql(&self.rb, rb_task_id, &sql, &after_args, &mut result, elapsed).await?;
             Ok(Value::Array(result?))
         })
     }
+
+    fn query_stream(&mut self, sql: &str, args: Vec<Value>) -> BoxStream<'_, Result<Value, Error>> {
+        let rb_task_id = new_snowflake_id();
+        query_stream(&self.rb, &mut self.conn, rb_task_id, sql, args)
+    }
 }
 
 impl RbatisRef for RBatisConnExecutor {
@@ -182,8 +168,30 @@ impl RBatisConnExecutor {
             conn: tx,
             rb: self.rb,
             done: false,
+            depth: 0,
+            end_sql: vec![],
         });
     }
+
+    /// begin a transaction with isolation level and access mode.
+    /// the begin sql run with the intercepts and log just like `exec`
+    pub async fn begin_with(self, options: TxOptions) -> crate::Result<RBatisTxExecutor> {
+        let driver_type = self.rb.driver_type()?;
+        let begin_sql = options.begin_sql(driver_type)?;
+        let end_sql = options.end_sql(driver_type);
+        let mut tx = RBatisTxExecutor {
+            tx_id: new_snowflake_id(),
+            conn: self.conn,
+            rb: self.rb,
+            done: false,
+            depth: 0,
+            end_sql,
+        };
+        for sql in begin_sql {
+            Executor::exec(&mut tx, &sql, vec![]).await?;
+        }
+        Ok(tx)
+    }
 }
 
 pub struct RBatisTxExecutor {
@@ -191,12 +199,17 @@ pub struct RBatisTxExecutor {
     pub conn: Box<dyn Connection>,
     pub rb: Rbatis,
     pub done: bool,
+    /// nested transaction depth, 0 is the outermost transaction. every level above 0 is a savepoint
+    pub depth: u32,
+    /// the sql run after the transaction end, it restores the session options of `begin_with`
+    pub end_sql: Vec<String>,
 }
 
 impl Debug for RBatisTxExecutor {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RBatisTxExecutor")
             .field("tx_id", &self.tx_id)
+            .field("depth", &self.depth)
             .field("rb", &self.rb)
             .finish()
     }
@@ -221,6 +234,17 @@ impl<'a> RBatisTxExecutor {
         let v = Executor::query(self, sql, args).await?;
         Ok(decode(v)?)
     }
+    /// query and decode rows one by one
+    pub fn query_stream_decode<T>(
+        &mut self,
+        sql: &str,
+        args: Vec<Value>,
+    ) -> BoxStream<'_, Result<T, Error>>
+        where
+            T: DeserializeOwned + Send + 'static,
+    {
+        decode_stream(Executor::query_stream(self, sql, args))
+    }
 }
 
 impl Executor for RBatisTxExecutor {
@@ -231,43 +255,18 @@ impl Executor for RBatisTxExecutor {
     ) -> BoxFuture<'_, Result<ExecResult, Error>> {
         let mut sql = sql.to_string();
         Box::pin(async move {
-            let is_prepared = args.len() > 0;
-            for item in self.rbatis_ref().sql_intercepts.iter() {
-                item.do_intercept(self.rbatis_ref(), &mut sql, &mut args, is_prepared)?;
-            }
-            if self.rbatis_ref().log_plugin.is_enable() {
-                let b = Value::Array(args);
-                self.rbatis_ref().log_plugin.do_log(
-                    LevelFilter::Info,
-                    &format!(
-                        "[rbatis] [{}] exec  => `{}` {}",
-                        self.tx_id,
-                        &sql,
-                        &b
-                    ),
-                );
-                args = b.into();
+            if let Some(parts) = split_sql(&self.rb, &sql, &args)? {
+                return exec_parts(self, parts).await;
             }
-            let result = self.conn.exec(&sql, args).await;
-            if self.rbatis_ref().log_plugin.is_enable() {
-                match &result {
-                    Ok(result) => {
-                        self.rbatis_ref().log_plugin.do_log(
-                            LevelFilter::Info,
-                            &format!(
-                                "[rbatis] [{}] exec  <= rows_affected={}",
-                                self.tx_id, result.rows_affected
-                            ),
-                        );
-                    }
-                    Err(e) => {
-                        self.rbatis_ref().log_plugin.do_log(
-                            LevelFilter::Error,
-                            &format!("[rbatis] [{}] exec  <= {}", self.tx_id, e),
-                        );
-                    }
-                }
+            let mut result = Ok(ExecResult::default());
+            let (run, after_args) =
+                before_sql(&self.rb, self.tx_id, &mut sql, &mut args, &mut result).await?;
+            let start = Instant::now();
+            if run {
+                result = self.conn.exec(&sql, args).await;
             }
+            let elapsed = start.elapsed();
+            after_sql(&self.rb, self.tx_id, &sql, &after_args, &mut result, elapsed).await?;
             result
         })
     }
@@ -275,54 +274,22 @@ impl Executor for RBatisTxExecutor {
     fn query(&mut self, sql: &str, mut args: Vec<Value>) -> BoxFuture<'_, Result<Value, Error>> {
         let mut sql = sql.to_string();
         Box::pin(async move {
-            let is_prepared = args.len() > 0;
-            for item in self.rbatis_ref().sql_intercepts.iter() {
-                item.do_intercept(self.rbatis_ref(), &mut sql, &mut args, is_prepared)?;
-            }
-            if self.rbatis_ref().log_plugin.is_enable() {
-                let b = Value::Array(args);
-                self.rbatis_ref().log_plugin.do_log(
-                    LevelFilter::Info,
-                    &format!(
-                        "[rbatis] [{}] query => `{}` {}",
-                        self.tx_id,
-                        &sql,
-                        &b
-                    ),
-                );
-                args = b.into();
-            }
-            let mut result = self.conn.get_values(&sql, args).await;
-            if self.rbatis_ref().log_plugin.is_enable() {
-                result = match result {
-                    Ok(result) => {
-                        let result_len = result.len();
-                        let data = Value::Array(result);
-                        if is_debug_mode() {
-                            self.rbatis_ref().log_plugin.do_log(
-                                LevelFilter::Info,
-                                &format!("[rbatis] [{}] query <= len={},rows = {}", self.tx_id, result_len, &data),
-                            );
-                        } else {
-                            self.rbatis_ref().log_plugin.do_log(
-                                LevelFilter::Info,
-                                &format!("[rbatis] [{}] query <= len={}", self.tx_id, result_len),
-                            );
-                        }
-                        Ok(data.into())
-                    }
-                    Err(e) => {
-                        self.rbatis_ref().log_plugin.do_log(
-                            LevelFilter::Error,
-                            &format!("[rbatis] [{}] query <= {}", self.tx_id, e),
-                        );
-                        Err(e)
-                    }
-                }
+            let mut result = Ok(vec![]);
+            let (run, after_args) =
+                before_sql(&self.rb, self.tx_id, &mut sql, &mut args, &mut result).await?;
+            let start = Instant::now();
+            if run {
+                result = self.conn.get_values(&sql, args).await;
             }
+            let elapsed = start.elapsed();
+            after_sql(&self.rb, self.tx_id, &sql, &after_args, &mut result, elapsed).await?;
             Ok(Value::Array(result?))
         })
     }
+
+    fn query_stream(&mut self, sql: &str, args: Vec<Value>) -> BoxStream<'_, Result<Value, Error>> {
+        query_stream(&self.rb, &mut self.conn, self.tx_id, sql, args)
+    }
 }
 
 impl RbatisRef for RBatisTxExecutor {
@@ -332,23 +299,61 @@ impl RbatisRef for RBatisTxExecutor {
 }
 
 impl RBatisTxExecutor {
+    /// begin a nested transaction(savepoint `sp_N`) if the transaction is not done,
+    /// otherwise begin a new transaction on this connection
     pub async fn begin(mut self) -> crate::Result<Self> {
-        self.conn = self.conn.begin().await?;
+        if self.done {
+            self.conn = self.conn.begin().await?;
+            self.done = false;
+            self.depth = 0;
+            return Ok(self);
+        }
+        let save_point = SavePoint::new(self.rb.driver_type()?, self.depth + 1);
+        self.conn.exec(&save_point.begin_sql(), vec![]).await?;
+        self.depth += 1;
         return Ok(self);
     }
+
+    /// commit the transaction, or release the savepoint if it is a nested transaction
     pub async fn commit(&mut self) -> crate::Result<bool> {
+        if self.depth > 0 {
+            let save_point = SavePoint::new(self.rb.driver_type()?, self.depth);
+            if let Some(sql) = save_point.commit_sql() {
+                self.conn.exec(&sql, vec![]).await?;
+            }
+            self.depth -= 1;
+            return Ok(true);
+        }
         if let Ok(()) = self.conn.commit().await {
             self.done = true;
+            self.end().await?;
         }
         return Ok(self.done);
     }
+
+    /// rollback the transaction, or rollback to the savepoint if it is a nested transaction
     pub async fn rollback(&mut self) -> crate::Result<bool> {
+        if self.depth > 0 {
+            let save_point = SavePoint::new(self.rb.driver_type()?, self.depth);
+            self.conn.exec(&save_point.rollback_sql(), vec![]).await?;
+            self.depth -= 1;
+            return Ok(true);
+        }
         if let Ok(()) = self.conn.rollback().await {
             self.done = true;
+            self.end().await?;
         }
         return Ok(self.done);
     }
 
+    /// run the `end_sql` after the transaction end
+    pub(crate) async fn end(&mut self) -> crate::Result<()> {
+        for sql in std::mem::take(&mut self.end_sql) {
+            Executor::exec(self, &sql, vec![]).await?;
+        }
+        Ok(())
+    }
+
     pub fn take_conn(self) -> Box<dyn Connection> {
         return self.conn;
     }
@@ -489,6 +494,13 @@ impl Executor for RBatisTxExecutorGuard {
             }
         })
     }
+
+    fn query_stream(&mut self, sql: &str, args: Vec<Value>) -> BoxStream<'_, Result<Value, Error>> {
+        match self.tx.as_mut() {
+            None => futures::stream::once(async { Err(Error::from("the tx is done!")) }).boxed(),
+            Some(v) => v.query_stream(sql, args),
+        }
+    }
 }
 
 impl Rbatis {
@@ -500,7 +512,7 @@ impl Rbatis {
 
     /// query raw Value
     pub async fn query(&self, sql: &str, args: Vec<Value>) -> Result<Value, Error> {
-        let mut conn = self.acquire().await?;
+        let mut conn = self.acquire_read().await?;
         let v = conn.query(sql, args).await?;
         Ok(v)
     }
@@ -510,10 +522,22 @@ impl Rbatis {
         where
             T: DeserializeOwned,
     {
-        let mut conn = self.acquire().await?;
+        let mut conn = self.acquire_read().await?;
         let v = conn.query(sql, args).await?;
         Ok(decode(v)?)
     }
+
+    /// query and decode rows one by one,the connection is held until the stream is dropped
+    pub fn query_stream_decode<T>(
+        &self,
+        sql: &str,
+        args: Vec<Value>,
+    ) -> BoxStream<'_, Result<T, Error>>
+        where
+            T: DeserializeOwned + Send + 'static,
+    {
+        decode_stream(acquire_query_stream(self, sql, args))
+    }
 }
 
 impl Executor for Rbatis {
@@ -528,10 +552,18 @@ impl Executor for Rbatis {
     fn query(&mut self, sql: &str, args: Vec<Value>) -> BoxFuture<'_, Result<Value, Error>> {
         let sql = sql.to_string();
         Box::pin(async move {
-            let mut conn = self.acquire().await?;
+            let mut conn = self.acquire_read().await?;
             conn.query(&sql, args).await
         })
     }
+
+    fn query_stream(&mut self, sql: &str, args: Vec<Value>) -> BoxStream<'_, Result<Value, Error>> {
+        acquire_query_stream(self, sql, args)
+    }
+
+    fn is_pool(&self) -> bool {
+        true
+    }
 }
 
 impl RbatisRef for &Rbatis {
@@ -552,8 +584,311 @@ impl Executor for &Rbatis {
     fn query(&mut self, sql: &str, args: Vec<Value>) -> BoxFuture<'_, Result<Value, Error>> {
         let sql = sql.to_string();
         Box::pin(async move {
-            let mut conn = self.acquire().await?;
+            let mut conn = self.acquire_read().await?;
             conn.query(&sql, args).await
         })
     }
+
+    fn query_stream(&mut self, sql: &str, args: Vec<Value>) -> BoxStream<'_, Result<Value, Error>> {
+        acquire_query_stream(self, sql, args)
+    }
+
+    fn is_pool(&self) -> bool {
+        true
+    }
+}
+
+/// run the intercepts and log plugin, then stream rows from the connection
+fn query_stream<'a>(
+    rb: &'a Rbatis,
+    conn: &'a mut Box<dyn Connection>,
+    rb_task_id: i64,
+    sql: &str,
+    mut args: Vec<Value>,
+) -> BoxStream<'a, Result<Value, Error>> {
+    let mut sql = sql.to_string();
+    Box::pin(TryAsyncStream::new(move |mut sender| async move {
+        let mut result = Ok(0);
+        let (run, after_args) =
+            before_sql(rb, rb_task_id, &mut sql, &mut args, &mut result).await?;
+        let start = Instant::now();
+        let mut drop_log = StreamDropLog {
+            rb,
+            task_id: rb_task_id,
+            start,
+            len: 0,
+            done: false,
+        };
+        if run {
+            result = async {
+                let mut rows = conn.get_values_stream(&sql, args);
+                while let Some(row) = rows.try_next().await? {
+                    drop_log.len += 1;
+                    // the stream is dropped
+                    if sender.send(Ok(row)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(drop_log.len)
+            }
+            .await;
+        }
+        drop_log.done = true;
+        let elapsed = start.elapsed();
+        after_sql(rb, rb_task_id, &sql, &after_args, &mut result, elapsed).await?;
+        result?;
+        Ok(())
+    }))
+}
+
+/// log the rows of the stream dropped before the end, `after_sql` is not run because it's async
+struct StreamDropLog<'a> {
+    rb: &'a Rbatis,
+    task_id: i64,
+    start: Instant,
+    len: u64,
+    done: bool,
+}
+
+impl Drop for StreamDropLog<'_> {
+    fn drop(&mut self) {
+        if !self.done && self.rb.log_plugin.is_enable() {
+            self.rb.log_plugin.do_log(
+                LevelFilter::Info,
+                &format!(
+                    "[rbatis] [{}] {} <= len={},elapsed={:?},the stream is dropped",
+                    self.task_id,
+                    <u64 as SqlResult>::NAME,
+                    self.len,
+                    self.start.elapsed()
+                ),
+            );
+        }
+    }
+}
+
+/// acquire a connection and stream rows from it, the connection is released when the stream ends
+fn acquire_query_stream<'a>(
+    rb: &'a Rbatis,
+    sql: &str,
+    args: Vec<Value>,
+) -> BoxStream<'a, Result<Value, Error>> {
+    let sql = sql.to_string();
+    Box::pin(TryAsyncStream::new(move |mut sender| async move {
+        let mut conn = rb.acquire_read().await?;
+        let mut rows = conn.query_stream(&sql, args);
+        while let Some(row) = rows.try_next().await? {
+            if sender.send(Ok(row)).await.is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }))
+}
+
+fn decode_stream<'a, T>(
+    stream: BoxStream<'a, Result<Value, Error>>,
+) -> BoxStream<'a, Result<T, Error>>
+    where
+        T: DeserializeOwned + Send + 'static,
+{
+    stream
+        .and_then(|row| async move { Ok(rbs::from_value::<T>(row)?) })
+        .boxed()
+}
+
+/// run `Intercept::before` one by one, return false if the sql is skipped
+async fn intercept_before(
+    intercepts: &SyncVec<Box<dyn Intercept>>,
+    rb: &Rbatis,
+    task_id: i64,
+    sql: &mut String,
+    args: &mut Vec<Value>,
+    mut result: ResultType<'_>,
+) -> Result<bool, Error> {
+    for item in intercepts.iter() {
+        if !item.before(rb, task_id, sql, args, result.reborrow()).await? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// run `Intercept::after` one by one
+async fn intercept_after(
+    intercepts: &SyncVec<Box<dyn Intercept>>,
+    rb: &Rbatis,
+    task_id: i64,
+    sql: &str,
+    args: &[Value],
+    mut result: ResultType<'_>,
+    elapsed: Duration,
+) -> Result<(), Error> {
+    for item in intercepts.iter() {
+        item.after(rb, task_id, sql, args, result.reborrow(), elapsed)
+            .await?;
+    }
+    Ok(())
+}
+
+/// the result of exec, query and query_stream
+trait SqlResult: Sized {
+    /// the name in log
+    const NAME: &'static str;
+
+    fn result_type(result: &mut Result<Self, Error>) -> ResultType<'_>;
+
+    /// the log of the result
+    fn log(&mut self, elapsed: Duration) -> String;
+}
+
+impl SqlResult for ExecResult {
+    const NAME: &'static str = "exec ";
+
+    fn result_type(result: &mut Result<Self, Error>) -> ResultType<'_> {
+        ResultType::Exec(result)
+    }
+
+    fn log(&mut self, elapsed: Duration) -> String {
+        format!("rows_affected={},elapsed={:?}", self.rows_affected, elapsed)
+    }
+}
+
+impl SqlResult for Vec<Value> {
+    const NAME: &'static str = "query";
+
+    fn result_type(result: &mut Result<Self, Error>) -> ResultType<'_> {
+        ResultType::Query(result)
+    }
+
+    fn log(&mut self, elapsed: Duration) -> String {
+        if !is_debug_mode() {
+            return format!("len={},elapsed={:?}", self.len(), elapsed);
+        }
+        let len = self.len();
+        let data = Value::Array(std::mem::take(self));
+        let log = format!("len={},elapsed={:?},rows={}", len, elapsed, &data);
+        *self = data.into();
+        log
+    }
+}
+
+/// the rows count of query_stream
+impl SqlResult for u64 {
+    const NAME: &'static str = "query";
+
+    fn result_type(result: &mut Result<Self, Error>) -> ResultType<'_> {
+        ResultType::Stream(result)
+    }
+
+    fn log(&mut self, elapsed: Duration) -> String {
+        format!("len={},elapsed={:?}", self, elapsed)
+    }
+}
+
+/// split the sql by `SqlIntercept::split`, the first intercept split it win
+fn split_sql(
+    rb: &Rbatis,
+    sql: &str,
+    args: &[Value],
+) -> Result<Option<SqlParts>, Error> {
+    for item in rb.sql_intercepts.iter() {
+        if let Some(parts) = item.split(rb, sql, args)? {
+            return Ok(Some(parts));
+        }
+    }
+    Ok(None)
+}
+
+/// exec the split sql one by one, sum the rows_affected and keep the last `last_insert_id`.
+/// it stop at the first error, so it is atomic only in a transaction
+async fn exec_parts<E: Executor>(
+    executor: &mut E,
+    parts: SqlParts,
+) -> Result<ExecResult, Error> {
+    let mut result = ExecResult::default();
+    for (sql, args) in parts {
+        let v = executor.exec(&sql, args).await?;
+        result.rows_affected += v.rows_affected;
+        result.last_insert_id = v.last_insert_id;
+    }
+    Ok(result)
+}
+
+/// run the sql intercepts, `Intercept::before` and log the sql.
+/// return false if the sql is skipped by `Intercept::before`, and the args for `Intercept::after`
+async fn before_sql<T: SqlResult>(
+    rb: &Rbatis,
+    task_id: i64,
+    sql: &mut String,
+    args: &mut Vec<Value>,
+    result: &mut Result<T, Error>,
+) -> Result<(bool, Vec<Value>), Error> {
+    let is_prepared = !args.is_empty();
+    for item in rb.sql_intercepts.iter() {
+        item.do_intercept(rb, sql, args, is_prepared)?;
+    }
+    let run = intercept_before(
+        &rb.intercepts,
+        rb,
+        task_id,
+        sql,
+        args,
+        T::result_type(result),
+    )
+    .await?;
+    if rb.log_plugin.is_enable() {
+        let b = Value::Array(std::mem::take(args));
+        rb.log_plugin.do_log(
+            LevelFilter::Info,
+            &format!("[rbatis] [{}] {} => `{}` {}", task_id, T::NAME, sql, &b),
+        );
+        *args = b.into();
+    }
+    let after_args = if rb.intercepts.is_empty() {
+        vec![]
+    } else {
+        args.clone()
+    };
+    Ok((run, after_args))
+}
+
+/// run `Intercept::after` and log the result, the error of `Intercept::after` is returned after the log
+async fn after_sql<T: SqlResult>(
+    rb: &Rbatis,
+    task_id: i64,
+    sql: &str,
+    args: &[Value],
+    result: &mut Result<T, Error>,
+    elapsed: Duration,
+) -> Result<(), Error> {
+    let after = intercept_after(
+        &rb.intercepts,
+        rb,
+        task_id,
+        sql,
+        args,
+        T::result_type(result),
+        elapsed,
+    )
+    .await;
+    if rb.log_plugin.is_enable() {
+        match result {
+            Ok(v) => rb.log_plugin.do_log(
+                LevelFilter::Info,
+                &format!("[rbatis] [{}] {} <= {}", task_id, T::NAME, v.log(elapsed)),
+            ),
+            Err(e) => rb.log_plugin.do_log(
+                LevelFilter::Error,
+                &format!(
+                    "[rbatis] [{}] {} <= {},elapsed={:?}",
+                    task_id,
+                    T::NAME,
+                    e,
+                    elapsed
+                ),
+            ),
+        }
+    }
+    after
 }
@@ -3,7 +3,9 @@ pub extern crate rbatis_codegen;
 extern crate rbatis_macro_driver;
 pub extern crate rbdc;
 
-pub use rbatis_macro_driver::{html_sql, py_sql, sql};
+pub use rbatis_macro_driver::{html_sql, py_sql, sql, Table};
+#[doc(hidden)]
+pub use rbatis_macro_driver::schema_check_table;
 
 pub mod plugin;
 
@@ -14,6 +16,7 @@ pub mod rbatis;
 pub mod utils;
 pub mod executor;
 pub mod sql;
+pub mod table;
 #[macro_use]
 pub mod crud;
 #[macro_use]
@@ -25,4 +28,6 @@ pub use crate::rbatis::RbatisOption;
 pub use crud::*;
 pub use decode::decode;
 pub use error::Error;
+pub use error::ErrorKind;
 pub use error::Result;
+pub use table::{ColumnMeta, TableMeta};
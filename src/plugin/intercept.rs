@@ -1,6 +1,13 @@
 use crate::rbatis::Rbatis;
 use crate::Error;
+use futures_core::future::BoxFuture;
+use rbdc::db::ExecResult;
 use rbs::Value;
+use std::any::Any;
+use std::time::Duration;
+
+/// the (sql, args) of `SqlIntercept::split`
+pub type SqlParts = Vec<(String, Vec<Value>)>;
 
 /// sql intercept
 pub trait SqlIntercept: Send + Sync {
@@ -13,6 +20,93 @@ pub trait SqlIntercept: Send + Sync {
         args: &mut Vec<Value>,
         is_prepared_sql: bool,
     ) -> Result<(), Error>;
+
+    /// split the sql of `exec` into many sql before `do_intercept`, they run one by one on the same connection
+    /// (and every one run the intercepts). for example, the insert rows of many shard tables. None is not split
+    fn split(&self, _rb: &Rbatis, _sql: &str, _args: &[Value]) -> Result<Option<SqlParts>, Error> {
+        Ok(None)
+    }
+}
+
+/// the sql result, `Intercept` can read or change it
+#[derive(Debug)]
+pub enum ResultType<'a> {
+    /// result of exec
+    Exec(&'a mut Result<ExecResult, Error>),
+    /// rows of query
+    Query(&'a mut Result<Vec<Value>, Error>),
+    /// rows count of query_stream, the rows are yielded to the stream and not held
+    Stream(&'a mut Result<u64, Error>),
+}
+
+impl ResultType<'_> {
+    /// reborrow it, so it can be passed to the next intercept
+    pub fn reborrow(&mut self) -> ResultType<'_> {
+        match self {
+            ResultType::Exec(v) => ResultType::Exec(v),
+            ResultType::Query(v) => ResultType::Query(v),
+            ResultType::Stream(v) => ResultType::Stream(v),
+        }
+    }
+}
+
+/// async intercept with before/after hooks, it run after the `SqlIntercept`
+///
+/// for example(audit log):
+/// ```rust
+/// use futures_core::future::BoxFuture;
+/// use rbatis::intercept::{Intercept, ResultType};
+/// use rbatis::Rbatis;
+/// use rbs::Value;
+/// use std::time::Duration;
+///
+/// pub struct AuditIntercept {}
+///
+/// impl Intercept for AuditIntercept {
+///     fn after<'a>(
+///         &'a self,
+///         _rb: &'a Rbatis,
+///         task_id: i64,
+///         sql: &'a str,
+///         args: &'a [Value],
+///         result: ResultType<'a>,
+///         elapsed: Duration,
+///     ) -> BoxFuture<'a, Result<(), rbatis::Error>> {
+///         Box::pin(async move {
+///             if let ResultType::Exec(Ok(v)) = result {
+///                 println!("[{}] `{}` {:?} rows_affected={} {:?}", task_id, sql, args, v.rows_affected, elapsed);
+///             }
+///             Ok(())
+///         })
+///     }
+/// }
+/// ```
+pub trait Intercept: Any + Send + Sync {
+    /// run before the sql, it can change sql/args.
+    /// return Ok(false) to skip the sql, then the `result` set by this method is returned(for example, the cached rows)
+    fn before<'a>(
+        &'a self,
+        _rb: &'a Rbatis,
+        _task_id: i64,
+        _sql: &'a mut String,
+        _args: &'a mut Vec<Value>,
+        _result: ResultType<'a>,
+    ) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async { Ok(true) })
+    }
+
+    /// run after the sql(or skipped by `before`), it can read or change the result or error
+    fn after<'a>(
+        &'a self,
+        _rb: &'a Rbatis,
+        _task_id: i64,
+        _sql: &'a str,
+        _args: &'a [Value],
+        _result: ResultType<'a>,
+        _elapsed: Duration,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async { Ok(()) })
+    }
 }
 
 /// Prevent full table updates and deletions
@@ -27,7 +121,7 @@ impl SqlIntercept for BlockAttackDeleteInterceptor {
         _args: &mut Vec<Value>,
         _is_prepared_sql: bool,
     ) -> Result<(), Error> {
-        let sql = sql.trim();
+        let sql = skip_comments(sql);
         if sql.starts_with(crate::sql::TEMPLATE.delete_from.value)
             && !sql.contains(crate::sql::TEMPLATE.r#where.left_right_space)
         {
@@ -52,7 +146,7 @@ impl SqlIntercept for BlockAttackUpdateInterceptor {
         _args: &mut Vec<Value>,
         _is_prepared_sql: bool,
     ) -> Result<(), Error> {
-        let sql = sql.trim();
+        let sql = skip_comments(sql);
         if sql.starts_with(crate::sql::TEMPLATE.update.value)
             && !sql.contains(crate::sql::TEMPLATE.r#where.left_right_space)
         {
@@ -64,3 +158,17 @@ impl SqlIntercept for BlockAttackUpdateInterceptor {
         return Ok(());
     }
 }
+
+/// skip the leading comments(for example `/* physical */` of `PHYSICAL_DELETE_HINT`), so the comment can not hide the sql
+fn skip_comments(sql: &str) -> &str {
+    let mut sql = sql.trim();
+    loop {
+        if let Some(v) = sql.strip_prefix("/*") {
+            sql = v.find("*/").map(|i| &v[i + 2..]).unwrap_or_default().trim();
+        } else if let Some(v) = sql.strip_prefix("--") {
+            sql = v.find('\n').map(|i| &v[i + 1..]).unwrap_or_default().trim();
+        } else {
+            return sql;
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use crate::intercept::SqlIntercept;
+use crate::rbatis::Rbatis;
+use crate::Error;
+use rbatis_codegen::identifier::quote_identifier;
+use rbs::Value;
+
+/// the sql start with this hint is a physical delete, `LogicDeleteIntercept` will not rewrite it
+pub const PHYSICAL_DELETE_HINT: &str = "/* physical */";
+
+/// logic(soft) delete.
+/// * `delete from table where ...` => `update table set column = deleted where ...`
+/// * `select ... from table ...` and `update table set ... where ...` append the condition `column = undeleted`,
+///   unless the where already contains the column(for example, query the deleted rows)
+/// * the sql start with `PHYSICAL_DELETE_HINT` is a physical delete, for example `delete_by_column_physical`,
+///   `delete_in_column_physical` of `impl_delete!` and `delete_by_id_physical` of `impl_by_id!`
+/// * the column is quoted by the driver type, for example `"delete_flag"` of postgres
+///
+/// for example:
+/// ```rust
+/// use rbatis::Rbatis;
+/// use rbatis::logic_delete::LogicDeleteIntercept;
+/// let mut rb = Rbatis::new();
+/// rb.set_sql_intercepts(vec![Box::new(LogicDeleteIntercept::new("delete_flag", 1, 0))]);
+/// ```
+#[derive(Debug)]
+pub struct LogicDeleteIntercept {
+    pub column: String,
+    pub deleted: Value,
+    pub undeleted: Value,
+    /// the tables have the column, empty is all tables
+    pub tables: Vec<String>,
+}
+
+impl LogicDeleteIntercept {
+    pub fn new(column: &str, deleted: impl Into<Value>, undeleted: impl Into<Value>) -> Self {
+        Self {
+            column: column.to_string(),
+            deleted: deleted.into(),
+            undeleted: undeleted.into(),
+            tables: vec![],
+        }
+    }
+
+    /// only rewrite sql of these tables
+    pub fn tables(mut self, tables: &[&str]) -> Self {
+        self.tables = tables.iter().map(|v| v.to_string()).collect();
+        self
+    }
+
+    fn is_logic_table(&self, table: &str) -> bool {
+        let table = table.trim_matches(|c| c == '`' || c == '"' || c == '[' || c == ']');
+        self.tables.is_empty() || self.tables.iter().any(|v| v.eq_ignore_ascii_case(table))
+    }
+
+    /// append `column = ?` to the where, or add the where before group by/order by/limit
+    fn append_where(&self, column: &str, sql: &mut String, args: &mut Vec<Value>, from: usize) {
+        let lower = sql.to_ascii_lowercase();
+        let tail = [" group by ", " order by ", " limit ", " offset "]
+            .iter()
+            .filter_map(|k| lower[from..].find(k).map(|v| v + from))
+            .min()
+            .unwrap_or(sql.len());
+        match lower[from..tail].find(" where ") {
+            Some(p) => {
+                let begin = from + p + " where ".len();
+                let condition = &lower[begin..tail];
+                if contains_word(condition, &self.column.to_ascii_lowercase()) {
+                    return;
+                }
+                let index = sql[..begin].matches('?').count();
+                let new_sql = format!(
+                    "{}{} = ? and ({}){}",
+                    &sql[..begin],
+                    column,
+                    sql[begin..tail].trim_end(),
+                    &sql[tail..]
+                );
+                *sql = new_sql;
+                args.insert(index.min(args.len()), self.undeleted.clone());
+            }
+            None => {
+                let index = sql[..tail].matches('?').count();
+                let new_sql = format!(
+                    "{} where {} = ?{}",
+                    sql[..tail].trim_end(),
+                    column,
+                    &sql[tail..]
+                );
+                *sql = new_sql;
+                args.insert(index.min(args.len()), self.undeleted.clone());
+            }
+        }
+    }
+}
+
+impl SqlIntercept for LogicDeleteIntercept {
+    fn do_intercept(
+        &self,
+        rb: &Rbatis,
+        sql: &mut String,
+        args: &mut Vec<Value>,
+        _is_prepared_sql: bool,
+    ) -> Result<(), Error> {
+        let trimmed = sql.trim_start();
+        if let Some(v) = trimmed.strip_prefix(PHYSICAL_DELETE_HINT) {
+            *sql = v.trim_start().to_string();
+            return Ok(());
+        }
+        let column = quote_identifier(rb.driver_type().unwrap_or_default(), &self.column);
+        let lower = trimmed.to_ascii_lowercase();
+        let mut words = lower.split_whitespace();
+        match words.next() {
+            Some("delete") => {
+                let table = match (words.next(), words.next()) {
+                    (Some("from"), Some(table)) => table,
+                    _ => return Ok(()),
+                };
+                if !self.is_logic_table(table) {
+                    return Ok(());
+                }
+                let begin = lower.find(" from ").unwrap_or_default() + " from ".len();
+                let begin = begin + lower[begin..].len() - lower[begin..].trim_start().len();
+                let end = begin + table.len();
+                let new_sql = format!(
+                    "update {} set {} = ?{}",
+                    &trimmed[begin..end],
+                    column,
+                    &trimmed[end..]
+                );
+                *sql = new_sql;
+                args.insert(0, self.deleted.clone());
+            }
+            Some("update") => {
+                let table = match words.next() {
+                    Some(table) => table,
+                    None => return Ok(()),
+                };
+                if !self.is_logic_table(table) {
+                    return Ok(());
+                }
+                *sql = trimmed.to_string();
+                let from = lower.find(" set ").unwrap_or_default();
+                self.append_where(&column, sql, args, from);
+            }
+            Some("select") => {
+                *sql = trimmed.to_string();
+                self.rewrite_select(&column, sql, args);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl LogicDeleteIntercept {
+    /// append the condition to the select, the sub query `from (select ...) as t` is rewritten instead of the select
+    /// (for example the count of page `select count(1) as count from (select ... from table) as rb_count_t`)
+    fn rewrite_select(&self, column: &str, sql: &mut String, args: &mut Vec<Value>) {
+        let lower = sql.to_ascii_lowercase();
+        let from = match lower.find(" from ") {
+            Some(v) => v,
+            None => return,
+        };
+        let table = match lower[from..].split_whitespace().nth(1) {
+            Some(table) => table,
+            None => return,
+        };
+        if table.starts_with('(') {
+            let open = from + lower[from..].find('(').unwrap_or_default();
+            let close = match find_close(&lower[open..]) {
+                Some(v) => open + v,
+                None => return,
+            };
+            if !lower[open + 1..close].trim_start().starts_with("select ") {
+                return;
+            }
+            let mut sub_sql = sql[open + 1..close].trim_start().to_string();
+            let first = sql[..open].matches('?').count().min(args.len());
+            let last = (first + sub_sql.matches('?').count()).min(args.len());
+            let mut sub_args = args[first..last].to_vec();
+            self.rewrite_select(column, &mut sub_sql, &mut sub_args);
+            sql.replace_range(open + 1..close, &sub_sql);
+            args.splice(first..last, sub_args);
+            return;
+        }
+        // skip the join and multiple tables
+        if !self.is_logic_table(table.trim_end_matches(','))
+            || lower.contains(" join ")
+            || table.ends_with(',')
+        {
+            return;
+        }
+        self.append_where(column, sql, args, from);
+    }
+}
+
+/// the index of `)` match the `(` at the start of sql
+fn find_close(sql: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in sql.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn contains_word(s: &str, word: &str) -> bool {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut from = 0;
+    while let Some(p) = s[from..].find(word) {
+        let start = from + p;
+        let end = start + word.len();
+        if !s[..start].ends_with(is_ident) && !s[end..].starts_with(is_ident) {
+            return true;
+        }
+        from = end;
+    }
+    false
+}
@@ -1,5 +1,10 @@
 pub mod intercept;
 pub mod log;
+pub mod logic_delete;
 pub mod object_id;
+pub mod replica;
+pub mod sharding;
+pub mod slow_query;
 pub mod snowflake;
 pub mod table_sync;
+pub mod version_lock;
@@ -0,0 +1,42 @@
+use rbdc::pool::Pool;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// choose a replica pool for the read query.
+/// `replicas` is never empty, and the return index must less than `replicas.len()`
+pub trait ReplicaRoute: Send + Sync + Debug {
+    fn select(&self, replicas: &[&Pool]) -> usize;
+}
+
+/// choose replicas one by one
+#[derive(Debug, Default)]
+pub struct RoundRobinRoute {
+    pub index: AtomicUsize,
+}
+
+impl ReplicaRoute for RoundRobinRoute {
+    fn select(&self, replicas: &[&Pool]) -> usize {
+        self.index.fetch_add(1, Ordering::Relaxed) % replicas.len()
+    }
+}
+
+/// choose the replica with the least connections in use
+#[derive(Debug, Default)]
+pub struct LeastConnectionsRoute {}
+
+impl ReplicaRoute for LeastConnectionsRoute {
+    fn select(&self, replicas: &[&Pool]) -> usize {
+        let mut index = 0;
+        let mut least = isize::MAX;
+        for (i, pool) in replicas.iter().enumerate() {
+            let status = pool.status();
+            // available is negative when tasks waiting for connection
+            let in_use = status.size as isize - status.available;
+            if in_use < least {
+                least = in_use;
+                index = i;
+            }
+        }
+        index
+    }
+}
@@ -0,0 +1,704 @@
+use crate::intercept::{SqlIntercept, SqlParts};
+use crate::rbatis::Rbatis;
+use crate::Error;
+use rbs::Value;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+/// shard strategy, map the shard key value to the table suffix.
+/// the shard table name is `{table}_{suffix}`
+pub trait ShardStrategy: Send + Sync + Debug {
+    /// table suffix of the shard key value
+    fn shard(&self, value: &Value) -> Result<String, Error>;
+    /// all table suffix, used by the query without shard key
+    fn shards(&self) -> Vec<String>;
+}
+
+/// `value % count`, the value must be a number(or number string)
+#[derive(Debug)]
+pub struct ModShard {
+    pub count: u64,
+}
+
+impl ModShard {
+    pub fn new(count: u64) -> Self {
+        Self { count }
+    }
+}
+
+impl ShardStrategy for ModShard {
+    fn shard(&self, value: &Value) -> Result<String, Error> {
+        let v = match value.as_str() {
+            Some(s) => s.parse::<i64>().ok(),
+            None => value.as_i64(),
+        }
+        .ok_or_else(|| {
+            Error::from(format!(
+                "[rbatis][ModShard] shard key must be a number, but is {}",
+                value
+            ))
+        })?;
+        Ok(v.rem_euclid(self.count as i64).to_string())
+    }
+
+    fn shards(&self) -> Vec<String> {
+        (0..self.count).map(|v| v.to_string()).collect()
+    }
+}
+
+/// `hash(value) % count`, the hash is FNV-1a, it's stable across rust version and platform
+#[derive(Debug)]
+pub struct HashShard {
+    pub count: u64,
+}
+
+impl HashShard {
+    pub fn new(count: u64) -> Self {
+        Self { count }
+    }
+}
+
+impl ShardStrategy for HashShard {
+    fn shard(&self, value: &Value) -> Result<String, Error> {
+        let v = match value.as_str() {
+            Some(s) => s.to_string(),
+            None => value.to_string(),
+        };
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for b in v.as_bytes() {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        Ok((hash % self.count).to_string())
+    }
+
+    fn shards(&self) -> Vec<String> {
+        (0..self.count).map(|v| v.to_string()).collect()
+    }
+}
+
+/// shard by date range. the value(DateTime,Date or date string) is compared with the begin date of each range
+///
+/// for example:
+/// ```rust
+/// use rbatis::sharding::DateRangeShard;
+/// // order_2022 save date < 2023-01-01, order_2023 save 2023-01-01 <= date
+/// let shard = DateRangeShard::new()
+///     .range("2022-01-01", "2022")
+///     .range("2023-01-01", "2023");
+/// ```
+#[derive(Debug, Default)]
+pub struct DateRangeShard {
+    /// (begin date, table suffix), sorted by begin date
+    pub ranges: Vec<(String, String)>,
+}
+
+impl DateRangeShard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add a range begin with the date(include)
+    pub fn range(mut self, begin: &str, suffix: &str) -> Self {
+        self.ranges.push((begin.to_string(), suffix.to_string()));
+        self.ranges.sort_by(|a, b| a.0.cmp(&b.0));
+        self
+    }
+}
+
+impl ShardStrategy for DateRangeShard {
+    fn shard(&self, value: &Value) -> Result<String, Error> {
+        let date = value.as_str().ok_or_else(|| {
+            Error::from(format!(
+                "[rbatis][DateRangeShard] shard key must be a date, but is {}",
+                value
+            ))
+        })?;
+        self.ranges
+            .iter()
+            .rev()
+            .find(|(begin, _)| date >= begin.as_str())
+            .map(|(_, suffix)| suffix.clone())
+            .ok_or_else(|| {
+                Error::from(format!(
+                    "[rbatis][DateRangeShard] no range for date {}",
+                    date
+                ))
+            })
+    }
+
+    fn shards(&self) -> Vec<String> {
+        self.ranges
+            .iter()
+            .map(|(_, suffix)| suffix.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct ShardTable {
+    /// shard key column
+    pub column: String,
+    pub strategy: Box<dyn ShardStrategy>,
+}
+
+/// rewrite the table name to the shard table by the shard key arg.
+/// * insert with the rows of many shards is split to one insert per shard(by `SqlIntercept::split`), run it in a transaction to be atomic
+/// * insert/update/delete/select with shard key(`column = ?` or `column in (?,?)` in the `and` chain after where) run on the shard table
+/// * select without shard key(or the where has `or`) run on all shard tables with `union all`, order by/limit apply on the merged result
+/// * update/delete without shard key is not allowed
+/// * every shard table of the sql is rewritten(for example join), but only one of them can run on all shard tables
+///
+/// for example:
+/// ```rust
+/// use rbatis::Rbatis;
+/// use rbatis::sharding::{ModShard, ShardingIntercept};
+/// let mut rb = Rbatis::new();
+/// rb.set_sql_intercepts(vec![Box::new(
+///     ShardingIntercept::new().table("biz_order", "user_id", ModShard::new(16)),
+/// )]);
+/// ```
+#[derive(Debug, Default)]
+pub struct ShardingIntercept {
+    /// the tables are processed by the order of name
+    pub tables: BTreeMap<String, ShardTable>,
+}
+
+impl ShardingIntercept {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// register the table with shard key column and strategy
+    pub fn table(
+        mut self,
+        table: &str,
+        column: &str,
+        strategy: impl ShardStrategy + 'static,
+    ) -> Self {
+        self.tables.insert(
+            table.to_string(),
+            ShardTable {
+                column: column.to_string(),
+                strategy: Box::new(strategy),
+            },
+        );
+        self
+    }
+}
+
+impl SqlIntercept for ShardingIntercept {
+    fn do_intercept(
+        &self,
+        _rb: &Rbatis,
+        sql: &mut String,
+        args: &mut Vec<Value>,
+        _is_prepared_sql: bool,
+    ) -> Result<(), Error> {
+        let mut fan_out_table: Option<(&str, Vec<String>)> = None;
+        for (table, shard_table) in &self.tables {
+            let (start, end) = match find_table(sql, table) {
+                None => continue,
+                Some(v) => v,
+            };
+            let lower = sql.trim_start().to_ascii_lowercase();
+            let is_insert = lower.starts_with("insert");
+            let is_select = lower.starts_with("select");
+            let arg_indexes = if is_insert {
+                find_insert_args(sql, end, &shard_table.column, args.len())
+            } else {
+                find_where_args(sql, &shard_table.column)
+            };
+            let mut shards = vec![];
+            if let Some(indexes) = arg_indexes {
+                for index in indexes {
+                    let value = args.get(index).ok_or_else(|| {
+                        Error::from(format!(
+                            "[rbatis][ShardingIntercept] shard key arg {} not found",
+                            index
+                        ))
+                    })?;
+                    let shard = shard_table.strategy.shard(value)?;
+                    if !shards.contains(&shard) {
+                        shards.push(shard);
+                    }
+                }
+            }
+            if shards.len() == 1 {
+                sql.replace_range(start..end, &format!("{}_{}", table, shards[0]));
+                continue;
+            }
+            if !is_select {
+                return Err(Error::from(format!(
+                    "[rbatis][ShardingIntercept] sql must run on one shard of '{}' by '{}', sql: {}",
+                    table, shard_table.column, sql
+                )));
+            }
+            if let Some((other, _)) = &fan_out_table {
+                return Err(Error::from(format!(
+                    "[rbatis][ShardingIntercept] sql must run on one shard of '{}' or '{}', sql: {}",
+                    other, table, sql
+                )));
+            }
+            if shards.is_empty() {
+                shards = shard_table.strategy.shards();
+            }
+            fan_out_table = Some((table, shards));
+        }
+        if let Some((table, shards)) = fan_out_table {
+            // the other shard tables maybe rewritten, so find the table again
+            if let Some((start, end)) = find_table(sql, table) {
+                fan_out_scope(sql, args, table, start, end, &shards);
+            }
+        }
+        Ok(())
+    }
+
+    fn split(&self, _rb: &Rbatis, sql: &str, args: &[Value]) -> Result<Option<SqlParts>, Error> {
+        if !sql.trim_start().to_ascii_lowercase().starts_with("insert") {
+            return Ok(None);
+        }
+        for (table, shard_table) in &self.tables {
+            if let Some((_, end)) = find_table(sql, table) {
+                return split_insert(sql, args, end, shard_table);
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// group the rows of `insert into table (c1,c2) VALUES (?,?),(?,?)` by the shard,
+/// return the insert of every shard, None if the rows are in one shard
+fn split_insert(
+    sql: &str,
+    args: &[Value],
+    table_end: usize,
+    shard_table: &ShardTable,
+) -> Result<Option<SqlParts>, Error> {
+    let open = match sql[table_end..].find('(') {
+        Some(v) => table_end + v,
+        None => return Ok(None),
+    };
+    let close = match find_close(&sql[open..]) {
+        Some(v) => open + v,
+        None => return Ok(None),
+    };
+    let column = split_items(&sql[open + 1..close]).iter().position(|c| {
+        c.trim()
+            .trim_matches(is_quote)
+            .eq_ignore_ascii_case(&shard_table.column)
+    });
+    let column = match column {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let values = match sql[close..].to_ascii_lowercase().find("values") {
+        Some(v) => close + v + "values".len(),
+        None => return Ok(None),
+    };
+    // (start, end, first arg, args len) of every row, and the shards
+    let mut rows = vec![];
+    let mut shards: Vec<(String, Vec<usize>)> = vec![];
+    let mut arg = count_args(&sql[..values]);
+    let prefix_args = arg;
+    let mut offset = values;
+    loop {
+        let rest = sql[offset..].trim_start();
+        let start = sql.len() - rest.len();
+        if !rest.starts_with('(') {
+            break;
+        }
+        let end = match find_close(rest) {
+            Some(v) => start + v + 1,
+            None => return Ok(None),
+        };
+        let items = split_items(&sql[start + 1..end - 1]);
+        let key = match items.get(column) {
+            Some(v) if v.trim() == "?" => {
+                arg + items[..column].iter().map(|v| count_args(v)).sum::<usize>()
+            }
+            _ => return Ok(None),
+        };
+        let value = args.get(key).ok_or_else(|| {
+            Error::from(format!(
+                "[rbatis][ShardingIntercept] shard key arg {} not found",
+                key
+            ))
+        })?;
+        let shard = shard_table.strategy.shard(value)?;
+        let len = count_args(&sql[start..end]);
+        match shards.iter_mut().find(|(v, _)| v == &shard) {
+            Some((_, v)) => v.push(rows.len()),
+            None => shards.push((shard, vec![rows.len()])),
+        }
+        rows.push((start, end, arg, len));
+        arg += len;
+        offset = end;
+        match sql[offset..].trim_start().strip_prefix(',') {
+            Some(v) => offset = sql.len() - v.len(),
+            None => break,
+        }
+    }
+    if shards.len() < 2 {
+        return Ok(None);
+    }
+    let (head, tail) = (&sql[..rows[0].0], &sql[offset..]);
+    let mut sqls = Vec::with_capacity(shards.len());
+    for (_, indexes) in shards {
+        let mut row_sql = Vec::with_capacity(indexes.len());
+        let mut row_args = args[..prefix_args].to_vec();
+        for i in indexes {
+            let (start, end, first, len) = rows[i];
+            row_sql.push(&sql[start..end]);
+            row_args.extend_from_slice(&args[first..first + len]);
+        }
+        row_args.extend_from_slice(&args[arg.min(args.len())..]);
+        sqls.push((format!("{}{}{}", head, row_sql.join(","), tail), row_args));
+    }
+    Ok(Some(sqls))
+}
+
+/// fan out the select of the table, or the sub query `(select ...)` of the table
+/// (for example the count of page `select count(1) as count from (select ... from table) as rb_count_t`)
+fn fan_out_scope(
+    sql: &mut String,
+    args: &mut Vec<Value>,
+    table: &str,
+    start: usize,
+    end: usize,
+    shards: &[String],
+) {
+    let (scope_start, scope_end) = match find_sub_query(sql, start) {
+        Some(v) => v,
+        None => return fan_out(sql, args, table, start, end, shards),
+    };
+    let mut sub_sql = sql[scope_start..scope_end].to_string();
+    let first = count_args(&sql[..scope_start]).min(args.len());
+    let last = (first + count_args(&sub_sql)).min(args.len());
+    let mut sub_args = args[first..last].to_vec();
+    fan_out(
+        &mut sub_sql,
+        &mut sub_args,
+        table,
+        start - scope_start,
+        end - scope_start,
+        shards,
+    );
+    sql.replace_range(scope_start..scope_end, &sub_sql);
+    args.splice(first..last, sub_args);
+}
+
+/// the byte range in the innermost `(select ...)` around the index
+fn find_sub_query(sql: &str, index: usize) -> Option<(usize, usize)> {
+    let mut opens = vec![];
+    for (i, c) in unquoted_chars(&sql[..index]) {
+        match c {
+            '(' => opens.push(i),
+            ')' => {
+                opens.pop();
+            }
+            _ => {}
+        }
+    }
+    for open in opens.into_iter().rev() {
+        let close = open + find_close(&sql[open..])?;
+        let sub = &sql[open + 1..close];
+        if starts_with_word(&sub.trim_start().to_ascii_lowercase(), "select") {
+            return Some((open + 1 + sub.len() - sub.trim_start().len(), close));
+        }
+    }
+    None
+}
+
+/// rewrite select to `union all` of every shard table
+fn fan_out(
+    sql: &mut String,
+    args: &mut Vec<Value>,
+    table: &str,
+    start: usize,
+    end: usize,
+    shards: &[String],
+) {
+    let lower = mask_literal(sql).to_ascii_lowercase();
+    let tail = [" order by ", " limit ", " offset "]
+        .iter()
+        .filter_map(|k| lower[end..].find(k).map(|v| v + end))
+        .min()
+        .unwrap_or(sql.len());
+    let from = lower[..start].rfind("from ").unwrap_or(0) + "from ".len();
+    let select = &sql[..from];
+    let is_count = select.to_ascii_lowercase().contains("count(");
+    let body_args = count_args(&sql[..tail]).min(args.len());
+    let tail_args = args.split_off(body_args);
+    let mut branches = Vec::with_capacity(shards.len());
+    for shard in shards {
+        let branch_select = if is_count { "select 1 from " } else { select };
+        branches.push(format!(
+            "{}{}{}_{}{}",
+            branch_select,
+            &sql[from..start],
+            table,
+            shard,
+            &sql[end..tail]
+        ));
+    }
+    let union = branches.join(" union all ");
+    let new_sql = if is_count {
+        format!("{}({}) as shard_t{}", select, union, &sql[tail..])
+    } else if tail < sql.len() {
+        format!("select * from ({}) as shard_t{}", union, &sql[tail..])
+    } else {
+        union
+    };
+    let body = std::mem::take(args);
+    for _ in shards {
+        args.extend(body.iter().cloned());
+    }
+    args.extend(tail_args);
+    *sql = new_sql;
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_quote(c: char) -> bool {
+    c == '`' || c == '"' || c == '[' || c == ']'
+}
+
+/// the chars(and byte index) of the sql out of the string literal `'...'`
+fn unquoted_chars(sql: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut in_literal = false;
+    sql.char_indices().filter(move |(_, c)| {
+        if *c == '\'' {
+            in_literal = !in_literal;
+            return false;
+        }
+        !in_literal
+    })
+}
+
+/// replace the string literal `'...'` with space(keep the byte index), so the keyword and `?` in it are not found
+fn mask_literal(sql: &str) -> String {
+    let mut masked = sql.as_bytes().to_vec();
+    let mut from = 0;
+    for (i, _) in unquoted_chars(sql) {
+        masked[from..i].fill(b' ');
+        from = i + 1;
+    }
+    masked[from..].fill(b' ');
+    String::from_utf8(masked).unwrap_or_default()
+}
+
+/// the count of `?` out of the string literal
+fn count_args(sql: &str) -> usize {
+    unquoted_chars(sql).filter(|(_, c)| *c == '?').count()
+}
+
+/// the index of `)` match the `(` at the start of sql
+fn find_close(sql: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in unquoted_chars(sql) {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// split the items by `,` out of the parentheses and string literal
+fn split_items(sql: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in unquoted_chars(sql) {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&sql[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&sql[start..]);
+    items
+}
+
+/// find the table name(without quote) after from/into/update/join, return byte range
+fn find_table(sql: &str, table: &str) -> Option<(usize, usize)> {
+    let masked = mask_literal(sql);
+    let sql = masked.as_str();
+    let mut prev = "";
+    let mut offset = 0;
+    for word in sql.split_whitespace() {
+        let word_start = offset + sql[offset..].find(word)?;
+        offset = word_start + word.len();
+        let keyword = prev.to_ascii_lowercase();
+        prev = word;
+        if !matches!(keyword.as_str(), "from" | "into" | "update" | "join") {
+            continue;
+        }
+        let name_start = word_start + word.len() - word.trim_start_matches(is_quote).len();
+        let name_len = sql[name_start..]
+            .find(|c: char| !is_ident_char(c))
+            .unwrap_or(sql.len() - name_start);
+        if sql[name_start..name_start + name_len].eq_ignore_ascii_case(table) {
+            return Some((name_start, name_start + name_len));
+        }
+    }
+    None
+}
+
+/// arg indexes of the shard column in `insert into table (c1,c2) VALUES (?,?),(?,?)`
+fn find_insert_args(
+    sql: &str,
+    table_end: usize,
+    column: &str,
+    args_len: usize,
+) -> Option<Vec<usize>> {
+    let open = table_end + sql[table_end..].find('(')?;
+    let close = open + sql[open..].find(')')?;
+    let columns: Vec<&str> = sql[open + 1..close]
+        .split(',')
+        .map(|c| c.trim().trim_matches(is_quote))
+        .collect();
+    let index = columns
+        .iter()
+        .position(|c| c.eq_ignore_ascii_case(column))?;
+    let mut indexes = vec![];
+    let mut i = index;
+    while i < args_len {
+        indexes.push(i);
+        i += columns.len();
+    }
+    Some(indexes)
+}
+
+/// arg indexes of `column = ?` or `column in (?,?)` in the `and` chain after where.
+/// None if the where has `or`, or the column is not a condition of the `and` chain(for example `not (column = ?)`)
+fn find_where_args(sql: &str, column: &str) -> Option<Vec<usize>> {
+    let lower = mask_literal(sql).to_ascii_lowercase();
+    let column = column.to_ascii_lowercase();
+    let where_start = find_word(&lower, "where")? + "where".len();
+    let where_end = [
+        " group by ",
+        " having ",
+        " order by ",
+        " limit ",
+        " offset ",
+    ]
+    .iter()
+    .filter_map(|k| lower[where_start..].find(k).map(|v| v + where_start))
+    .min()
+    .unwrap_or(lower.len());
+    if contains_word(&lower[where_start..where_end], "or") {
+        return None;
+    }
+    let mut from = where_start;
+    while let Some(p) = lower[from..where_end].find(&column) {
+        let start = from + p;
+        let end = start + column.len();
+        from = end;
+        if lower[..start].ends_with(is_ident_char) {
+            continue;
+        }
+        let rest = lower[end..where_end].trim_start_matches(is_quote);
+        if rest.starts_with(is_ident_char) {
+            continue;
+        }
+        if !in_and_chain(&lower[where_start..start]) {
+            continue;
+        }
+        let rest = rest.trim_start();
+        let begin = where_end - rest.len();
+        let (indexes, rest) = if let Some(v) = rest.strip_prefix('=') {
+            match v.trim_start().strip_prefix('?') {
+                Some(v) => (vec![lower[..begin].matches('?').count()], v),
+                None => continue,
+            }
+        } else if starts_with_word(rest, "in") {
+            match rest["in".len()..].trim_start().strip_prefix('(') {
+                Some(v) => {
+                    let close = v.find(')')?;
+                    let first = lower[..begin].matches('?').count();
+                    let len = v[..close].matches('?').count();
+                    ((first..first + len).collect(), &v[close + 1..])
+                }
+                None => continue,
+            }
+        } else {
+            continue;
+        };
+        // the condition end with `and`, `)` or the where
+        let rest = rest.trim_start();
+        if rest.is_empty() || rest.starts_with(')') || starts_with_word(rest, "and") {
+            return Some(indexes);
+        }
+    }
+    None
+}
+
+/// the condition after the prefix(of the where) is a condition of the `and` chain,
+/// the prefix end with `and`/`(`/nothing(the alias `t.` is skipped), and all the open `(` are groups of the chain
+fn in_and_chain(prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches(is_quote);
+    let prefix = match prefix.strip_suffix('.') {
+        Some(v) => v.trim_end_matches(is_ident_char).trim_end_matches(is_quote),
+        None => prefix,
+    };
+    let is_chain = |v: &str| {
+        let v = v.trim_end();
+        v.is_empty() || v.ends_with('(') || ends_with_word(v, "and")
+    };
+    // the open parentheses, true if it's a group of the chain
+    let mut groups: Vec<bool> = vec![];
+    for (i, c) in prefix.char_indices() {
+        match c {
+            '(' => {
+                let before = &prefix[..i];
+                let is_group = is_chain(before)
+                    && (!before.trim_end().ends_with('(') || groups.last() == Some(&true));
+                groups.push(is_group);
+            }
+            ')' => {
+                groups.pop();
+            }
+            _ => {}
+        }
+    }
+    groups.iter().all(|v| *v) && is_chain(prefix)
+}
+
+fn contains_word(s: &str, word: &str) -> bool {
+    find_word(s, word).is_some()
+}
+
+fn find_word(s: &str, word: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(p) = s[from..].find(word) {
+        let start = from + p;
+        let end = start + word.len();
+        if !s[..start].ends_with(is_ident_char) && !s[end..].starts_with(is_ident_char) {
+            return Some(start);
+        }
+        from = end;
+    }
+    None
+}
+
+fn starts_with_word(s: &str, word: &str) -> bool {
+    s.starts_with(word) && !s[word.len()..].starts_with(is_ident_char)
+}
+
+fn ends_with_word(s: &str, word: &str) -> bool {
+    s.ends_with(word) && !s[..s.len() - word.len()].ends_with(is_ident_char)
+}
@@ -0,0 +1,125 @@
+use crate::intercept::{Intercept, ResultType};
+use crate::rbatis::Rbatis;
+use crate::Error;
+use futures_core::future::BoxFuture;
+use log::LevelFilter;
+use rbs::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// log the sql with WARN level if it's elapsed time >= threshold,
+/// and optional log the `EXPLAIN` output of the slow query(postgres,mysql,sqlite)
+///
+/// for example:
+/// ```rust
+/// use rbatis::Rbatis;
+/// use rbatis::slow_query::SlowQueryIntercept;
+/// use std::time::Duration;
+/// let mut rb = Rbatis::new();
+/// rb.set_intercepts(vec![Box::new(
+///     SlowQueryIntercept::new(Duration::from_millis(500)).explain_sample(10),
+/// )]);
+/// ```
+#[derive(Debug)]
+pub struct SlowQueryIntercept {
+    pub threshold: Duration,
+    /// explain 1 of every `explain_sample` slow queries, 0 is disable
+    pub explain_sample: u64,
+    pub slow_count: AtomicU64,
+}
+
+impl SlowQueryIntercept {
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            explain_sample: 0,
+            slow_count: AtomicU64::new(0),
+        }
+    }
+
+    /// explain 1 of every `sample` slow queries, 0 is disable
+    pub fn explain_sample(mut self, sample: u64) -> Self {
+        self.explain_sample = sample;
+        self
+    }
+
+    /// the explain sql of driver, None if the driver is not support
+    pub fn explain_sql(driver_type: &str, sql: &str) -> Option<String> {
+        match driver_type {
+            "postgres" | "mysql" => Some(format!("explain {}", sql)),
+            "sqlite" => Some(format!("explain query plan {}", sql)),
+            _ => None,
+        }
+    }
+
+    fn explain(&self, rb: &Rbatis, task_id: i64, sql: &str, args: &[Value]) {
+        let sql = match Self::explain_sql(rb.driver_type().unwrap_or_default(), sql) {
+            None => return,
+            Some(v) => v,
+        };
+        let pool = match rb.get_pool() {
+            Err(_) => return,
+            Ok(v) => v,
+        };
+        let rb = rb.clone();
+        let args = args.to_vec();
+        // run on a new connection of the pool which run the query(the `rb` of replica connection is on the replica),
+        // so the current connection(maybe in transaction) is not blocked
+        pool.spawn_task(async move {
+            let result = match rb.acquire().await {
+                Ok(mut conn) => conn.conn.get_values(&sql, args).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(rows) => rb.log_plugin.do_log(
+                    LevelFilter::Warn,
+                    &format!(
+                        "[rbatis] [{}] slow sql explain <= {}",
+                        task_id,
+                        Value::Array(rows)
+                    ),
+                ),
+                Err(e) => rb.log_plugin.do_log(
+                    LevelFilter::Warn,
+                    &format!("[rbatis] [{}] slow sql explain <= {}", task_id, e),
+                ),
+            }
+        });
+    }
+}
+
+impl Intercept for SlowQueryIntercept {
+    fn after<'a>(
+        &'a self,
+        rb: &'a Rbatis,
+        task_id: i64,
+        sql: &'a str,
+        args: &'a [Value],
+        result: ResultType<'a>,
+        elapsed: Duration,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            if elapsed < self.threshold {
+                return Ok(());
+            }
+            rb.log_plugin.do_log(
+                LevelFilter::Warn,
+                &format!(
+                    "[rbatis] [{}] slow sql elapsed={:?} => `{}` {}",
+                    task_id,
+                    elapsed,
+                    sql,
+                    Value::Array(args.to_vec())
+                ),
+            );
+            let count = self.slow_count.fetch_add(1, Ordering::Relaxed);
+            // checked_rem is None if explain_sample is 0
+            if count.checked_rem(self.explain_sample) == Some(0)
+                && matches!(result, ResultType::Query(Ok(_)) | ResultType::Stream(Ok(_)))
+            {
+                self.explain(rb, task_id, sql, args);
+            }
+            Ok(())
+        })
+    }
+}
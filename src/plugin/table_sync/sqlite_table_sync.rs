@@ -1,6 +1,6 @@
 use crate::executor::RBatisConnExecutor;
 use crate::table_sync::TableSync;
-use crate::Error;
+use crate::{Error, ErrorKind};
 use futures_core::future::BoxFuture;
 use rbs::Value;
 
@@ -74,7 +74,7 @@ impl TableSync for SqliteTableSync {
                     match result_create {
                         Ok(_) => {}
                         Err(e) => {
-                            if e.to_string().contains("already exists") {
+                            if e.kind() == ErrorKind::AlreadyExists {
                                 for (k, v) in &m {
                                     let k = k.as_str().unwrap_or_default();
                                     let mut id_key = "";
@@ -96,7 +96,7 @@ impl TableSync for SqliteTableSync {
                                     {
                                         Ok(_) => {}
                                         Err(e) => {
-                                            if e.to_string().contains("duplicate column") {
+                                            if e.kind() == ErrorKind::AlreadyExists {
                                                 continue;
                                             }
                                             return Err(e);
@@ -0,0 +1,214 @@
+use crate::intercept::{Intercept, ResultType};
+use crate::rbatis::Rbatis;
+use crate::table::TableMeta;
+use crate::{Error, ErrorKind};
+use futures_core::future::BoxFuture;
+use rbatis_codegen::identifier::{quote_chars, quote_identifier};
+use rbs::Value;
+use std::cell::RefCell;
+use std::future::Future;
+use std::time::Duration;
+
+rbdc::rt::tokio::task_local! {
+    /// the new version of the update rewritten by `VersionLockIntercept` in the `VersionLockIntercept::scope`
+    static NEW_VERSION: RefCell<Option<Value>>;
+}
+
+/// optimistic lock by the version column.
+/// * `update table set a=?,version=? where id = ?` => `update table set a=?,version = version + 1 where (id = ?) and version = ?`
+/// * the rewritten update return `ErrorKind::ConcurrencyConflict` if rows_affected is 0(the row is changed by others)
+/// * the update without version value(null) or where is not rewritten
+///
+/// use `update_by_column_version` of `impl_update!` to write the new version back into the struct,
+/// the new version is recorded by the intercept in the `VersionLockIntercept::scope` of the update.
+///
+/// for example:
+/// ```rust
+/// use rbatis::Rbatis;
+/// use rbatis::version_lock::VersionLockIntercept;
+/// let mut rb = Rbatis::new();
+/// rb.set_intercepts(vec![Box::new(VersionLockIntercept::new("version"))]);
+/// ```
+#[derive(Debug)]
+pub struct VersionLockIntercept {
+    pub column: String,
+    /// the tables have the column, empty is all tables
+    pub tables: Vec<String>,
+}
+
+impl VersionLockIntercept {
+    pub fn new(column: &str) -> Self {
+        Self {
+            column: column.to_string(),
+            tables: vec![],
+        }
+    }
+
+    /// only rewrite sql of these tables
+    pub fn tables(mut self, tables: &[&str]) -> Self {
+        self.tables = tables.iter().map(|v| v.to_string()).collect();
+        self
+    }
+
+    fn is_lock_table(&self, table: &str) -> bool {
+        let table = table.trim_matches(|c| c == '`' || c == '"' || c == '[' || c == ']');
+        self.tables.is_empty() || self.tables.iter().any(|v| v.eq_ignore_ascii_case(table))
+    }
+
+    /// the column quoted by the driver type
+    fn quote_column(&self, driver_type: &str) -> String {
+        quote_identifier(driver_type, &self.column)
+    }
+
+    fn increase_sql(&self, driver_type: &str) -> String {
+        let column = self.quote_column(driver_type);
+        format!("{} = {} + 1", column, column)
+    }
+
+    /// run the update in the scope, return the new version if the update is rewritten and done by the intercept
+    pub async fn scope<F: Future>(f: F) -> (F::Output, Option<Value>) {
+        NEW_VERSION
+            .scope(RefCell::new(None), async move {
+                let output = f.await;
+                (output, NEW_VERSION.with(|v| v.borrow_mut().take()))
+            })
+            .await
+    }
+
+    /// write the new version(of `VersionLockIntercept::scope`) into the row(the fields of struct)
+    pub fn write_version(&self, meta: Option<&TableMeta>, row: &mut Value, version: Value) {
+        let field = match meta.and_then(|m| {
+            m.columns
+                .iter()
+                .find(|c| c.name.eq_ignore_ascii_case(&self.column))
+        }) {
+            Some(c) if c.skip => return,
+            Some(c) => c.field,
+            None => self.column.as_str(),
+        };
+        row[field] = version;
+    }
+
+    /// rewrite `version=?` in set to `version = version + 1`, and move the arg to `and version = ?`.
+    /// the column in set maybe quoted by the driver type, for example `` `version`=? ``
+    fn rewrite(&self, driver_type: &str, sql: &mut String, args: &mut Vec<Value>) {
+        let lower = sql.trim_start().to_ascii_lowercase();
+        let mut words = lower.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("update"), Some(table)) if self.is_lock_table(table) => {}
+            _ => return,
+        }
+        let set = match lower.find(" set ") {
+            Some(v) => v + " set ".len(),
+            None => return,
+        };
+        let r#where = match lower[set..].find(" where ") {
+            Some(v) => set + v,
+            None => return,
+        };
+        let column = self.column.to_ascii_lowercase();
+        let mut offset = set;
+        let mut item = None;
+        for v in lower[set..r#where].split(',') {
+            let mut kv = v.splitn(2, '=');
+            if let (Some(k), Some(value)) = (kv.next(), kv.next()) {
+                let k = k.trim();
+                let k = match quote_chars(driver_type) {
+                    Some((open, close)) => k
+                        .strip_prefix(open)
+                        .and_then(|k| k.strip_suffix(close))
+                        .unwrap_or(k),
+                    None => k,
+                };
+                if k == column && value.trim() == "?" {
+                    item = Some((offset, offset + v.len()));
+                    break;
+                }
+            }
+            offset += v.len() + 1;
+        }
+        let (start, end) = match item {
+            Some(v) => v,
+            None => return,
+        };
+        let trimmed = sql.trim_start();
+        let index = trimmed[..start].matches('?').count();
+        if index >= args.len() {
+            return;
+        }
+        let version = args.remove(index);
+        let condition = trimmed[r#where + " where ".len()..].trim_end();
+        let new_sql = format!(
+            "{}{}{} where ({}) and {} = ?",
+            &trimmed[..start],
+            self.increase_sql(driver_type),
+            &trimmed[end..r#where],
+            condition,
+            self.quote_column(driver_type)
+        );
+        *sql = new_sql;
+        args.push(version);
+    }
+}
+
+impl Intercept for VersionLockIntercept {
+    fn before<'a>(
+        &'a self,
+        rb: &'a Rbatis,
+        _task_id: i64,
+        sql: &'a mut String,
+        args: &'a mut Vec<Value>,
+        result: ResultType<'a>,
+    ) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async move {
+            if let ResultType::Exec(_) = result {
+                self.rewrite(rb.driver_type().unwrap_or_default(), sql, args);
+            }
+            Ok(true)
+        })
+    }
+
+    fn after<'a>(
+        &'a self,
+        rb: &'a Rbatis,
+        _task_id: i64,
+        sql: &'a str,
+        args: &'a [Value],
+        result: ResultType<'a>,
+        _elapsed: Duration,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            if let ResultType::Exec(result) = result {
+                let increase_sql = self.increase_sql(rb.driver_type().unwrap_or_default());
+                if !sql.contains(&increase_sql) {
+                    return Ok(());
+                }
+                if matches!(result, Ok(v) if v.rows_affected != 0) {
+                    // the old version is the last arg of the rewritten update
+                    let version = args.last().and_then(next_version);
+                    let _ = NEW_VERSION.try_with(|v| *v.borrow_mut() = version);
+                } else if result.is_ok() {
+                    *result = Err(Error::new(
+                        ErrorKind::ConcurrencyConflict,
+                        format!(
+                            "[rbatis][VersionLockIntercept] the row is changed by others(version mismatch), sql: {}",
+                            sql
+                        ),
+                    ));
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// the next version of the version value, None if it's null or not a integer
+pub fn next_version(version: &Value) -> Option<Value> {
+    match version {
+        Value::I32(v) => Some(Value::I32(v + 1)),
+        Value::I64(v) => Some(Value::I64(v + 1)),
+        Value::U32(v) => Some(Value::U32(v + 1)),
+        Value::U64(v) => Some(Value::U64(v + 1)),
+        _ => None,
+    }
+}
@@ -0,0 +1,277 @@
+use crate::sql::DEFAULT_PAGE_SIZE;
+use crate::table::TableMeta;
+use crate::Error;
+use rbs::value::map::ValueMap;
+use rbs::Value;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// keyset(cursor) page request, the first page has no cursor
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct CursorPageRequest {
+    /// `next_cursor` or `prev_cursor` of the `CursorPage`
+    pub cursor: Option<String>,
+    /// page page_size default 10
+    pub page_size: u64,
+}
+
+impl CursorPageRequest {
+    pub fn new(page_size: u64) -> Self {
+        Self {
+            cursor: None,
+            page_size,
+        }
+    }
+
+    /// set the cursor, for example `page.next_cursor`
+    pub fn cursor(mut self, cursor: Option<String>) -> Self {
+        self.cursor = cursor;
+        self
+    }
+}
+
+impl Default for CursorPageRequest {
+    fn default() -> Self {
+        CursorPageRequest::new(DEFAULT_PAGE_SIZE)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct CursorPage<T> {
+    /// data
+    pub records: Vec<T>,
+    /// default 10
+    pub page_size: u64,
+    /// the cursor of next page, None if there is no more rows
+    pub next_cursor: Option<String>,
+    /// the cursor of previous page, None if it's the first page
+    pub prev_cursor: Option<String>,
+}
+
+impl<T> Default for CursorPage<T> {
+    fn default() -> Self {
+        CursorPage {
+            records: vec![],
+            page_size: DEFAULT_PAGE_SIZE,
+            next_cursor: None,
+            prev_cursor: None,
+        }
+    }
+}
+
+/// the decoded cursor, it's the values of order columns of the first/last row
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cursor {
+    /// true is the cursor of previous page
+    pub backward: bool,
+    pub values: Vec<Value>,
+}
+
+impl Cursor {
+    /// encode to the opaque hex string
+    pub fn encode(&self) -> String {
+        let mut buf = vec![if self.backward { b'p' } else { b'n' }];
+        for v in &self.values {
+            encode_value(v, &mut buf);
+        }
+        hex::encode(buf)
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, Error> {
+        let err = || Error::from(format!("[rbatis] invalid cursor: {}", cursor));
+        let buf = hex::decode(cursor).map_err(|_| err())?;
+        let backward = match buf.first() {
+            Some(b'p') => true,
+            Some(b'n') => false,
+            _ => return Err(err()),
+        };
+        let mut values = vec![];
+        let mut index = 1;
+        while index < buf.len() {
+            values.push(decode_value(&buf, &mut index).ok_or_else(err)?);
+        }
+        Ok(Self { backward, values })
+    }
+}
+
+fn encode_value(v: &Value, buf: &mut Vec<u8>) {
+    let push_str = |tag: u8, s: &str, buf: &mut Vec<u8>| {
+        buf.push(tag);
+        buf.extend((s.len() as u32).to_be_bytes());
+        buf.extend(s.as_bytes());
+    };
+    match v {
+        Value::Bool(b) => buf.extend([b'b', *b as u8]),
+        Value::I32(_) | Value::I64(_) => {
+            buf.push(b'i');
+            buf.extend(v.as_i64().unwrap_or_default().to_be_bytes());
+        }
+        Value::U32(_) | Value::U64(_) => {
+            buf.push(b'u');
+            buf.extend(v.as_u64().unwrap_or_default().to_be_bytes());
+        }
+        Value::F32(_) | Value::F64(_) => {
+            buf.push(b'f');
+            buf.extend(v.as_f64().unwrap_or_default().to_be_bytes());
+        }
+        Value::String(s) => push_str(b's', s, buf),
+        Value::Ext(name, v) => {
+            push_str(b'e', name, buf);
+            encode_value(v, buf);
+        }
+        _ => buf.push(b'0'),
+    }
+}
+
+fn take<'a>(buf: &'a [u8], index: &mut usize, len: usize) -> Option<&'a [u8]> {
+    let v = buf.get(*index..*index + len)?;
+    *index += len;
+    Some(v)
+}
+
+fn take_8(buf: &[u8], index: &mut usize) -> Option<[u8; 8]> {
+    take(buf, index, 8)?.try_into().ok()
+}
+
+fn decode_value(buf: &[u8], index: &mut usize) -> Option<Value> {
+    let tag = take(buf, index, 1)?[0];
+    let v = match tag {
+        b'0' => Value::Null,
+        b'b' => Value::Bool(take(buf, index, 1)?[0] != 0),
+        b'i' => Value::I64(i64::from_be_bytes(take_8(buf, index)?)),
+        b'u' => Value::U64(u64::from_be_bytes(take_8(buf, index)?)),
+        b'f' => Value::F64(f64::from_be_bytes(take_8(buf, index)?)),
+        b's' | b'e' => {
+            let len = u32::from_be_bytes(take(buf, index, 4)?.try_into().ok()?) as usize;
+            let s = String::from_utf8(take(buf, index, len)?.to_vec()).ok()?;
+            if tag == b's' {
+                Value::String(s)
+            } else {
+                let name = match s.as_str() {
+                    "Date" => "Date",
+                    "DateTime" => "DateTime",
+                    "Time" => "Time",
+                    "Timestamp" => "Timestamp",
+                    "Decimal" => "Decimal",
+                    "Uuid" => "Uuid",
+                    "Json" => "Json",
+                    _ => return None,
+                };
+                Value::Ext(name, Box::new(decode_value(buf, index)?))
+            }
+        }
+        _ => return None,
+    };
+    Some(v)
+}
+
+/// the keyset where of cursor, for example columns `(create_time,id)` with desc order:
+/// `((create_time < ?) or (create_time = ? and id < ?))`.
+/// the row value compare `(create_time,id) < (?,?)` is not support by mssql, so it's expanded.
+/// return the array of `{sql: arg}`, the where is the sql and arg of every item and end with `))`
+pub fn cursor_condition(columns: &[&str], desc: bool, cursor: &Cursor) -> Result<Value, Error> {
+    if cursor.values.len() != columns.len() {
+        return Err(Error::from(format!(
+            "[rbatis] the cursor has {} values, but the order columns is {:?}",
+            cursor.values.len(),
+            columns
+        )));
+    }
+    // the previous page is reverse order
+    let op = if desc != cursor.backward { "<" } else { ">" };
+    let mut items = vec![];
+    for i in 0..columns.len() {
+        for (j, (column, value)) in columns.iter().zip(&cursor.values).enumerate().take(i + 1) {
+            let prefix = if i == 0 && j == 0 {
+                "(("
+            } else if j == 0 {
+                ") or ("
+            } else {
+                " and "
+            };
+            let cmp = if j < i { "=" } else { op };
+            let mut item = ValueMap::new();
+            item.insert(
+                Value::String(format!("{}{} {} ", prefix, column, cmp)),
+                value.clone(),
+            );
+            items.push(Value::Map(item));
+        }
+    }
+    Ok(Value::Array(items))
+}
+
+/// the order by of cursor page, for example `create_time desc,id desc`
+pub fn cursor_order(columns: &[&str], desc: bool, backward: bool) -> String {
+    let order = if desc != backward { "desc" } else { "asc" };
+    columns
+        .iter()
+        .map(|c| format!("{} {}", c, order))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl<T: DeserializeOwned> CursorPage<T> {
+    /// make the page from `page_size + 1` rows selected by `cursor_condition` and `cursor_order`,
+    /// the cursor is read from the row by the field of the order column(the `TableMeta` rename)
+    pub fn from_rows(
+        mut rows: Vec<Value>,
+        meta: Option<&TableMeta>,
+        columns: &[&str],
+        page_size: u64,
+        cursor: Option<&Cursor>,
+    ) -> Result<Self, Error> {
+        let backward = cursor.map(|v| v.backward).unwrap_or_default();
+        let has_more = rows.len() as u64 > page_size;
+        rows.truncate(page_size as usize);
+        if backward {
+            rows.reverse();
+        }
+        let make_cursor = |row: Option<&Value>, backward: bool| {
+            row.map(|row| {
+                Cursor {
+                    backward,
+                    values: columns
+                        .iter()
+                        .map(|c| {
+                            // the column maybe `t.id`
+                            let name = c.rsplit('.').next().unwrap_or(c);
+                            let name = meta
+                                .and_then(|m| m.columns.iter().find(|v| v.name == name))
+                                .map(|v| v.field)
+                                .unwrap_or(name);
+                            row[name].clone()
+                        })
+                        .collect(),
+                }
+                .encode()
+            })
+        };
+        let (next_cursor, prev_cursor) = if backward {
+            let prev = if has_more {
+                make_cursor(rows.first(), true)
+            } else {
+                None
+            };
+            (make_cursor(rows.last(), false), prev)
+        } else {
+            let next = if has_more {
+                make_cursor(rows.last(), false)
+            } else {
+                None
+            };
+            let prev = if cursor.is_some() {
+                make_cursor(rows.first(), true)
+            } else {
+                None
+            };
+            (next, prev)
+        };
+        Ok(Self {
+            records: rbs::from_value(Value::Array(rows))?,
+            page_size,
+            next_cursor,
+            prev_cursor,
+        })
+    }
+}
@@ -1,7 +1,12 @@
 pub mod page;
 pub use page::*;
+pub mod cursor_page;
+pub use cursor_page::*;
+pub mod page_dialect;
 pub mod methods;
 pub mod template;
 pub mod tx;
+pub mod returning;
+pub mod upsert;
 pub use methods::*;
 pub use template::TEMPLATE;
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::fmt::{Debug, Display, Formatter};
+use std::future::Future;
 
 /// default 10
 pub const DEFAULT_PAGE_SIZE: u64 = 10;
@@ -67,6 +68,9 @@ pub struct Page<T> {
     pub page_size: u64,
     /// is search_count
     pub search_count: bool,
+    /// has next page, if search_count is false it is computed by select page_size + 1 rows
+    #[serde(default)]
+    pub has_next: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
@@ -78,6 +82,9 @@ pub struct PageRequest {
     /// page page_size default 10
     pub page_size: u64,
     pub search_count: bool,
+    /// run the count and select concurrently on two connections, only for the `Rbatis` executor
+    #[serde(default)]
+    pub concurrent: bool,
 }
 
 impl PageRequest {
@@ -103,8 +110,24 @@ impl PageRequest {
             page_size,
             page_no,
             search_count: true,
+            concurrent: false,
         };
     }
+
+    /// run the count and select concurrently on two connections, only for the `Rbatis` executor
+    pub fn set_concurrent(mut self, arg: bool) -> Self {
+        self.concurrent = arg;
+        self
+    }
+
+    /// the request with the page_size 0 replaced by `DEFAULT_PAGE_SIZE`, the same size as the `Page` of it
+    pub fn normalized(&self) -> Self {
+        let mut v = self.clone();
+        if v.page_size == 0 {
+            v.page_size = DEFAULT_PAGE_SIZE;
+        }
+        v
+    }
 }
 
 impl Default for PageRequest {
@@ -114,6 +137,7 @@ impl Default for PageRequest {
             page_size: DEFAULT_PAGE_SIZE,
             page_no: 1,
             search_count: true,
+            concurrent: false,
         };
     }
 }
@@ -183,6 +207,7 @@ impl<T> Page<T> {
                 page_no: 1 as u64,
                 records: vec![],
                 search_count: true,
+                has_next: total > page_size,
             };
         }
         return Self {
@@ -198,9 +223,20 @@ impl<T> Page<T> {
             page_no,
             records: vec![],
             search_count: true,
+            has_next: page_no * page_size < total,
         };
     }
 
+    /// create Page without count from the `page_size + 1` records, the total is unknown(0)
+    pub fn new_no_count(page_no: u64, page_size: u64, mut records: Vec<T>) -> Self {
+        let mut page = Page::<T>::new_total(page_no, page_size, 0);
+        page.search_count = false;
+        page.has_next = records.len() as u64 > page.page_size;
+        records.truncate(page.page_size as usize);
+        page.records = records;
+        page
+    }
+
     /// create Vec<Page> from data
     pub fn make_pages(mut data: Vec<T>, page_size: u64) -> Vec<Page<T>> {
         let total = data.len();
@@ -228,6 +264,15 @@ impl<T> Page<T> {
     }
 }
 
+/// run the count and select futures concurrently
+pub async fn join_count<C, S>(count: C, select: S) -> (C::Output, S::Output)
+where
+    C: Future,
+    S: Future,
+{
+    futures::future::join(count, select).await
+}
+
 impl<T> Default for Page<T> {
     fn default() -> Self {
         return Page {
@@ -237,6 +282,7 @@ impl<T> Default for Page<T> {
             page_size: DEFAULT_PAGE_SIZE,
             page_no: 1,
             search_count: true,
+            has_next: false,
         };
     }
 }
@@ -302,6 +348,7 @@ impl<T: Display + Debug> Display for Page<T> {
             .field("page_no", &self.page_no)
             .field("page_size", &self.page_size)
             .field("search_count", &self.search_count)
+            .field("has_next", &self.has_next)
             .finish()
     }
 }
@@ -317,6 +364,7 @@ impl<V> Page<V> {
         p.page_size = arg.page_size;
         p.total = arg.total;
         p.search_count = arg.search_count;
+        p.has_next = arg.has_next;
         p.records = {
             let mut records = Vec::with_capacity(arg.records.len());
             for x in arg.records {
@@ -0,0 +1,64 @@
+/// the pagination sql of database, selected by the driver type
+pub trait PageDialect: Send + Sync {
+    /// the page sql append to the select, for example ` limit 10 offset 0`
+    fn page_sql(&self, offset: u64, page_size: u64) -> String;
+
+    /// the page sql need a `order by`(mssql)
+    fn need_order_by(&self) -> bool {
+        false
+    }
+}
+
+/// ` limit {page_size} offset {offset}`, postgres/sqlite
+#[derive(Debug, Default)]
+pub struct LimitOffsetDialect {}
+
+impl PageDialect for LimitOffsetDialect {
+    fn page_sql(&self, offset: u64, page_size: u64) -> String {
+        format!(" limit {} offset {}", page_size, offset)
+    }
+}
+
+/// ` limit {offset},{page_size}`, mysql
+#[derive(Debug, Default)]
+pub struct MysqlDialect {}
+
+impl PageDialect for MysqlDialect {
+    fn page_sql(&self, offset: u64, page_size: u64) -> String {
+        format!(" limit {},{}", offset, page_size)
+    }
+}
+
+/// ` offset {offset} rows fetch next {page_size} rows only`, mssql(2012+)
+#[derive(Debug, Default)]
+pub struct MssqlDialect {}
+
+impl PageDialect for MssqlDialect {
+    fn page_sql(&self, offset: u64, page_size: u64) -> String {
+        format!(" offset {} rows fetch next {} rows only", offset, page_size)
+    }
+
+    fn need_order_by(&self) -> bool {
+        true
+    }
+}
+
+/// the dialect of driver type, the unknown driver use `MysqlDialect`
+pub fn page_dialect(driver_type: &str) -> Box<dyn PageDialect> {
+    match driver_type {
+        "postgres" | "sqlite" => Box::new(LimitOffsetDialect {}),
+        "mssql" => Box::new(MssqlDialect {}),
+        _ => Box::new(MysqlDialect {}),
+    }
+}
+
+/// the select with group by/distinct/union can not count by `select count(1) from table ...`,
+/// it should count by the sub query `select count(1) from (select ...) as t`
+pub fn is_complex_select(table_column: &str, sql: &str) -> bool {
+    let table_column = table_column.to_ascii_lowercase();
+    let sql = sql.to_ascii_lowercase();
+    table_column.trim_start().starts_with("distinct ")
+        || sql.contains("group by")
+        || sql.contains("distinct ")
+        || sql.contains(" union ")
+}
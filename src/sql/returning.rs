@@ -0,0 +1,73 @@
+use crate::Error;
+use rbs::Value;
+
+/// how to get the generated keys of the insert
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Returning {
+    /// the insert sql is `insert into t (a,b) ${output}VALUES (?,?)${returning}`,
+    /// and query the rows of generated keys(postgres/sqlite `returning`, mssql `output inserted`)
+    Query { output: String, returning: String },
+    /// exec the insert, the keys of the rows without key are `last_insert_id..last_insert_id + rows`(mysql).
+    /// the ids of one insert statement are consecutive with `innodb_autoinc_lock_mode` 0 or 1
+    LastInsertId,
+}
+
+impl Returning {
+    /// the key should be quoted by the driver type, for example `"id"`
+    pub fn new(driver_type: &str, key: &str) -> Result<Self, Error> {
+        match driver_type {
+            "postgres" | "sqlite" => Ok(Returning::Query {
+                output: String::new(),
+                returning: format!(" returning {}", key),
+            }),
+            "mssql" => Ok(Returning::Query {
+                output: format!("output inserted.{} ", key),
+                returning: String::new(),
+            }),
+            "mysql" => Ok(Returning::LastInsertId),
+            _ => Err(Error::from(format!(
+                "[rbatis] insert returning not support driver type: {}",
+                driver_type
+            ))),
+        }
+    }
+}
+
+/// the consecutive ids from the first id(`last_insert_id`), Err if the first id is null or 0
+pub fn consecutive_ids(first: &Value, count: u64) -> Result<Vec<Value>, Error> {
+    let ids = match first {
+        Value::U64(0) | Value::U32(0) | Value::I32(0) | Value::I64(0) => {
+            return Err(Error::from(
+                "[rbatis] the last_insert_id is 0, the key is not the auto increment column",
+            ))
+        }
+        Value::U64(v) => (0..count).map(|i| Value::U64(v + i)).collect(),
+        Value::U32(_) | Value::I32(_) | Value::I64(_) => {
+            let v = first.as_i64().unwrap_or_default();
+            (0..count).map(|i| Value::I64(v + i as i64)).collect()
+        }
+        _ => {
+            return Err(Error::from(format!(
+                "[rbatis] the last_insert_id {} is not the auto increment id",
+                first
+            )))
+        }
+    };
+    Ok(ids)
+}
+
+/// the keys of rows after insert, the null keys are filled by the consecutive ids from `last_insert_id`
+pub fn generated_ids(keys: &[Value], last_insert_id: &Value) -> Result<Vec<Value>, Error> {
+    let count = keys.iter().filter(|v| v.is_null()).count() as u64;
+    if count == 0 {
+        return Ok(keys.to_vec());
+    }
+    let mut ids = consecutive_ids(last_insert_id, count)?.into_iter();
+    Ok(keys
+        .iter()
+        .map(|key| match key {
+            Value::Null => ids.next().unwrap_or_default(),
+            _ => key.clone(),
+        })
+        .collect())
+}
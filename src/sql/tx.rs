@@ -1,6 +1,7 @@
-use crate::Error;
+use crate::{Error, ErrorKind};
 use futures_core::future::BoxFuture;
 use rbdc::db::Connection;
+use std::time::Duration;
 
 pub trait Tx {
     fn begin(self) -> BoxFuture<'static, Result<Self, Error>>
@@ -32,3 +33,242 @@ impl Tx for Box<dyn Connection> {
         })
     }
 }
+
+/// savepoint sql of nested transactions, `depth` is the nesting level of the savepoint (start from 1)
+pub struct SavePoint<'a> {
+    pub driver_type: &'a str,
+    pub depth: u32,
+}
+
+impl<'a> SavePoint<'a> {
+    pub fn new(driver_type: &'a str, depth: u32) -> Self {
+        Self { driver_type, depth }
+    }
+
+    /// savepoint name,for example: sp_1
+    pub fn name(&self) -> String {
+        format!("sp_{}", self.depth)
+    }
+
+    /// create savepoint sql
+    pub fn begin_sql(&self) -> String {
+        match self.driver_type {
+            "mssql" => format!("save transaction {}", self.name()),
+            _ => format!("savepoint {}", self.name()),
+        }
+    }
+
+    /// release savepoint sql. MSSQL have no release,the savepoint is committed with the outer transaction
+    pub fn commit_sql(&self) -> Option<String> {
+        match self.driver_type {
+            "mssql" => None,
+            _ => Some(format!("release savepoint {}", self.name())),
+        }
+    }
+
+    /// rollback to savepoint sql
+    pub fn rollback_sql(&self) -> String {
+        match self.driver_type {
+            "mssql" => format!("rollback transaction {}", self.name()),
+            _ => format!("rollback to savepoint {}", self.name()),
+        }
+    }
+}
+
+/// retry policy of `Rbatis::transaction_with_retry`
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// max retry times, 0 means never retry
+    pub max_retries: u32,
+    /// wait time before retry, it is multiplied by the current retry times
+    pub backoff: Duration,
+    /// decide the error can be retried,default is serialization failure or deadlock
+    pub retry_on: fn(&Error) -> bool,
+}
+
+impl RetryPolicy {
+    /// never retry
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    pub fn new(max_retries: u32, backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            backoff,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            backoff: Duration::from_millis(10),
+            retry_on: is_retryable_error,
+        }
+    }
+}
+
+/// is serialization failure or deadlock error
+pub fn is_retryable_error(e: &Error) -> bool {
+    matches!(
+        e.kind(),
+        ErrorKind::Deadlock | ErrorKind::SerializationFailure
+    )
+}
+
+/// transaction isolation level
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "read uncommitted",
+            IsolationLevel::ReadCommitted => "read committed",
+            IsolationLevel::RepeatableRead => "repeatable read",
+            IsolationLevel::Serializable => "serializable",
+        }
+    }
+}
+
+/// transaction options,used by `Rbatis::acquire_begin_with`
+///
+/// sqlite transaction is always serializable, the level choose the lock of begin:
+/// `ReadUncommitted`/`ReadCommitted` begin a `deferred`, `RepeatableRead` an `immediate` and `Serializable` an `exclusive` transaction.
+/// sqlite read only is `pragma query_only`, and mssql `set transaction isolation level` is scoped to the session,
+/// they are restored by `end_sql` after commit or rollback(so the pooled connection not keep them).
+/// the option which is not supported by the driver return an error
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TxOptions {
+    pub isolation: Option<IsolationLevel>,
+    pub read_only: bool,
+    /// only postgres support, it works with `serializable` and `read_only`
+    pub deferrable: bool,
+}
+
+impl TxOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn isolation(mut self, isolation: IsolationLevel) -> Self {
+        self.isolation = Some(isolation);
+        self
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn deferrable(mut self, deferrable: bool) -> Self {
+        self.deferrable = deferrable;
+        self
+    }
+
+    /// the sql list to begin a transaction on the driver, Err if the option is not supported by the driver
+    pub fn begin_sql(&self, driver_type: &str) -> Result<Vec<String>, Error> {
+        if self.deferrable && driver_type != "postgres" {
+            return Err(self.unsupported(driver_type, "deferrable"));
+        }
+        match driver_type {
+            "postgres" => {
+                let mut modes = vec![];
+                if let Some(isolation) = self.isolation {
+                    modes.push(format!("isolation level {}", isolation.as_str()));
+                }
+                if self.read_only {
+                    modes.push("read only".to_string());
+                }
+                if self.deferrable {
+                    modes.push("deferrable".to_string());
+                }
+                if modes.is_empty() {
+                    Ok(vec!["begin".to_string()])
+                } else {
+                    Ok(vec![format!("begin {}", modes.join(", "))])
+                }
+            }
+            "mysql" => {
+                let mut modes = vec![];
+                if let Some(isolation) = self.isolation {
+                    modes.push(format!("isolation level {}", isolation.as_str()));
+                }
+                if self.read_only {
+                    modes.push("read only".to_string());
+                }
+                let mut sqls = vec![];
+                if !modes.is_empty() {
+                    sqls.push(format!("set transaction {}", modes.join(", ")));
+                }
+                sqls.push("begin".to_string());
+                Ok(sqls)
+            }
+            "sqlite" => {
+                let mut sqls = vec![];
+                if self.read_only {
+                    sqls.push("pragma query_only = 1".to_string());
+                }
+                sqls.push(
+                    match self.isolation {
+                        None if !self.read_only => "begin",
+                        None
+                        | Some(IsolationLevel::ReadUncommitted)
+                        | Some(IsolationLevel::ReadCommitted) => "begin deferred",
+                        Some(IsolationLevel::RepeatableRead) => "begin immediate",
+                        Some(IsolationLevel::Serializable) => "begin exclusive",
+                    }
+                    .to_string(),
+                );
+                Ok(sqls)
+            }
+            "mssql" => {
+                if self.read_only {
+                    return Err(self.unsupported(driver_type, "read only"));
+                }
+                match self.isolation {
+                    Some(isolation) => Ok(vec![
+                        format!("set transaction isolation level {}", isolation.as_str()),
+                        "begin tran".to_string(),
+                    ]),
+                    None => Ok(vec!["begin".to_string()]),
+                }
+            }
+            _ => match self.isolation {
+                Some(isolation) => Err(self.unsupported(driver_type, isolation.as_str())),
+                None if self.read_only => Err(self.unsupported(driver_type, "read only")),
+                None => Ok(vec!["begin".to_string()]),
+            },
+        }
+    }
+
+    /// the sql list to restore the session after the transaction end(commit or rollback)
+    pub fn end_sql(&self, driver_type: &str) -> Vec<String> {
+        match driver_type {
+            "sqlite" if self.read_only => vec!["pragma query_only = 0".to_string()],
+            "mssql" if self.isolation.is_some() => vec![format!(
+                "set transaction isolation level {}",
+                IsolationLevel::ReadCommitted.as_str()
+            )],
+            _ => vec![],
+        }
+    }
+
+    fn unsupported(&self, driver_type: &str, option: &str) -> Error {
+        Error::from(format!(
+            "[rbatis] the transaction option `{}` is not supported by driver type: {}",
+            option, driver_type
+        ))
+    }
+}
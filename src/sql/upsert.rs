@@ -0,0 +1,143 @@
+use crate::table::insert_rows;
+use crate::Error;
+use rbatis_codegen::identifier::quote_identifier;
+use rbs::Value;
+
+/// build the insert-or-update sql of the driver type, return (sql, args).
+/// * `tables` is the rows(map), the rows is built by `table::insert_rows`(skip `id` if it's null in all rows)
+/// * postgres/sqlite: `insert into ... on conflict (c) do update set a = excluded.a`
+/// * mysql: `insert into ... on duplicate key update a = values(a)`, mysql use all unique keys of the table, the `conflict_columns` only excluded from update
+/// * mssql: `merge into ... using (values (?,?)) as s (c,a) on t.c = s.c when matched then update ... when not matched then insert ...;`
+pub fn upsert_sql(
+    driver_type: &str,
+    table_name: &str,
+    tables: &[Value],
+    conflict_columns: &[&str],
+) -> Result<(String, Vec<Value>), Error> {
+    let rows = insert_rows(None, tables.to_vec())?;
+    quote_upsert_sql(driver_type, "", table_name, &rows, conflict_columns)
+}
+
+/// the `upsert_sql` with the table and column names quoted by the `quote_driver`,
+/// the `tables` is the rows of `table::insert_rows`, the columns is the keys of the first row
+pub fn quote_upsert_sql(
+    driver_type: &str,
+    quote_driver: &str,
+    table_name: &str,
+    tables: &[Value],
+    conflict_columns: &[&str],
+) -> Result<(String, Vec<Value>), Error> {
+    if tables.is_empty() {
+        return Err(Error::from(
+            "[rbatis] upsert can not upsert empty array tables!",
+        ));
+    }
+    if conflict_columns.is_empty() {
+        return Err(Error::from(
+            "[rbatis] upsert conflict_columns can not be empty!",
+        ));
+    }
+    let mut columns = vec![];
+    if let Value::Map(m) = &tables[0] {
+        for (k, _) in m {
+            columns.push(k.as_str().unwrap_or_default().to_string());
+        }
+    }
+    for c in conflict_columns {
+        if !columns.iter().any(|v| v == c) {
+            return Err(Error::from(format!(
+                "[rbatis] upsert conflict column '{}' not found in table '{}'",
+                c, table_name
+            )));
+        }
+    }
+    let mut args = Vec::with_capacity(tables.len() * columns.len());
+    for table in tables {
+        for c in &columns {
+            args.push(table[c.as_str()].clone());
+        }
+    }
+    let row = format!("({})", vec!["?"; columns.len()].join(","));
+    let values = vec![row.as_str(); tables.len()].join(",");
+    let quote = |name: &str| quote_identifier(quote_driver, name);
+    let update_columns: Vec<String> = columns
+        .iter()
+        .filter(|c| !conflict_columns.contains(&c.as_str()))
+        .map(|c| quote(c))
+        .collect();
+    let table_name = quote(table_name);
+    let columns: Vec<String> = columns.iter().map(|c| quote(c)).collect();
+    let conflict_columns: Vec<String> = conflict_columns.iter().map(|c| quote(c)).collect();
+    let sql = match driver_type {
+        "postgres" | "sqlite" => {
+            let update = if update_columns.is_empty() {
+                "do nothing".to_string()
+            } else {
+                let sets: Vec<String> = update_columns
+                    .iter()
+                    .map(|c| format!("{} = excluded.{}", c, c))
+                    .collect();
+                format!("do update set {}", sets.join(","))
+            };
+            format!(
+                "insert into {} ({}) VALUES {} on conflict ({}) {}",
+                table_name,
+                columns.join(","),
+                values,
+                conflict_columns.join(","),
+                update
+            )
+        }
+        "mysql" => {
+            let sets: Vec<String> = if update_columns.is_empty() {
+                // update nothing
+                vec![format!("{} = {}", conflict_columns[0], conflict_columns[0])]
+            } else {
+                update_columns
+                    .iter()
+                    .map(|c| format!("{} = values({})", c, c))
+                    .collect()
+            };
+            format!(
+                "insert into {} ({}) VALUES {} on duplicate key update {}",
+                table_name,
+                columns.join(","),
+                values,
+                sets.join(",")
+            )
+        }
+        "mssql" => {
+            let on: Vec<String> = conflict_columns
+                .iter()
+                .map(|c| format!("t.{} = s.{}", c, c))
+                .collect();
+            let matched = if update_columns.is_empty() {
+                String::new()
+            } else {
+                let sets: Vec<String> = update_columns
+                    .iter()
+                    .map(|c| format!("t.{} = s.{}", c, c))
+                    .collect();
+                format!(" when matched then update set {}", sets.join(","))
+            };
+            let source: Vec<String> = columns.iter().map(|c| format!("s.{}", c)).collect();
+            format!(
+                "merge into {} as t using (VALUES {}) as s ({}) on {}{} when not matched then insert ({}) VALUES ({});",
+                table_name,
+                values,
+                columns.join(","),
+                on.join(" and "),
+                matched,
+                columns.join(","),
+                source.join(",")
+            )
+        }
+        _ => {
+            return Err(Error::from(format!(
+                "[rbatis] upsert not support driver type: {}",
+                driver_type
+            )));
+        }
+    };
+    Ok((sql, args))
+}
@@ -0,0 +1,355 @@
+use crate::Error;
+use rbatis_codegen::identifier::quote_identifier;
+use rbs::value::map::ValueMap;
+use rbs::Value;
+use serde::de::{DeserializeOwned, Deserializer, Visitor};
+use std::marker::PhantomData;
+
+/// the column metadata of `#[derive(Table)]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnMeta {
+    /// the column name in database
+    pub name: &'static str,
+    /// the (serde) field name of struct
+    pub field: &'static str,
+    /// the column is not in database
+    pub skip: bool,
+    /// is primary key
+    pub pk: bool,
+    /// the value is generated by database, it's not inserted if it's null
+    pub auto_increment: bool,
+    /// use the database default value, it's not inserted if it's null
+    pub insert_default: bool,
+}
+
+/// the table metadata of `#[derive(Table)]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableMeta {
+    pub name: &'static str,
+    pub columns: &'static [ColumnMeta],
+    /// quote the table and column names by the driver type(see `rbatis_codegen::identifier`), disabled by `#[table(no_quote)]`
+    pub quote: bool,
+}
+
+impl TableMeta {
+    /// the primary key columns
+    pub fn primary_keys(&self) -> Vec<&'static str> {
+        self.columns
+            .iter()
+            .filter(|c| c.pk && !c.skip)
+            .map(|c| c.name)
+            .collect()
+    }
+
+    /// the column of the field
+    pub fn column(&self, field: &str) -> Option<&ColumnMeta> {
+        self.columns.iter().find(|c| c.field == field)
+    }
+
+    /// the select columns, for example `id,create_time,type_name as name`
+    pub fn select_columns(&self) -> String {
+        self.columns
+            .iter()
+            .filter(|c| !c.skip)
+            .map(|c| {
+                if c.name == c.field {
+                    c.name.to_string()
+                } else {
+                    format!("{} as {}", c.name, c.field)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// rename the fields of the table(map) to columns, and remove the skip fields
+    pub fn to_columns(&self, table: Value) -> Value {
+        match table {
+            Value::Map(m) => {
+                let mut columns = ValueMap::with_capacity(m.len());
+                for (k, v) in m {
+                    match self.column(k.as_str().unwrap_or_default()) {
+                        Some(c) if c.skip => {}
+                        Some(c) => columns.insert(c.name.into(), v),
+                        None => columns.insert(k, v),
+                    }
+                }
+                Value::Map(columns)
+            }
+            v => v,
+        }
+    }
+}
+
+/// the table with metadata, impl by `#[derive(Table)]`
+///
+/// for example:
+/// ```rust
+/// use rbatis::Table;
+/// #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Table)]
+/// #[table(name = "biz_activity")]
+/// pub struct BizActivity {
+///     #[column(pk, auto_increment)]
+///     pub id: Option<i64>,
+///     #[column(name = "activity_name")]
+///     pub name: Option<String>,
+///     #[column(insert_default)]
+///     pub create_time: Option<String>,
+///     #[column(skip)]
+///     #[serde(default)]
+///     pub remark: Option<String>,
+/// }
+/// assert_eq!(<BizActivity as rbatis::table::Table>::table_meta().name, "biz_activity");
+/// ```
+pub trait Table {
+    fn table_meta() -> &'static TableMeta;
+}
+
+/// the table name of `TableMeta`, or the snake name of the type name
+pub fn table_name(meta: Option<&TableMeta>, type_name: &str) -> String {
+    match meta {
+        Some(meta) => meta.name.to_string(),
+        None => crate::utils::string_util::to_snake_name(type_name),
+    }
+}
+
+/// the driver type to quote the table and column names, it's empty if the table is `#[table(no_quote)]`
+pub fn quote_driver<'a>(driver_type: &'a str, meta: Option<&TableMeta>) -> &'a str {
+    match meta {
+        Some(meta) if !meta.quote => "",
+        _ => driver_type,
+    }
+}
+
+/// the select columns of `TableMeta`, or `*`
+pub fn select_columns(meta: Option<&TableMeta>) -> String {
+    match meta {
+        Some(meta) => meta.select_columns(),
+        None => "*".to_string(),
+    }
+}
+
+/// the select columns of the DTO type, it's the `select_columns` of `#[derive(Table)]`,
+/// or the serde field names of the struct, or `*` if it's not a struct
+pub fn dto_columns<T: DeserializeOwned>(meta: Option<&TableMeta>) -> String {
+    if let Some(meta) = meta {
+        return meta.select_columns();
+    }
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldsDeserializer(&mut fields));
+    if fields.is_empty() {
+        "*".to_string()
+    } else {
+        fields.join(",")
+    }
+}
+
+/// the deserializer only take the field names of struct
+struct FieldsDeserializer<'a>(&'a mut &'static [&'static str]);
+
+impl<'de, 'a> Deserializer<'de> for FieldsDeserializer<'a> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(serde::de::Error::custom("not struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(serde::de::Error::custom("only take the fields"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// rename the fields of the table(map) to columns by `TableMeta`
+pub fn to_columns(meta: Option<&TableMeta>, table: Value) -> Value {
+    match meta {
+        Some(meta) => meta.to_columns(table),
+        None => table,
+    }
+}
+
+/// the primary keys of `TableMeta`, or `id`
+pub fn primary_keys(meta: Option<&TableMeta>) -> Vec<&'static str> {
+    match meta {
+        Some(meta) if !meta.primary_keys().is_empty() => meta.primary_keys(),
+        _ => vec!["id"],
+    }
+}
+
+/// the (column, field) of the generated key, it's the `auto_increment` column(or primary key) of `TableMeta`, or `id`
+pub fn generated_key(meta: Option<&TableMeta>) -> (&'static str, &'static str) {
+    let columns = meta.map(|m| m.columns).unwrap_or_default();
+    columns
+        .iter()
+        .find(|c| c.auto_increment && !c.skip)
+        .or_else(|| columns.iter().find(|c| c.pk && !c.skip))
+        .map(|c| (c.name, c.field))
+        .unwrap_or(("id", "id"))
+}
+
+/// the where of primary keys, for example keys `(tenant_id,order_no)` with 2 ids:
+/// `(tenant_id = ? and order_no = ?) or (tenant_id = ? and order_no = ?)`,
+/// the single key with many ids is `id in (?,?)`.
+/// * the id of single key is the value, or `[value]`/`{key: value}`
+/// * the id of composite key is the array(tuple) of key values in order, or the map of `{column: value}`
+///
+/// return the array of `{sql: arg}`, the where is the sql and arg of every item and end with `)`
+pub fn pk_condition(keys: &[&str], ids: &[Value]) -> Result<Value, Error> {
+    quote_pk_condition("", keys, ids)
+}
+
+/// the `pk_condition` with the keys quoted by the driver type
+pub fn quote_pk_condition(driver_type: &str, keys: &[&str], ids: &[Value]) -> Result<Value, Error> {
+    if keys.is_empty() || ids.is_empty() {
+        return Err(Error::from(
+            "[rbatis] the primary keys and ids can't be empty",
+        ));
+    }
+    let mut items = vec![];
+    for (i, id) in ids.iter().enumerate() {
+        let values = match id {
+            Value::Map(_) => keys.iter().map(|k| id[*k].clone()).collect(),
+            Value::Array(arr) => arr.clone(),
+            v => vec![v.clone()],
+        };
+        if values.len() != keys.len() {
+            return Err(Error::from(format!(
+                "[rbatis] the id {} is not match the primary keys {:?}",
+                id, keys
+            )));
+        }
+        for (j, (key, value)) in keys.iter().zip(values).enumerate() {
+            if value.is_null() {
+                return Err(Error::from(format!(
+                    "[rbatis] the primary key `{}` of id {} is null",
+                    key, id
+                )));
+            }
+            let key = quote_identifier(driver_type, key);
+            let sql = if keys.len() == 1 && ids.len() > 1 {
+                if i == 0 {
+                    format!("{} in (", key)
+                } else {
+                    ",".to_string()
+                }
+            } else {
+                let prefix = match (i, j) {
+                    (0, 0) => "(",
+                    (_, 0) => ") or (",
+                    _ => " and ",
+                };
+                format!("{}{} = ", prefix, key)
+            };
+            let mut item = ValueMap::new();
+            item.insert(Value::String(sql), value);
+            items.push(Value::Map(item));
+        }
+    }
+    Ok(Value::Array(items))
+}
+
+/// the rows of insert, every row has the same columns.
+/// * the columns is the keys of first row(renamed by `TableMeta`)
+/// * the `auto_increment`/`insert_default` column(`id` if there is no `TableMeta`) is skipped if it's null in all rows,
+///   it's an error if the column is null in some rows only(insert them in different batch)
+pub fn insert_rows(meta: Option<&TableMeta>, rows: Vec<Value>) -> Result<Vec<Value>, Error> {
+    let rows: Vec<Value> = match meta {
+        Some(meta) => rows.into_iter().map(|v| meta.to_columns(v)).collect(),
+        None => rows,
+    };
+    let is_default = |column: &str| match meta {
+        Some(meta) => meta
+            .columns
+            .iter()
+            .any(|c| c.name == column && !c.skip && (c.auto_increment || c.insert_default)),
+        None => column == "id",
+    };
+    let mut columns = vec![];
+    if let Some(Value::Map(m)) = rows.first() {
+        for (k, _) in m {
+            let k = k.as_str().unwrap_or_default();
+            if is_default(k) {
+                let nulls = rows.iter().filter(|row| row[k].is_null()).count();
+                if nulls == rows.len() {
+                    continue;
+                }
+                if nulls != 0 {
+                    return Err(Error::from(format!(
+                        "[rbatis] the column `{}` is null in some rows of the batch, insert the rows with and without `{}` in different batch",
+                        k, k
+                    )));
+                }
+            }
+            columns.push(k.to_string());
+        }
+    }
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let mut m = ValueMap::with_capacity(columns.len());
+            for c in &columns {
+                m.insert(c.as_str().into(), row[c.as_str()].clone());
+            }
+            Value::Map(m)
+        })
+        .collect())
+}
+
+/// the `TableMeta` of type if it impl `Table`, use by the crud macros(`table_meta!`)
+#[doc(hidden)]
+pub struct MetaProbe<T>(PhantomData<T>);
+
+impl<T> MetaProbe<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for MetaProbe<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[doc(hidden)]
+pub trait MetaOfTable {
+    fn table_meta(&self) -> Option<&'static TableMeta>;
+}
+
+impl<T: Table> MetaOfTable for &MetaProbe<T> {
+    fn table_meta(&self) -> Option<&'static TableMeta> {
+        Some(T::table_meta())
+    }
+}
+
+#[doc(hidden)]
+pub trait MetaOfAny {
+    fn table_meta(&self) -> Option<&'static TableMeta>;
+}
+
+impl<T> MetaOfAny for MetaProbe<T> {
+    fn table_meta(&self) -> Option<&'static TableMeta> {
+        None
+    }
+}
+
+/// `Option<&'static TableMeta>` of the table type, None if it is not `#[derive(Table)]`
+#[macro_export]
+macro_rules! table_meta {
+    ($table:ty) => {{
+        #[allow(unused_imports)]
+        use $crate::table::{MetaOfAny, MetaOfTable};
+        (&&$crate::table::MetaProbe::<$table>::new()).table_meta()
+    }};
+}
@@ -15,11 +15,26 @@ mod test {
     #![allow(private_in_public)]
 
     use crossbeam::queue::SegQueue;
+    use futures::TryStreamExt;
     use futures_core::future::BoxFuture;
-    use rbatis::executor::RBatisConnExecutor;
-    use rbatis::intercept::SqlIntercept;
-    use rbatis::sql::PageRequest;
-    use rbatis::{Error, Rbatis};
+    use rbatis::executor::{Executor, RBatisConnExecutor, RbatisRef};
+    use rbatis::intercept::{
+        BlockAttackDeleteInterceptor, BlockAttackUpdateInterceptor, Intercept, ResultType,
+        SqlIntercept,
+    };
+    use log::{Level, LevelFilter};
+    use rbatis::log::LogPlugin;
+    use rbatis::logic_delete::LogicDeleteIntercept;
+    use rbatis::replica::LeastConnectionsRoute;
+    use rbatis::sharding::{ModShard, ShardingIntercept};
+    use rbatis::slow_query::SlowQueryIntercept;
+    use rbatis::sql::tx::{IsolationLevel, RetryPolicy, SavePoint, TxOptions};
+    use rbatis::sql::page_dialect::{is_complex_select, page_dialect};
+    use rbatis::sql::returning::{consecutive_ids, generated_ids, Returning};
+    use rbatis::sql::upsert::upsert_sql;
+    use rbatis::sql::{Cursor, CursorPage, CursorPageRequest, IPageRequest, Page, PageRequest};
+    use rbatis::version_lock::VersionLockIntercept;
+    use rbatis::{Error, ErrorKind, Rbatis};
     use rbdc::datetime::DateTime;
     use rbdc::db::{ConnectOptions, Connection, Driver, ExecResult, MetaData, Row};
     use rbdc::rt::block_on;
@@ -27,6 +42,7 @@ mod test {
     use std::any::Any;
     use std::collections::HashMap;
     use std::sync::Arc;
+    use std::time::Duration;
 
     pub struct MockIntercept {
         pub sql_args: Arc<SegQueue<(String, Vec<Value>)>>,
@@ -247,6 +263,72 @@ mod test {
         block_on(f);
     }
 
+    #[test]
+    fn test_query_stream() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let r: Vec<MockTable> = rb
+                .query_stream_decode("select * from mock_table", vec![])
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(r.len(), 1);
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from mock_table");
+            let mut conn = rb.acquire().await.unwrap();
+            let mut stream =
+                conn.query_stream_decode::<MockTable>("select * from mock_table", vec![]);
+            let mut len = 0;
+            while stream.try_next().await.unwrap().is_some() {
+                len += 1;
+            }
+            assert_eq!(len, 1);
+        };
+        block_on(f);
+    }
+
+    /// the executor without `query_stream`
+    struct MockQueryExecutor {
+        rb: Rbatis,
+    }
+
+    impl RbatisRef for MockQueryExecutor {
+        fn rbatis_ref(&self) -> &Rbatis {
+            &self.rb
+        }
+    }
+
+    impl Executor for MockQueryExecutor {
+        fn exec(&mut self, sql: &str, args: Vec<Value>) -> BoxFuture<'_, Result<ExecResult, Error>> {
+            let sql = sql.to_string();
+            Box::pin(async move { self.rb.exec(&sql, args).await })
+        }
+
+        fn query(&mut self, sql: &str, args: Vec<Value>) -> BoxFuture<'_, Result<Value, Error>> {
+            let sql = sql.to_string();
+            Box::pin(async move { self.rb.query(&sql, args).await })
+        }
+    }
+
+    #[test]
+    fn test_query_stream_default() {
+        let f = async move {
+            let rb = Rbatis::new();
+            rb.init(MockDriver {}, "test").unwrap();
+            let mut executor = MockQueryExecutor { rb };
+            let r: Vec<Value> = executor
+                .query_stream("select * from mock_table", vec![])
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(r.len(), 1);
+        };
+        block_on(f);
+    }
+
     crud!(MockTable {});
     #[test]
     fn test_insert() {
@@ -662,6 +744,246 @@ mod test {
         };
         block_on(f);
     }
+    #[test]
+    fn test_select_page_no_count() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let req = PageRequest::new(1, 10).set_search_count(false);
+            let r = MockTable::select_page(&mut rb, &req).await.unwrap();
+            assert_eq!(queue.len(), 1);
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from mock_table order by create_time desc limit 0,11"
+            );
+            assert_eq!(r.records.len(), 1);
+            assert_eq!(r.total, 0);
+            assert!(!r.search_count);
+            assert!(!r.has_next);
+
+            let page = Page::<i32>::new_no_count(2, 2, vec![1, 2, 3]);
+            assert_eq!(page.records, vec![1, 2]);
+            assert!(page.has_next);
+            assert_eq!(page.page_no, 2);
+        };
+        block_on(f);
+    }
+
+    htmlsql_select_page!(select_page_html(name: &str) -> MockTable => r#"<select id="select_page_html">`select `<if test="do_count == true">`count(1)`</if><if test="do_count == false">`*`</if>` from mock_table where name = #{name}`<if test="do_count == false">` limit ${page_no},${page_size}`</if></select>"#);
+    #[test]
+    fn test_htmlsql_select_page() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            // the page_size 0 is the default size
+            let page = select_page_html(&mut rb, &PageRequest::new(3, 0), "a")
+                .await
+                .unwrap();
+            assert_eq!(page.page_no, 3);
+            assert_eq!(page.page_size, 10);
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "select count(1) from mock_table where name = ?");
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from mock_table where name = ? limit 20,10");
+
+            let req = PageRequest::new(3, 0).set_search_count(false);
+            let page = select_page_html(&mut rb, &req, "a").await.unwrap();
+            assert_eq!(page.page_no, 3);
+            assert_eq!(page.page_size, 10);
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from mock_table where name = ? limit 20,11");
+            assert!(queue.pop().is_none());
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_select_page_concurrent() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let req = PageRequest::new(1, 10).set_concurrent(true);
+            let r = MockTable::select_page(&mut rb, &req).await.unwrap();
+            let mut sqls = vec![queue.pop().unwrap().0, queue.pop().unwrap().0];
+            sqls.sort();
+            assert_eq!(
+                sqls,
+                vec![
+                    "select * from mock_table order by create_time desc limit 0,10",
+                    "select count(1) as count from mock_table order by create_time desc"
+                ]
+            );
+            assert_eq!(r.total, 1);
+            assert_eq!(r.records.len(), 1);
+
+            let mut conn = rb.acquire().await.unwrap();
+            MockTable::select_page(&mut conn, &req).await.unwrap();
+            assert_eq!(queue.len(), 2);
+        };
+        block_on(f);
+    }
+
+    impl_select_page!(MockTable{select_page_group_by() => "`group by name`"});
+    #[test]
+    fn test_select_page_group_by() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let r = MockTable::select_page_group_by(&mut rb, &PageRequest::new(2, 10))
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select count(1) as count from (select * from mock_table group by name) as rb_count_t"
+            );
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from mock_table group by name limit 10,10");
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_page_dialect() {
+        assert_eq!(page_dialect("postgres").page_sql(20, 10), " limit 10 offset 20");
+        assert_eq!(page_dialect("sqlite").page_sql(0, 10), " limit 10 offset 0");
+        assert_eq!(page_dialect("mysql").page_sql(20, 10), " limit 20,10");
+        assert_eq!(
+            page_dialect("mssql").page_sql(20, 10),
+            " offset 20 rows fetch next 10 rows only"
+        );
+        assert!(page_dialect("mssql").need_order_by());
+        assert!(!page_dialect("postgres").need_order_by());
+        assert!(is_complex_select("*", "where a = 1 group by name"));
+        assert!(is_complex_select("distinct name", ""));
+        assert!(!is_complex_select("*", "where a = 1 order by id"));
+    }
+    impl_select_cursor_page!(MockTable{select_cursor_page(name:&str) => "
+     if name != '':
+       `where name = #{name}`"}, ["create_time", "id"], desc);
+    #[test]
+    fn test_select_cursor_page() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let r = MockTable::select_cursor_page(&mut rb, &CursorPageRequest::new(10), "")
+                .await
+                .unwrap();
+            assert_eq!(r.records.len(), 1);
+            assert_eq!(r.next_cursor, None);
+            assert_eq!(r.prev_cursor, None);
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from mock_table  order by create_time desc,id desc limit 0,11"
+            );
+            assert!(args.is_empty());
+
+            let cursor = Cursor {
+                backward: false,
+                values: vec![to_value!("2023-01-01 00:00:00"), to_value!("9")],
+            };
+            let req = CursorPageRequest::new(10).cursor(Some(cursor.encode()));
+            MockTable::select_cursor_page(&mut rb, &req, "a")
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from mock_table where name = ? and ((create_time < ?) or (create_time = ? and id < ?)) order by create_time desc,id desc limit 0,11");
+            assert_eq!(
+                args,
+                vec![
+                    to_value!("a"),
+                    to_value!("2023-01-01 00:00:00"),
+                    to_value!("2023-01-01 00:00:00"),
+                    to_value!("9")
+                ]
+            );
+
+            let cursor = Cursor {
+                backward: true,
+                values: vec![to_value!("2023-01-01 00:00:00"), to_value!("9")],
+            };
+            let req = CursorPageRequest::new(10).cursor(Some(cursor.encode()));
+            MockTable::select_cursor_page(&mut rb, &req, "")
+                .await
+                .unwrap();
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from mock_table  where ((create_time > ?) or (create_time = ? and id > ?)) order by create_time asc,id asc limit 0,11");
+
+            let req = CursorPageRequest::new(10).cursor(Some("zz".to_string()));
+            assert!(MockTable::select_cursor_page(&mut rb, &req, "").await.is_err());
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_cursor_page_from_rows() {
+        let cursor = Cursor {
+            backward: false,
+            values: vec![
+                to_value!(1),
+                to_value!("a"),
+                Value::Null,
+                to_value!(1.5),
+                Value::Ext("DateTime", Box::new(to_value!("2023-01-01T00:00:00Z"))),
+            ],
+        };
+        let decoded = Cursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded.values[0], to_value!(1i64));
+        assert_eq!(&decoded.values[1..], &cursor.values[1..]);
+
+        let rows: Vec<Value> = (1..=4).rev().map(|id| to_value! {"id": id,}).collect();
+        let page = CursorPage::<Value>::from_rows(rows, None, &["id"], 3, None).unwrap();
+        assert_eq!(page.records.len(), 3);
+        assert_eq!(page.prev_cursor, None);
+        let next = Cursor::decode(page.next_cursor.as_ref().unwrap()).unwrap();
+        assert!(!next.backward);
+        assert_eq!(next.values, vec![to_value!(2i64)]);
+
+        let rows: Vec<Value> = (1..=2).map(|id| to_value! {"id": id,}).collect();
+        let page = CursorPage::<Value>::from_rows(rows, None, &["t.id"], 3, Some(&next)).unwrap();
+        assert_eq!(page.next_cursor, None);
+        let prev = Cursor::decode(page.prev_cursor.as_ref().unwrap()).unwrap();
+        assert!(prev.backward);
+        assert_eq!(prev.values, vec![to_value!(1i64)]);
+
+        // the rows of previous page is selected by reverse order
+        let rows: Vec<Value> = (2..=5).map(|id| to_value! {"id": id,}).collect();
+        let page = CursorPage::<Value>::from_rows(rows, None, &["id"], 3, Some(&prev)).unwrap();
+        assert_eq!(
+            page.records,
+            vec![
+                to_value! {"id": 4,},
+                to_value! {"id": 3,},
+                to_value! {"id": 2,}
+            ]
+        );
+        assert!(page.prev_cursor.is_some());
+        assert!(page.next_cursor.is_some());
+
+        // the column `activity_name` is selected as `name`
+        let meta = rbatis::table_meta!(MockActivity);
+        let rows: Vec<Value> = (1..=4)
+            .rev()
+            .map(|id| to_value! {"id": id, "name": format!("a{}", id),})
+            .collect();
+        let page = CursorPage::<Value>::from_rows(rows, meta, &["activity_name", "id"], 3, None)
+            .unwrap();
+        let next = Cursor::decode(page.next_cursor.as_ref().unwrap()).unwrap();
+        assert_eq!(next.values, vec![to_value!("a2"), to_value!(2i64)]);
+    }
+
     impl_select_page!(MockTable{select_page_by_name(name:&str) =>"
      if name != null && name != '':
        `where name != #{name}`
@@ -748,6 +1070,40 @@ mod test {
         block_on(f);
     }
 
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+    struct MockNameDTO {
+        pub id: Option<String>,
+        #[serde(rename = "name")]
+        pub activity_name: Option<String>,
+    }
+
+    impl_select!(MockTable{select_name_by_id(id:&str) -> Vec<MockNameDTO> => "`where id = #{id}`"});
+    impl_select!(MockTable{select_id_by_name(name:&str) -> Option => "`where name = #{name} limit 1`"}, columns = ["id", "name"]);
+    impl_select!(MockTable{select_id_from_table_name(id:&str) => "`where id = #{id}`"}, columns = ["id"], "mock_table2");
+
+    #[test]
+    fn test_select_columns() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let r: Vec<MockNameDTO> = MockTable::select_name_by_id(&mut rb, "1").await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select id,name from mock_table where id = ?");
+            assert_eq!(args, vec![to_value!("1")]);
+
+            let r = MockTable::select_id_by_name(&mut rb, "1").await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select id,name from mock_table where name = ? limit 1");
+
+            let r = MockTable::select_id_from_table_name(&mut rb, "1").await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select id from mock_table2 where id = ?");
+        };
+        block_on(f);
+    }
+
     #[test]
     fn test_select_in_column() {
         let f = async move {
@@ -817,6 +1173,244 @@ mod test {
         block_on(f);
     }
 
+    #[test]
+    fn test_tx_nested() {
+        let f = async move {
+            let rb = Rbatis::new();
+            rb.init(MockDriver {}, "test").unwrap();
+            let tx = rb.acquire_begin().await.unwrap();
+            assert_eq!(tx.depth, 0);
+            let tx = tx.begin().await.unwrap();
+            assert_eq!(tx.depth, 1);
+            let mut tx = tx.begin().await.unwrap();
+            assert_eq!(tx.depth, 2);
+            tx.rollback().await.unwrap();
+            assert_eq!(tx.depth, 1);
+            tx.commit().await.unwrap();
+            assert_eq!(tx.depth, 0);
+            assert!(!tx.done);
+            tx.commit().await.unwrap();
+            assert!(tx.done);
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_tx_save_point_sql() {
+        let sp = SavePoint::new("postgres", 1);
+        assert_eq!(sp.begin_sql(), "savepoint sp_1");
+        assert_eq!(sp.commit_sql(), Some("release savepoint sp_1".to_string()));
+        assert_eq!(sp.rollback_sql(), "rollback to savepoint sp_1");
+        let sp = SavePoint::new("mssql", 2);
+        assert_eq!(sp.begin_sql(), "save transaction sp_2");
+        assert_eq!(sp.commit_sql(), None);
+        assert_eq!(sp.rollback_sql(), "rollback transaction sp_2");
+    }
+
+    #[test]
+    fn test_tx_options_sql() {
+        let opt = TxOptions::new()
+            .isolation(IsolationLevel::Serializable)
+            .read_only(true)
+            .deferrable(true);
+        assert_eq!(
+            opt.begin_sql("postgres").unwrap(),
+            vec!["begin isolation level serializable, read only, deferrable"]
+        );
+        assert!(opt.begin_sql("mysql").is_err());
+        let opt = opt.deferrable(false);
+        assert_eq!(
+            opt.begin_sql("mysql").unwrap(),
+            vec![
+                "set transaction isolation level serializable, read only",
+                "begin"
+            ]
+        );
+        assert_eq!(
+            opt.begin_sql("sqlite").unwrap(),
+            vec!["pragma query_only = 1", "begin exclusive"]
+        );
+        assert_eq!(opt.end_sql("sqlite"), vec!["pragma query_only = 0"]);
+        assert_eq!(
+            opt.read_only(false).begin_sql("sqlite").unwrap(),
+            vec!["begin exclusive"]
+        );
+        assert!(opt.read_only(false).end_sql("sqlite").is_empty());
+        assert_eq!(
+            TxOptions::new().read_only(true).begin_sql("sqlite").unwrap(),
+            vec!["pragma query_only = 1", "begin deferred"]
+        );
+        assert_eq!(
+            TxOptions::new()
+                .isolation(IsolationLevel::RepeatableRead)
+                .begin_sql("sqlite")
+                .unwrap(),
+            vec!["begin immediate"]
+        );
+        assert_eq!(
+            TxOptions::new()
+                .isolation(IsolationLevel::ReadCommitted)
+                .begin_sql("sqlite")
+                .unwrap(),
+            vec!["begin deferred"]
+        );
+        assert_eq!(
+            opt.read_only(false).begin_sql("mssql").unwrap(),
+            vec!["set transaction isolation level serializable", "begin tran"]
+        );
+        assert_eq!(
+            opt.end_sql("mssql"),
+            vec!["set transaction isolation level read committed"]
+        );
+        assert_eq!(
+            opt.begin_sql("mssql").unwrap_err().to_string(),
+            "[rbatis] the transaction option `read only` is not supported by driver type: mssql"
+        );
+        for driver in ["postgres", "mysql", "sqlite", "mssql"] {
+            assert_eq!(TxOptions::default().begin_sql(driver).unwrap(), vec!["begin"]);
+            assert!(TxOptions::default().end_sql(driver).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_sharding_insert_split() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![
+                Box::new(ShardingIntercept::new().table("biz_order", "user_id", ModShard::new(4))),
+                Box::new(MockIntercept::new(queue.clone())),
+            ]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let mut tx = rb.acquire_begin().await.unwrap();
+            tx.exec(
+                "insert into biz_order (id,user_id) VALUES (?,?),(?,?),(?,?)",
+                vec![
+                    to_value!("1"),
+                    to_value!(5),
+                    to_value!("2"),
+                    to_value!(6),
+                    to_value!("3"),
+                    to_value!(9),
+                ],
+            )
+            .await
+            .unwrap();
+            assert_eq!(queue.pop().unwrap().0, "begin");
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "insert into biz_order_1 (id,user_id) VALUES (?,?),(?,?)");
+            assert_eq!(
+                args,
+                vec![to_value!("1"), to_value!(5), to_value!("3"), to_value!(9)]
+            );
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "insert into biz_order_2 (id,user_id) VALUES (?,?)");
+            assert_eq!(args, vec![to_value!("2"), to_value!(6)]);
+            assert!(queue.pop().is_none());
+            tx.rollback().await.unwrap();
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_acquire_begin_with() {
+        let f = async move {
+            let rb = Rbatis::new();
+            rb.init(MockDialectDriver("postgres"), "test").unwrap();
+            let mut tx = rb
+                .acquire_begin_with(TxOptions::new().isolation(IsolationLevel::Serializable))
+                .await
+                .unwrap();
+            assert!(tx.commit().await.unwrap());
+            let rb = Rbatis::new();
+            rb.init(MockDriver {}, "test").unwrap();
+            assert!(rb
+                .acquire_begin_with(TxOptions::new().isolation(IsolationLevel::Serializable))
+                .await
+                .is_err());
+
+            // the begin and end sql run with the intercepts
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDialectDriver("mssql"), "test").unwrap();
+            let mut tx = rb
+                .acquire_begin_with(TxOptions::new().isolation(IsolationLevel::Serializable))
+                .await
+                .unwrap();
+            assert_eq!(
+                queue.pop().unwrap().0,
+                "set transaction isolation level serializable"
+            );
+            assert_eq!(queue.pop().unwrap().0, "begin tran");
+            assert!(tx.rollback().await.unwrap());
+            assert_eq!(
+                queue.pop().unwrap().0,
+                "set transaction isolation level read committed"
+            );
+            assert!(queue.pop().is_none());
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_transaction() {
+        let f = async move {
+            let rb = Rbatis::new();
+            rb.init(MockDriver {}, "test").unwrap();
+            let r = rb
+                .transaction(|tx| {
+                    Box::pin(async move {
+                        let v: Vec<MockTable> =
+                            tx.query_decode("select * from mock_table", vec![]).await?;
+                        Ok(v.len())
+                    })
+                })
+                .await
+                .unwrap();
+            assert_eq!(r, 1);
+            let r = rb
+                .transaction(|_tx| Box::pin(async move { Err::<(), Error>(Error::from("fail")) }))
+                .await;
+            assert_eq!(r.unwrap_err().to_string(), "fail");
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_transaction_retry() {
+        let f = async move {
+            let rb = Rbatis::new();
+            rb.init(MockDriver {}, "test").unwrap();
+            let mut times = 0;
+            let r = rb
+                .transaction_with_retry(RetryPolicy::new(3, Duration::ZERO), |_tx| {
+                    times += 1;
+                    let t = times;
+                    Box::pin(async move {
+                        if t < 3 {
+                            Err(Error::new(ErrorKind::Deadlock, "Deadlock found"))
+                        } else {
+                            Ok(t)
+                        }
+                    })
+                })
+                .await
+                .unwrap();
+            assert_eq!(r, 3);
+            let mut times = 0;
+            let r = rb
+                .transaction_with_retry(RetryPolicy::new(3, Duration::ZERO), |_tx| {
+                    times += 1;
+                    Box::pin(async move { Err::<(), Error>(Error::from("syntax error")) })
+                })
+                .await;
+            assert!(r.is_err());
+            assert_eq!(times, 1);
+        };
+        block_on(f);
+    }
+
     #[test]
     fn test_pool_get() {
         let f = async move {
@@ -848,4 +1442,1123 @@ mod test {
         };
         block_on(f);
     }
+
+    #[test]
+    fn test_replica_route() {
+        let f = async move {
+            let rb = Rbatis::new();
+            rb.init(MockDriver {}, "test").unwrap();
+            rb.init_replica("r1", MockDriver {}, "test").unwrap();
+            rb.init_replica("r2", MockDriver {}, "test").unwrap();
+            assert!(rb.init_replica("r1", MockDriver {}, "test").is_err());
+            let in_use = |name: &str| {
+                let status = rb.get_replica(name).unwrap().status();
+                status.size as isize - status.available
+            };
+            let c1 = rb.acquire_read().await.unwrap();
+            assert_eq!((in_use("r1"), in_use("r2")), (1, 0));
+            // the rb of replica connection is on the replica
+            let c = c1.rb.acquire().await.unwrap();
+            assert_eq!((in_use("r1"), in_use("r2")), (2, 0));
+            drop(c);
+            let c2 = rb.acquire_read().await.unwrap();
+            assert_eq!((in_use("r1"), in_use("r2")), (1, 1));
+            drop(c1);
+            drop(c2);
+            let c3 = rb.primary().acquire_read().await.unwrap();
+            assert_eq!((in_use("r1"), in_use("r2")), (0, 0));
+            assert_eq!(rb.get_pool().unwrap().status().size, 1);
+            drop(c3);
+            let r: Vec<MockTable> = rb
+                .query_decode("select * from mock_table", vec![])
+                .await
+                .unwrap();
+            assert_eq!(r.len(), 1);
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_replica_least_connections() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            rb.set_replica_route(LeastConnectionsRoute::default());
+            rb.init(MockDriver {}, "test").unwrap();
+            rb.init_replica("r1", MockDriver {}, "test").unwrap();
+            rb.init_replica("r2", MockDriver {}, "test").unwrap();
+            let r1 = rb.get_replica("r1").unwrap();
+            let r2 = rb.get_replica("r2").unwrap();
+            let c1 = rb.acquire_read().await.unwrap();
+            let c2 = rb.acquire_read().await.unwrap();
+            assert_eq!(r1.status().size, 1);
+            assert_eq!(r2.status().size, 1);
+            drop(c1);
+            let _c3 = rb.acquire_read().await.unwrap();
+            assert_eq!(r1.status().available, 0);
+            assert_eq!(r2.status().available, 0);
+            drop(c2);
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_datasource() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            rb.init_named("analytics", MockDriver {}, "test").unwrap();
+            assert!(rb.init_named("analytics", MockDriver {}, "test").is_err());
+            assert!(rb.datasource("cache").is_err());
+            let mut analytics = rb.datasource("analytics").unwrap();
+            let r = MockTable::select_all(&mut analytics).await.unwrap();
+            assert_eq!(r.len(), 1);
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from mock_table ");
+            assert_eq!(analytics.get_pool().unwrap().status().size, 1);
+            assert_eq!(rb.get_pool().unwrap().status().size, 0);
+        };
+        block_on(f);
+    }
+
+    struct MockAsyncIntercept {
+        pub after: Arc<SegQueue<(String, Vec<Value>)>>,
+    }
+
+    impl Intercept for MockAsyncIntercept {
+        fn before<'a>(
+            &'a self,
+            _rb: &'a Rbatis,
+            _task_id: i64,
+            sql: &'a mut String,
+            _args: &'a mut Vec<Value>,
+            result: ResultType<'a>,
+        ) -> BoxFuture<'a, Result<bool, Error>> {
+            Box::pin(async move {
+                if sql.contains("cache_table") {
+                    match result {
+                        ResultType::Query(rows) => *rows = Ok(vec![to_value! {"id": "cached",}]),
+                        ResultType::Stream(len) => *len = Ok(0),
+                        _ => {}
+                    }
+                    return Ok(false);
+                }
+                Ok(true)
+            })
+        }
+
+        fn after<'a>(
+            &'a self,
+            _rb: &'a Rbatis,
+            _task_id: i64,
+            sql: &'a str,
+            args: &'a [Value],
+            result: ResultType<'a>,
+            _elapsed: Duration,
+        ) -> BoxFuture<'a, Result<(), Error>> {
+            Box::pin(async move {
+                self.after.push((sql.to_string(), args.to_vec()));
+                if sql.contains("after_error") {
+                    return Err(Error::from("after error"));
+                }
+                if let ResultType::Exec(Ok(v)) = result {
+                    v.rows_affected += 100;
+                }
+                Ok(())
+            })
+        }
+    }
+
+    #[test]
+    fn test_async_intercept() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_intercepts(vec![Box::new(MockAsyncIntercept {
+                after: queue.clone(),
+            })]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let r = rb
+                .exec("update mock_table set name = ?", vec![to_value!("a")])
+                .await
+                .unwrap();
+            assert_eq!(r.rows_affected, 100);
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "update mock_table set name = ?");
+            assert_eq!(args, vec![to_value!("a")]);
+
+            let mut tx = rb.acquire_begin().await.unwrap();
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "begin");
+            let r = tx.query("select * from cache_table", vec![]).await.unwrap();
+            assert_eq!(r, Value::Array(vec![to_value! {"id": "cached",}]));
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from cache_table");
+            let r: Vec<MockTable> = tx
+                .query_decode("select * from mock_table", vec![])
+                .await
+                .unwrap();
+            assert_eq!(r.len(), 1);
+            assert_eq!(queue.len(), 1);
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_async_intercept_stream() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_intercepts(vec![Box::new(MockAsyncIntercept {
+                after: queue.clone(),
+            })]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let r: Vec<MockTable> = rb
+                .query_stream_decode("select * from mock_table", vec![to_value!(1)])
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(r.len(), 1);
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from mock_table");
+            assert_eq!(args, vec![to_value!(1)]);
+
+            let mut tx = rb.acquire_begin().await.unwrap();
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "begin");
+            let r: Vec<Value> = tx
+                .query_stream_decode("select * from cache_table", vec![])
+                .try_collect()
+                .await
+                .unwrap();
+            assert!(r.is_empty());
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from cache_table");
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_async_intercept_after_error() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let logs = Arc::new(SegQueue::new());
+            rb.set_log_plugin(MockLogPlugin { logs: logs.clone() });
+            rb.set_intercepts(vec![Box::new(MockAsyncIntercept {
+                after: Arc::new(SegQueue::new()),
+            })]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let e = rb
+                .query("select * from after_error", vec![])
+                .await
+                .unwrap_err();
+            assert_eq!(e.to_string(), "after error");
+            let logs: Vec<String> = std::iter::from_fn(|| logs.pop()).collect();
+            assert!(logs.iter().any(|v| v.contains("query <= len=1,elapsed=")));
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_query_stream_drop_log() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let logs = Arc::new(SegQueue::new());
+            rb.set_log_plugin(MockLogPlugin { logs: logs.clone() });
+            rb.init(MockDriver {}, "test").unwrap();
+            let mut conn = rb.acquire().await.unwrap();
+            let mut stream = conn.query_stream("select * from mock_table", vec![]);
+            assert!(stream.try_next().await.unwrap().is_some());
+            drop(stream);
+            let logs: Vec<String> = std::iter::from_fn(|| logs.pop()).collect();
+            assert!(logs
+                .iter()
+                .any(|v| v.contains("query <= len=1,elapsed=") && v.ends_with("the stream is dropped")));
+        };
+        block_on(f);
+    }
+
+    struct MockLogPlugin {
+        pub logs: Arc<SegQueue<String>>,
+    }
+
+    impl LogPlugin for MockLogPlugin {
+        fn get_level_filter(&self) -> LevelFilter {
+            LevelFilter::Info
+        }
+
+        fn set_level_filter(&self, _level: LevelFilter) {}
+
+        fn get_level(&self, level: Level) -> Level {
+            level
+        }
+
+        fn set_level(&self, _from: Level, _to: Level) {}
+
+        fn do_log(&self, _level: LevelFilter, data: &str) {
+            self.logs.push(data.to_string());
+        }
+    }
+
+    #[test]
+    fn test_slow_query() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let logs = Arc::new(SegQueue::new());
+            rb.set_log_plugin(MockLogPlugin { logs: logs.clone() });
+            rb.set_intercepts(vec![Box::new(SlowQueryIntercept::new(Duration::ZERO))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            rb.query("select * from mock_table", vec![to_value!(1)])
+                .await
+                .unwrap();
+            let logs: Vec<String> = std::iter::from_fn(|| logs.pop()).collect();
+            assert!(logs.iter().any(|v| v.contains("query <= len=1,elapsed=")));
+            assert!(logs.iter().any(|v| v.contains("slow sql elapsed=")
+                && v.ends_with("=> `select * from mock_table` [1]")));
+
+            let mut rb = Rbatis::new();
+            let logs = Arc::new(SegQueue::new());
+            rb.set_log_plugin(MockLogPlugin { logs: logs.clone() });
+            rb.set_intercepts(vec![Box::new(SlowQueryIntercept::new(
+                Duration::from_secs(60),
+            ))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            rb.exec("update mock_table set name = 1", vec![]).await.unwrap();
+            let logs: Vec<String> = std::iter::from_fn(|| logs.pop()).collect();
+            assert!(logs.iter().any(|v| v.contains("exec  <= rows_affected=0,elapsed=")));
+            assert!(!logs.iter().any(|v| v.contains("slow sql")));
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_explain_sql() {
+        assert_eq!(
+            SlowQueryIntercept::explain_sql("postgres", "select 1"),
+            Some("explain select 1".to_string())
+        );
+        assert_eq!(
+            SlowQueryIntercept::explain_sql("sqlite", "select 1"),
+            Some("explain query plan select 1".to_string())
+        );
+        assert_eq!(SlowQueryIntercept::explain_sql("mssql", "select 1"), None);
+    }
+
+    #[test]
+    fn test_logic_delete() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![
+                Box::new(
+                    LogicDeleteIntercept::new("delete_flag", 1, 0)
+                        .tables(&["mock_table", "mock_order"]),
+                ),
+                Box::new(MockIntercept::new(queue.clone())),
+            ]);
+            rb.init(MockDriver {}, "test").unwrap();
+            MockTable::delete_by_column(&mut rb, "id", "1").await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "update mock_table set delete_flag = ? where id = ?");
+            assert_eq!(args, vec![to_value!(1i64), to_value!("1")]);
+
+            MockTable::delete_by_column_physical(&mut rb, "id", "1")
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "delete from mock_table where id = ?");
+            assert_eq!(args, vec![to_value!("1")]);
+
+            MockTable::delete_in_column_physical(&mut rb, "id", &["1", "2"])
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "delete from mock_table where id in (?,?)");
+            assert_eq!(args, vec![to_value!("1"), to_value!("2")]);
+
+            MockOrder::delete_by_id(&mut rb, ("1", "a")).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update mock_order set delete_flag = ? where (tenant_id = ? and order_no = ?)"
+            );
+            assert_eq!(args, vec![to_value!(1i64), to_value!("1"), to_value!("a")]);
+
+            MockOrder::delete_by_id_physical(&mut rb, ("1", "a"))
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "delete from mock_order where (tenant_id = ? and order_no = ?)"
+            );
+            assert_eq!(args, vec![to_value!("1"), to_value!("a")]);
+
+            MockTable::select_all(&mut rb).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from mock_table where delete_flag = ?");
+            assert_eq!(args, vec![to_value!(0i64)]);
+
+            MockTable::select_by_column(&mut rb, "id", "1").await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from mock_table  where delete_flag = ? and (id = ?)"
+            );
+            assert_eq!(args, vec![to_value!(0i64), to_value!("1")]);
+
+            rb.query(
+                "select * from mock_table where delete_flag = 1 order by id",
+                vec![],
+            )
+            .await
+            .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from mock_table where delete_flag = 1 order by id"
+            );
+            assert!(args.is_empty());
+
+            rb.query("select * from other_table limit 1", vec![])
+                .await
+                .unwrap();
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from other_table limit 1");
+
+            // the count of page with group by
+            rb.query(
+                "select count(1) as count from (select name from mock_table where status = ? group by name) as rb_count_t",
+                vec![to_value!(1)],
+            )
+            .await
+            .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select count(1) as count from (select name from mock_table where delete_flag = ? and (status = ?) group by name) as rb_count_t"
+            );
+            assert_eq!(args, vec![to_value!(0i64), to_value!(1)]);
+
+            let t = MockTable {
+                id: Some("2".into()),
+                name: Some("2".into()),
+                pc_link: None,
+                h5_link: None,
+                pc_banner_img: None,
+                h5_banner_img: None,
+                sort: None,
+                status: None,
+                remark: None,
+                create_time: None,
+                version: None,
+                delete_flag: None,
+                count: 0,
+            };
+            MockTable::update_by_column(&mut rb, &t, "id").await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update mock_table set name=?,count=? where delete_flag = ? and (id = ?)"
+            );
+            assert_eq!(
+                args,
+                vec![to_value!("2"), to_value!(0u64), to_value!(0i64), to_value!("2")]
+            );
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_block_attack_comment() {
+        let rb = Rbatis::new();
+        for sql in [
+            "/* physical */ delete from mock_table",
+            "-- all\n/* physical */ delete from mock_table",
+            "/* physical */ update mock_table set name = ?",
+        ] {
+            let mut sql = sql.to_string();
+            assert!(BlockAttackDeleteInterceptor {}
+                .do_intercept(&rb, &mut sql, &mut vec![], false)
+                .and_then(|_| BlockAttackUpdateInterceptor {}.do_intercept(
+                    &rb,
+                    &mut sql,
+                    &mut vec![],
+                    false
+                ))
+                .is_err());
+        }
+        let mut sql = "/* physical */ delete from mock_table where id = ?".to_string();
+        assert!(BlockAttackDeleteInterceptor {}
+            .do_intercept(&rb, &mut sql, &mut vec![], false)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_logic_delete_quote() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![
+                Box::new(LogicDeleteIntercept::new("delete_flag", 1, 0)),
+                Box::new(MockIntercept::new(queue.clone())),
+            ]);
+            rb.init(MockDialectDriver("postgres"), "test").unwrap();
+            MockTable::delete_by_column(&mut rb, "id", "1").await.unwrap();
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update \"mock_table\" set \"delete_flag\" = ? where \"id\" = ?"
+            );
+
+            MockTable::select_all(&mut rb).await.unwrap();
+            let (sql, _) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from \"mock_table\" where \"delete_flag\" = ?"
+            );
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_version_lock() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_intercepts(vec![
+                Box::new(MockAsyncIntercept {
+                    after: queue.clone(),
+                }),
+                Box::new(VersionLockIntercept::new("version")),
+            ]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let mut t = MockTable {
+                id: Some("2".into()),
+                name: Some("2".into()),
+                pc_link: None,
+                h5_link: None,
+                pc_banner_img: None,
+                h5_banner_img: None,
+                sort: None,
+                status: None,
+                remark: None,
+                create_time: None,
+                version: Some(1),
+                delete_flag: None,
+                count: 0,
+            };
+            MockTable::update_by_column_version(&mut rb, &mut t, "id")
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update mock_table set name=?,version = version + 1,count=? where (id = ?) and version = ?"
+            );
+            assert_eq!(
+                args,
+                vec![
+                    to_value!("2"),
+                    to_value!(0u64),
+                    to_value!("2"),
+                    to_value!(1i64)
+                ]
+            );
+            assert_eq!(t.version, Some(2));
+            // the new version is recorded by the intercept in the scope
+            let (r, version) =
+                VersionLockIntercept::scope(MockTable::update_by_column(&mut rb, &t, "id")).await;
+            r.unwrap();
+            assert_eq!(version, Some(to_value!(3i64)));
+            queue.pop().unwrap();
+
+            let mut rb = Rbatis::new();
+            rb.set_intercepts(vec![Box::new(VersionLockIntercept::new("version"))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let e = MockTable::update_by_column_version(&mut rb, &mut t, "id")
+                .await
+                .unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::ConcurrencyConflict);
+            assert_eq!(t.version, Some(2));
+            let (r, version) =
+                VersionLockIntercept::scope(MockTable::update_by_column(&mut rb, &t, "id")).await;
+            assert!(r.is_err());
+            assert_eq!(version, None);
+
+            t.version = None;
+            let r = MockTable::update_by_column(&mut rb, &t, "id").await.unwrap();
+            assert_eq!(r.rows_affected, 0);
+
+            // not bumped without the intercept, or the table is not locked
+            t.version = Some(2);
+            let mut rb = Rbatis::new();
+            rb.init(MockDriver {}, "test").unwrap();
+            MockTable::update_by_column_version(&mut rb, &mut t, "id")
+                .await
+                .unwrap();
+            assert_eq!(t.version, Some(2));
+            rb.set_intercepts(vec![Box::new(
+                VersionLockIntercept::new("version").tables(&["other_table"]),
+            )]);
+            MockTable::update_by_column_version(&mut rb, &mut t, "id")
+                .await
+                .unwrap();
+            assert_eq!(t.version, Some(2));
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_upsert_sql() {
+        let rows = vec![
+            to_value! {"id": "1", "name": "a", "count": 1,},
+            to_value! {"id": "2", "name": "b", "count": 2,},
+        ];
+        let (sql, args) = upsert_sql("postgres", "mock_table", &rows, &["id"]).unwrap();
+        assert_eq!(sql, "insert into mock_table (id,name,count) VALUES (?,?,?),(?,?,?) on conflict (id) do update set name = excluded.name,count = excluded.count");
+        assert_eq!(
+            args,
+            vec![
+                to_value!("1"),
+                to_value!("a"),
+                to_value!(1),
+                to_value!("2"),
+                to_value!("b"),
+                to_value!(2)
+            ]
+        );
+        let (sql, _) = upsert_sql("sqlite", "mock_table", &rows, &["id", "name"]).unwrap();
+        assert_eq!(sql, "insert into mock_table (id,name,count) VALUES (?,?,?),(?,?,?) on conflict (id,name) do update set count = excluded.count");
+        let (sql, _) = upsert_sql("mysql", "mock_table", &rows, &["id"]).unwrap();
+        assert_eq!(sql, "insert into mock_table (id,name,count) VALUES (?,?,?),(?,?,?) on duplicate key update name = values(name),count = values(count)");
+        let (sql, args) = upsert_sql("mssql", "mock_table", &rows, &["id"]).unwrap();
+        assert_eq!(sql, "merge into mock_table as t using (VALUES (?,?,?),(?,?,?)) as s (id,name,count) on t.id = s.id when matched then update set t.name = s.name,t.count = s.count when not matched then insert (id,name,count) VALUES (s.id,s.name,s.count);");
+        assert_eq!(args.len(), 6);
+
+        let rows = vec![to_value! {"id": "1",}];
+        let (sql, _) = upsert_sql("postgres", "mock_table", &rows, &["id"]).unwrap();
+        assert_eq!(
+            sql,
+            "insert into mock_table (id) VALUES (?) on conflict (id) do nothing"
+        );
+        let (sql, _) = upsert_sql("mysql", "mock_table", &rows, &["id"]).unwrap();
+        assert_eq!(
+            sql,
+            "insert into mock_table (id) VALUES (?) on duplicate key update id = id"
+        );
+
+        assert!(upsert_sql("postgres", "mock_table", &rows, &[]).is_err());
+        assert!(upsert_sql("postgres", "mock_table", &rows, &["name"]).is_err());
+        assert!(upsert_sql("test", "mock_table", &rows, &["id"]).is_err());
+    }
+
+    #[test]
+    fn test_upsert() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            rb.init(MockDriver {}, "test").unwrap();
+            let t = MockTable {
+                id: Some("2".into()),
+                name: Some("2".into()),
+                pc_link: None,
+                h5_link: None,
+                pc_banner_img: None,
+                h5_banner_img: None,
+                sort: None,
+                status: None,
+                remark: None,
+                create_time: None,
+                version: None,
+                delete_flag: None,
+                count: 0,
+            };
+            let e = MockTable::upsert(&mut rb, &t, &["id"]).await.unwrap_err();
+            assert_eq!(
+                e.to_string(),
+                "[rbatis] upsert not support driver type: test"
+            );
+        };
+        block_on(f);
+    }
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, rbatis::Table)]
+    #[table(name = "biz_activity")]
+    struct MockActivity {
+        #[column(pk, auto_increment)]
+        pub id: Option<i64>,
+        #[column(name = "activity_name")]
+        pub name: Option<String>,
+        #[column(insert_default)]
+        pub create_time: Option<String>,
+        #[column(skip)]
+        #[serde(default)]
+        pub remark: Option<String>,
+    }
+
+    crud!(MockActivity {});
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, rbatis::Table)]
+    #[serde(rename_all = "camelCase")]
+    #[table(name = "biz_camel")]
+    struct MockCamel {
+        #[column(pk)]
+        pub user_id: Option<i64>,
+        #[serde(rename = "NAME")]
+        pub user_name: Option<String>,
+        #[column(name = "create_time")]
+        pub create_time: Option<String>,
+    }
+
+    #[test]
+    fn test_derive_table() {
+        let meta = <MockActivity as rbatis::table::Table>::table_meta();
+        assert_eq!(meta.name, "biz_activity");
+        assert_eq!(meta.primary_keys(), vec!["id"]);
+        assert_eq!(meta.select_columns(), "id,activity_name as name,create_time");
+        assert_eq!(rbatis::table_meta!(MockActivity), Some(meta));
+        assert_eq!(rbatis::table_meta!(MockTable), None);
+        let meta = <MockCamel as rbatis::table::Table>::table_meta();
+        assert_eq!(meta.primary_keys(), vec!["userId"]);
+        assert_eq!(
+            meta.select_columns(),
+            "userId,NAME,create_time as createTime"
+        );
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            let t = MockActivity {
+                id: None,
+                name: Some("a".into()),
+                create_time: None,
+                remark: Some("r".into()),
+            };
+            MockActivity::insert(&mut rb, &t).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "insert into biz_activity (activity_name) VALUES (?)");
+            assert_eq!(args, vec![to_value!("a")]);
+
+            MockActivity::select_all(&mut rb).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql.trim(),
+                "select id,activity_name as name,create_time from biz_activity"
+            );
+
+            let t = MockActivity {
+                id: Some(1),
+                name: Some("b".into()),
+                create_time: None,
+                remark: Some("r".into()),
+            };
+            MockActivity::update_by_column(&mut rb, &t, "id").await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "update biz_activity set activity_name=? where id = ?");
+            assert_eq!(args, vec![to_value!("b"), to_value!(1i64)]);
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_upsert_table() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDialectDriver("postgres"), "test").unwrap();
+            let t = MockActivity {
+                id: None,
+                name: Some("a".into()),
+                create_time: Some("2023-01-01".into()),
+                remark: Some("r".into()),
+            };
+            MockActivity::upsert(&mut rb, &t, &["activity_name"])
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "insert into \"biz_activity\" (\"activity_name\",\"create_time\") VALUES (?,?) on conflict (\"activity_name\") do update set \"create_time\" = excluded.\"create_time\"");
+            assert_eq!(args, vec![to_value!("a"), to_value!("2023-01-01")]);
+        };
+        block_on(f);
+    }
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+    struct MockOrder {
+        pub tenant_id: Option<String>,
+        pub order_no: Option<String>,
+        pub amount: Option<i64>,
+    }
+
+    crud!(MockOrder {}, ["tenant_id", "order_no"]);
+
+    impl_by_id!(MockActivity {});
+
+    #[test]
+    fn test_by_id() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDriver {}, "test").unwrap();
+            MockOrder::select_by_id(&mut rb, ("1", "a")).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select * from mock_order where (tenant_id = ? and order_no = ?)"
+            );
+            assert_eq!(args, vec![to_value!("1"), to_value!("a")]);
+
+            MockOrder::select_by_ids(&mut rb, &[("1", "a"), ("1", "b")])
+                .await
+                .unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select * from mock_order where (tenant_id = ? and order_no = ?) or (tenant_id = ? and order_no = ?)");
+            assert_eq!(
+                args,
+                vec![to_value!("1"), to_value!("a"), to_value!("1"), to_value!("b")]
+            );
+
+            let t = MockOrder {
+                tenant_id: Some("1".into()),
+                order_no: Some("a".into()),
+                amount: Some(2),
+            };
+            MockOrder::update_by_id(&mut rb, &t).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "update mock_order set amount=? where (tenant_id = ? and order_no = ?)"
+            );
+            assert_eq!(args, vec![to_value!(2i64), to_value!("1"), to_value!("a")]);
+
+            let t = MockOrder { amount: None, ..t };
+            let e = MockOrder::update_by_id(&mut rb, &t).await.unwrap_err();
+            assert_eq!(
+                e.to_string(),
+                "[rbatis] update_by_id of `mock_order` has no column to set, the columns except the primary keys are all null"
+            );
+            assert!(queue.pop().is_none());
+
+            let mut id = rbs::value::map::ValueMap::new();
+            id.insert("order_no".into(), "a".into());
+            id.insert("tenant_id".into(), "1".into());
+            MockOrder::delete_by_id(&mut rb, Value::Map(id)).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "delete from mock_order where (tenant_id = ? and order_no = ?)"
+            );
+            assert_eq!(args, vec![to_value!("1"), to_value!("a")]);
+
+            let e = MockOrder::select_by_id(&mut rb, "1").await.unwrap_err();
+            assert_eq!(
+                e.to_string(),
+                r#"[rbatis] the id "1" is not match the primary keys ["tenant_id", "order_no"]"#
+            );
+
+            MockActivity::select_by_ids(&mut rb, &[1, 2]).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select id,activity_name as name,create_time from biz_activity where id in (?,?)"
+            );
+            assert_eq!(args, vec![to_value!(1), to_value!(2)]);
+        };
+        block_on(f);
+    }
+
+    /// the mock driver with the driver type, for example "postgres"
+    #[derive(Debug, Clone)]
+    struct MockDialectDriver(&'static str);
+
+    impl Driver for MockDialectDriver {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn connect(&self, url: &str) -> BoxFuture<'_, Result<Box<dyn Connection>, Error>> {
+            MockDriver {}.connect(url)
+        }
+
+        fn connect_opt<'a>(
+            &'a self,
+            opt: &'a dyn ConnectOptions,
+        ) -> BoxFuture<'a, Result<Box<dyn Connection>, Error>> {
+            MockDriver {}.connect_opt(opt)
+        }
+
+        fn default_option(&self) -> Box<dyn ConnectOptions> {
+            MockDriver {}.default_option()
+        }
+    }
+
+    #[test]
+    fn test_insert_batch_returning() {
+        assert_eq!(
+            Returning::new("mssql", "id").unwrap(),
+            Returning::Query {
+                output: "output inserted.id ".to_string(),
+                returning: "".to_string()
+            }
+        );
+        assert_eq!(
+            Returning::new("mysql", "id").unwrap(),
+            Returning::LastInsertId
+        );
+        assert_eq!(
+            consecutive_ids(&Value::U64(5), 3).unwrap(),
+            vec![Value::U64(5), Value::U64(6), Value::U64(7)]
+        );
+        assert!(consecutive_ids(&Value::Null, 3).is_err());
+        assert!(consecutive_ids(&Value::U64(0), 3).is_err());
+        assert_eq!(
+            generated_ids(&[Value::Null, Value::U64(9), Value::Null], &Value::U64(5)).unwrap(),
+            vec![Value::U64(5), Value::U64(9), Value::U64(6)]
+        );
+        assert_eq!(
+            generated_ids(&[Value::U64(9)], &Value::U64(0)).unwrap(),
+            vec![Value::U64(9)]
+        );
+        assert!(generated_ids(&[Value::Null], &Value::U64(0)).is_err());
+        let f = async move {
+            for (driver, sql) in [
+                ("postgres", "insert into \"biz_activity\" (\"activity_name\") VALUES (?),(?) returning \"id\""),
+                ("mssql", "insert into [biz_activity] ([activity_name]) output inserted.[id] VALUES (?),(?)"),
+            ] {
+                let mut rb = Rbatis::new();
+                let queue = Arc::new(SegQueue::new());
+                rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+                rb.init(MockDialectDriver(driver), "test").unwrap();
+                let t = MockActivity {
+                    id: None,
+                    name: Some("a".into()),
+                    create_time: None,
+                    remark: None,
+                };
+                let mut tables = vec![t.clone(), t];
+                //the mock row has no id
+                let ids = MockActivity::insert_batch_returning(&mut rb, &mut tables, 10)
+                    .await
+                    .unwrap();
+                assert_eq!(ids, vec![Value::Null]);
+                let (s, args) = queue.pop().unwrap();
+                assert_eq!(s.trim(), sql);
+                assert_eq!(args, vec![to_value!("a"), to_value!("a")]);
+            }
+            let mut rb = Rbatis::new();
+            rb.init(MockDialectDriver("mysql"), "test").unwrap();
+            let t = MockActivity {
+                id: None,
+                name: Some("a".into()),
+                create_time: None,
+                remark: None,
+            };
+            //the mock last_insert_id is null
+            assert!(MockActivity::insert_batch_returning(&mut rb, &mut [t.clone()], 10)
+                .await
+                .is_err());
+            //the batch of explicit and null keys
+            let mut tables = vec![
+                MockActivity {
+                    id: Some(1),
+                    ..t.clone()
+                },
+                t.clone(),
+            ];
+            let e = MockActivity::insert_batch_returning(&mut rb, &mut tables, 10)
+                .await
+                .unwrap_err();
+            assert!(e.to_string().contains("is null in some rows"));
+            assert!(MockActivity::insert_batch(&mut rb, &tables, 10)
+                .await
+                .is_err());
+            let mut tables = vec![MockActivity {
+                id: Some(1),
+                ..t
+            }];
+            let ids = MockActivity::insert_batch_returning(&mut rb, &mut tables, 10)
+                .await
+                .unwrap();
+            assert_eq!(ids, vec![to_value!(1i64)]);
+            assert_eq!(tables[0].id, Some(1));
+
+            let mut rb = Rbatis::new();
+            rb.init(MockDriver {}, "test").unwrap();
+            let e = MockActivity::insert_batch_returning(&mut rb, &mut [], 10)
+                .await
+                .unwrap_err();
+            assert_eq!(e.to_string(), "insert can not insert empty array tables!");
+        };
+        block_on(f);
+    }
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, rbatis::Table)]
+    #[table(name = "user")]
+    struct MockUser {
+        #[column(pk)]
+        pub id: Option<i64>,
+        #[column(name = "order")]
+        pub order_no: Option<i64>,
+    }
+
+    crud!(MockUser {});
+    impl_by_id!(MockUser {});
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, rbatis::Table)]
+    #[table(name = "user", no_quote)]
+    struct MockUserNoQuote {
+        #[column(pk)]
+        pub id: Option<i64>,
+        #[column(name = "order")]
+        pub order_no: Option<i64>,
+    }
+
+    crud!(MockUserNoQuote {});
+
+    #[html_sql(
+        r#"<insert id="insert_columns">`insert into ${table_name.quote()} (`<foreach collection="table" index="k" item="v" separator=",">${k.quote()}</foreach>`)`</insert>"#
+    )]
+    async fn insert_columns(
+        rb: &mut dyn rbatis::executor::Executor,
+        table_name: &str,
+        table: &Value,
+    ) -> Result<ExecResult, Error> {
+        impled!()
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        let f = async move {
+            let user = MockUser {
+                id: Some(1),
+                order_no: Some(2),
+            };
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDialectDriver("postgres"), "test").unwrap();
+            MockUser::insert(&mut rb, &user).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "insert into \"user\" (\"id\",\"order\") VALUES (?,?)");
+            assert_eq!(args, vec![to_value!(1i64), to_value!(2i64)]);
+
+            MockUser::select_by_column(&mut rb, "order", 2).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select \"id\",\"order\" as \"order_no\" from \"user\"  where \"order\" = ?"
+            );
+            assert_eq!(args, vec![to_value!(2)]);
+
+            MockUser::update_by_id(&mut rb, &user).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "update \"user\" set \"order\"=? where (\"id\" = ?)");
+            assert_eq!(args, vec![to_value!(2i64), to_value!(1i64)]);
+
+            insert_columns(&mut rb, "user", &to_value!(&user)).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "insert into \"user\" (\"id\",\"order_no\")");
+
+            MockUserNoQuote::select_by_column(&mut rb, "order", 2).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "select id,order as order_no from user  where order = ?");
+
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDialectDriver("mysql"), "test").unwrap();
+            MockUser::select_by_ids(&mut rb, &[1, 2]).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(
+                sql,
+                "select `id`,`order` as `order_no` from `user` where `id` in (?,?)"
+            );
+            assert_eq!(args, vec![to_value!(1), to_value!(2)]);
+
+            let mut rb = Rbatis::new();
+            let queue = Arc::new(SegQueue::new());
+            rb.set_sql_intercepts(vec![Box::new(MockIntercept::new(queue.clone()))]);
+            rb.init(MockDialectDriver("mssql"), "test").unwrap();
+            MockUser::delete_by_column(&mut rb, "t.order", 2).await.unwrap();
+            let (sql, args) = queue.pop().unwrap();
+            assert_eq!(sql, "delete from [user] where [t].[order] = ?");
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_reject_column() {
+        let f = async move {
+            let mut rb = Rbatis::new();
+            rb.init(MockDriver {}, "test").unwrap();
+            let e = MockUser::select_by_column(&mut rb, "id=1 or 1=1 --", 1)
+                .await
+                .unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::SqlRejected);
+            assert_eq!(
+                e.to_string(),
+                "[rbatis] the ${name.quote()} value \"id=1 or 1=1 --\" is not the table or column name"
+            );
+            let e = MockUser::delete_by_column(&mut rb, "id;drop table user", 1)
+                .await
+                .unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::SqlRejected);
+            MockUser::select_by_column(&mut rb, "\"order\"", 1)
+                .await
+                .unwrap();
+        };
+        block_on(f);
+    }
+
+    #[test]
+    fn test_version_lock_quote() {
+        let f = async move {
+            for (driver, expect) in [
+                ("postgres", "update \"mock_table\" set \"name\"=?,\"version\" = \"version\" + 1,\"count\"=? where (\"id\" = ?) and \"version\" = ?"),
+                ("mysql", "update `mock_table` set `name`=?,`version` = `version` + 1,`count`=? where (`id` = ?) and `version` = ?"),
+                ("mssql", "update [mock_table] set [name]=?,[version] = [version] + 1,[count]=? where ([id] = ?) and [version] = ?"),
+            ] {
+                let mut rb = Rbatis::new();
+                let queue = Arc::new(SegQueue::new());
+                rb.set_intercepts(vec![
+                    Box::new(MockAsyncIntercept {
+                        after: queue.clone(),
+                    }),
+                    Box::new(VersionLockIntercept::new("version")),
+                ]);
+                rb.init(MockDialectDriver(driver), "test").unwrap();
+                let t = MockTable {
+                    id: Some("2".into()),
+                    name: Some("2".into()),
+                    pc_link: None,
+                    h5_link: None,
+                    pc_banner_img: None,
+                    h5_banner_img: None,
+                    sort: None,
+                    status: None,
+                    remark: None,
+                    create_time: None,
+                    version: Some(1),
+                    delete_flag: None,
+                    count: 0,
+                };
+                MockTable::update_by_column(&mut rb, &t, "id").await.unwrap();
+                let (sql, args) = queue.pop().unwrap();
+                assert_eq!(sql, expect);
+                assert_eq!(
+                    args,
+                    vec![
+                        to_value!("2"),
+                        to_value!(0u64),
+                        to_value!("2"),
+                        to_value!(1i64)
+                    ]
+                );
+            }
+        };
+        block_on(f);
+    }
 }
@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod test {
+    use rbatis_codegen::codegen::interpreter::{eval_expr, eval_py_sql, Mapper};
+    use rbs::to_value;
+    use rbs::Value;
+
+    const HTML: &str = r#"
+       <!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "https://raw.githubusercontent.com/rbatis/rbatis/master/rbatis-codegen/mybatis-3-mapper.dtd">
+        <mapper>
+            <sql id="columns">`id,name`</sql>
+            <select id="select_by_condition">
+                `select `<include refid="columns"></include>` from biz_activity`
+                <where>
+                    <if test="name != ''">
+                        ` and name like #{name}`
+                    </if>
+                    <if test="ids != null && ids.len() > 0">
+                        ` and id in `
+                        <foreach collection="ids" item="item" index="index" open="(" close=")" separator=",">
+                            #{item}
+                        </foreach>
+                    </if>
+                    <choose>
+                        <when test="status == 1">
+                            ` and status = 1`
+                        </when>
+                        <otherwise>
+                            ` and status = 0`
+                        </otherwise>
+                    </choose>
+                </where>
+                <bind name="order" value="'create_time'"></bind>
+                ` order by ${order} desc`
+            </select>
+            <update id="update_by_id">
+                `update biz_activity`
+                <set>
+                    <if test="name != null">`name = #{name},`</if>
+                    <if test="status != null">`status = #{status},`</if>
+                </set>
+                `where id = #{id}`
+            </update>
+        </mapper>"#;
+
+    #[test]
+    fn test_mapper_render() {
+        let mapper = Mapper::from_html(HTML).unwrap();
+        assert_eq!(
+            mapper.ids(),
+            vec!["columns", "select_by_condition", "update_by_id"]
+        );
+        let (sql, args) = mapper
+            .render(
+                "select_by_condition",
+                &to_value! {
+                    "name": "a",
+                    "ids": vec![1, 2],
+                    "status": 1,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            sql,
+            "select id,name from biz_activity where name like ? and id in (?,?) and status = 1 order by create_time desc"
+        );
+        assert_eq!(args, vec![to_value!("a"), to_value!(1), to_value!(2)]);
+
+        let (sql, args) = mapper
+            .render("select_by_condition", &to_value! {"name": "",})
+            .unwrap();
+        assert_eq!(
+            sql,
+            "select id,name from biz_activity where status = 0 order by create_time desc"
+        );
+        assert!(args.is_empty());
+
+        let (sql, args) = mapper
+            .render("update_by_id", &to_value! {"id": 1, "status": 2,})
+            .unwrap();
+        assert_eq!(sql, "update biz_activity set status = ? where id = ?");
+        assert_eq!(args, vec![to_value!(2), to_value!(1)]);
+
+        assert_eq!(
+            mapper
+                .render("not_exist", &Value::Null)
+                .unwrap_err()
+                .to_string(),
+            "[rbatis] html not find id: not_exist"
+        );
+    }
+
+    #[test]
+    fn test_mapper_reload() {
+        let mut mapper = Mapper::from_html(HTML).unwrap();
+        mapper
+            .reload(
+                r#"<mapper><select id="select_all">`select * from biz_activity`</select></mapper>"#,
+            )
+            .unwrap();
+        assert_eq!(mapper.ids(), vec!["select_all"]);
+        assert!(mapper
+            .reload(r#"<mapper><select id="a"><include refid="b"></include></select></mapper>"#)
+            .is_err());
+        assert_eq!(mapper.ids(), vec!["select_all"]);
+    }
+
+    #[test]
+    fn test_eval_py_sql() {
+        let (sql, args) = eval_py_sql(
+            "`select * from biz_activity`
+  where:
+    if name != '':
+      ` and name = #{name}`
+    for k,v in map:
+      if v == null:
+        continue:
+      ` and ${k} = #{v}`
+  ` limit ${page_size}`",
+            &to_value! {
+                "name": "a",
+                "map": to_value!{"status": 1, "remark": Value::Null,},
+                "page_size": 10,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "select * from biz_activity where name = ? and status = ? limit 10"
+        );
+        assert_eq!(args, vec![to_value!("a"), to_value!(1)]);
+    }
+
+    #[test]
+    fn test_eval_quote() {
+        let py_sql = "`select * from ${table.quote()} where ${column.quote()} = 1`";
+        let (sql, _) = eval_py_sql(
+            py_sql,
+            &to_value! {"table": "user", "column": "order", "quote_driver": "postgres",},
+        )
+        .unwrap();
+        assert_eq!(sql, "select * from \"user\" where \"order\" = 1");
+        let (sql, _) =
+            eval_py_sql(py_sql, &to_value! {"table": "user", "column": "order",}).unwrap();
+        assert_eq!(sql, "select * from user where order = 1");
+    }
+
+    #[test]
+    fn test_eval_expr() {
+        let arg = to_value! {"a": 1, "b": "x", "c": to_value!{"d": [1, 2],},};
+        assert_eq!(eval_expr("a + 1 == 2", &arg).unwrap(), Value::Bool(true));
+        assert_eq!(
+            eval_expr("b == 'x' && a >= 1", &arg).unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(eval_expr("!(a < 1) || b", &arg).unwrap(), Value::Bool(true));
+        assert_eq!(eval_expr("c.d[1]", &arg).unwrap(), to_value!(2));
+        assert_eq!(
+            eval_expr("b.contains('x')", &arg).unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(eval_expr("e == null", &arg).unwrap(), Value::Bool(true));
+        assert!(eval_expr("a << 1", &arg).is_err());
+        assert!(eval_expr("a.unknown()", &arg).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod test {
-    use rbatis_codegen::codegen::parser_html::{load_mapper_map, parse_html};
+    use rbatis_codegen::codegen::parser_html::{check_html, load_mapper_map, parse_html};
 
     #[test]
     fn test_parse_line_feed() {
@@ -80,4 +80,73 @@ mod test {
             "`and name != ''`"
         );
     }
+
+    #[test]
+    fn test_check_html_error() {
+        let err = check_html(
+            r#"<mapper>
+    <select id="select_by_ids">
+        `select * from biz_activity where id in `
+        <foreach item="item" open="(" close=")" separator=",">#{item}</foreach>
+    </select>
+</mapper>"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert_eq!(
+            err,
+            r#"[rbatis] html line 4, column 9, `<foreach close=")" item="item" open="(" separator=",">`: <foreach> element must have attr `collection`
+  hint: for example <foreach collection="ids" item="item" index="index" open="(" close=")" separator=",">"#
+        );
+        let err = check_html(
+            r#"<select id="select">
+    `select * from biz_activity`
+    <!-- <if> -->
+    <where><iff test="id != null">`id = #{id}`</iff></where>
+</select>"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.starts_with(
+            r#"[rbatis] html line 4, column 12, `<iff test="id != null">`: unknown tag <iff>"#
+        ));
+        let err = check_html(r#"<select id="select"><if test="id !=">`id = #{id}`</if></select>"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with(
+            r#"[rbatis] html line 1, column 21, `<if test="id !=">`: parse the `test` expression `id !=` fail"#
+        ));
+        assert!(check_html(
+            r#"<select id="select"><when test="id != null">`id = #{id}`</when></select>"#
+        )
+        .is_err());
+        let err = check_html(r#"<select id="select"><if test="a << 1">`id = 1`</if></select>"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with(
+            r#"[rbatis] html line 1, column 21, `<if test="a << 1">`: parse the `test` expression `a << 1` fail: unsupported operator `<<`"#
+        ));
+        let err = check_html(
+            r#"<select id="select">
+    `select * from biz_activity`
+    <where>`id = #{a += 1}`</where>
+</select>"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.starts_with(
+            r#"[rbatis] html line 3, column 5, `<where>`: parse the expression `#{a += 1}` fail: unsupported operator `+=`"#
+        ));
+        assert!(check_html(r#"<select id="select">`id = ${name.quote() +}`</select>"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_html_error() {
+        let token = parse_html(
+            r#"<select id="select">`select * from t where id = #{a << 1}`</select>"#,
+            "select",
+            &mut vec![],
+        );
+        assert!(token.to_string().contains("compile_error"));
+    }
 }
@@ -67,4 +67,35 @@ mod test {
             })]
         );
     }
+
+    #[test]
+    fn test_parse_error_line() {
+        let err = NodeType::parse_pysql(
+            "select * from biz_activity
+if name != null:
+  `and name = #{name}`
+  for item ids:
+    #{item}",
+        )
+        .unwrap_err()
+        .to_string();
+        assert_eq!(
+            err,
+            "[rbatis] py_sql line 4, column 3, `for item ids:`: parser for express fail: for item ids:\n  hint: for example `for item in ids:` or `for index,item in ids:`"
+        );
+        let err = NodeType::parse_pysql(
+            "select * from biz_activity
+if name != null:
+and name = #{name}",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.starts_with(
+            "[rbatis] py_sql line 2, column 1, `if name != null:`: the node not have child lines"
+        ));
+        let err = NodeType::parse_pysql("select * from biz_activity\n  limit 1:\n    1=1")
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("[rbatis] py_sql line 2, column 3, `limit 1:`: unknow tag"));
+    }
 }
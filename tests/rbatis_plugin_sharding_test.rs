@@ -0,0 +1,292 @@
+#[cfg(test)]
+mod test {
+    use rbatis::intercept::SqlIntercept;
+    use rbatis::sharding::{DateRangeShard, HashShard, ModShard, ShardStrategy, ShardingIntercept};
+    use rbatis::Rbatis;
+    use rbs::{to_value, Value};
+
+    fn intercept(sql: &str, args: Vec<Value>) -> Result<(String, Vec<Value>), rbatis::Error> {
+        let rb = Rbatis::new();
+        let sharding = ShardingIntercept::new()
+            .table("biz_order", "user_id", ModShard::new(4))
+            .table(
+                "biz_log",
+                "create_time",
+                DateRangeShard::new()
+                    .range("2022-01-01", "2022")
+                    .range("2023-01-01", "2023"),
+            );
+        let mut sql = sql.to_string();
+        let mut args = args;
+        sharding.do_intercept(&rb, &mut sql, &mut args, true)?;
+        Ok((sql, args))
+    }
+
+    #[test]
+    fn test_strategy() {
+        assert_eq!(ModShard::new(4).shard(&to_value!(5)).unwrap(), "1");
+        assert_eq!(ModShard::new(4).shard(&to_value!("6")).unwrap(), "2");
+        assert!(ModShard::new(4).shard(&to_value!("a")).is_err());
+        let hash = HashShard::new(8);
+        assert_eq!(
+            hash.shard(&to_value!("abc")).unwrap(),
+            hash.shard(&to_value!("abc")).unwrap()
+        );
+        let date = DateRangeShard::new()
+            .range("2023-01-01", "2023")
+            .range("2022-01-01", "2022");
+        assert_eq!(date.shards(), vec!["2022", "2023"]);
+        assert_eq!(
+            date.shard(&to_value!("2022-12-31 23:59:59")).unwrap(),
+            "2022"
+        );
+        assert_eq!(
+            date.shard(&to_value!("2023-01-01 00:00:00")).unwrap(),
+            "2023"
+        );
+        assert!(date.shard(&to_value!("2021-01-01")).is_err());
+    }
+
+    #[test]
+    fn test_insert() {
+        let (sql, args) = intercept(
+            "insert into biz_order (id,user_id,name) VALUES (?,?,?),(?,?,?)",
+            vec![
+                to_value!("1"),
+                to_value!(5),
+                to_value!("a"),
+                to_value!("2"),
+                to_value!(9),
+                to_value!("b"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "insert into biz_order_1 (id,user_id,name) VALUES (?,?,?),(?,?,?)"
+        );
+        assert_eq!(args.len(), 6);
+        let r = intercept(
+            "insert into biz_order (id,user_id,name) VALUES (?,?,?),(?,?,?)",
+            vec![
+                to_value!("1"),
+                to_value!(5),
+                to_value!("a"),
+                to_value!("2"),
+                to_value!(6),
+                to_value!("b"),
+            ],
+        );
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn test_insert_split() {
+        let rb = Rbatis::new();
+        let sharding = ShardingIntercept::new().table("biz_order", "user_id", ModShard::new(4));
+        let parts = sharding
+            .split(
+                &rb,
+                "insert into biz_order (id,user_id,name) VALUES (?,?,'a,(?)'),(?,?,?),(?,?,?)",
+                &[
+                    to_value!("1"),
+                    to_value!(5),
+                    to_value!("2"),
+                    to_value!(6),
+                    to_value!("b"),
+                    to_value!("3"),
+                    to_value!(9),
+                    to_value!("c"),
+                ],
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            parts,
+            vec![
+                (
+                    "insert into biz_order (id,user_id,name) VALUES (?,?,'a,(?)'),(?,?,?)"
+                        .to_string(),
+                    vec![
+                        to_value!("1"),
+                        to_value!(5),
+                        to_value!("3"),
+                        to_value!(9),
+                        to_value!("c"),
+                    ]
+                ),
+                (
+                    "insert into biz_order (id,user_id,name) VALUES (?,?,?)".to_string(),
+                    vec![to_value!("2"), to_value!(6), to_value!("b")]
+                ),
+            ]
+        );
+        // one shard is not split
+        assert!(sharding
+            .split(
+                &rb,
+                "insert into biz_order (id,user_id) VALUES (?,?),(?,?)",
+                &[to_value!("1"), to_value!(5), to_value!("2"), to_value!(9)],
+            )
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_update_delete() {
+        let (sql, _) = intercept(
+            "update biz_order set name=?,user_id=? where user_id = ?",
+            vec![to_value!("a"), to_value!(1), to_value!(2)],
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "update biz_order_2 set name=?,user_id=? where user_id = ?"
+        );
+        let (sql, _) = intercept(
+            "delete from `biz_log` where create_time = ?",
+            vec![to_value!("2022-05-01")],
+        )
+        .unwrap();
+        assert_eq!(sql, "delete from `biz_log_2022` where create_time = ?");
+        assert!(intercept("delete from biz_order where id = ?", vec![to_value!("1")]).is_err());
+    }
+
+    #[test]
+    fn test_select_fan_out() {
+        let (sql, args) = intercept(
+            "select * from biz_order  where user_id in (?,?) and status = ?",
+            vec![to_value!(1), to_value!(5), to_value!(1)],
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "select * from biz_order_1  where user_id in (?,?) and status = ?"
+        );
+        assert_eq!(args.len(), 3);
+
+        let (sql, args) = intercept(
+            "select * from biz_log  where status = ? order by id desc limit 0,10",
+            vec![to_value!(1)],
+        )
+        .unwrap();
+        assert_eq!(sql, "select * from (select * from biz_log_2022  where status = ? union all select * from biz_log_2023  where status = ?) as shard_t order by id desc limit 0,10");
+        assert_eq!(args, vec![to_value!(1), to_value!(1)]);
+
+        let (sql, args) = intercept(
+            "select count(1) as count from biz_log  where status = ?",
+            vec![to_value!(1)],
+        )
+        .unwrap();
+        assert_eq!(sql, "select count(1) as count from (select 1 from biz_log_2022  where status = ? union all select 1 from biz_log_2023  where status = ?) as shard_t");
+        assert_eq!(args.len(), 2);
+
+        // the count of the page with group by
+        let (sql, args) = intercept(
+            "select count(1) as count from (select status from biz_log where status = ? group by status) as rb_count_t",
+            vec![to_value!(1)],
+        )
+        .unwrap();
+        assert_eq!(sql, "select count(1) as count from (select status from biz_log_2022 where status = ? group by status union all select status from biz_log_2023 where status = ? group by status) as rb_count_t");
+        assert_eq!(args, vec![to_value!(1), to_value!(1)]);
+
+        let (sql, _) = intercept("select * from biz_log ", vec![]).unwrap();
+        assert_eq!(
+            sql,
+            "select * from biz_log_2022  union all select * from biz_log_2023 "
+        );
+
+        let (sql, _) = intercept("select * from biz_activity", vec![]).unwrap();
+        assert_eq!(sql, "select * from biz_activity");
+    }
+
+    #[test]
+    fn test_where_and_chain() {
+        let (sql, _) = intercept(
+            "select * from biz_order where delete_flag = ? and (user_id = ?) and status = ?",
+            vec![to_value!(0), to_value!(5), to_value!(1)],
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "select * from biz_order_1 where delete_flag = ? and (user_id = ?) and status = ?"
+        );
+        let (sql, _) = intercept(
+            "select * from biz_order o where o.user_id = ? order by id",
+            vec![to_value!(6)],
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "select * from biz_order_2 o where o.user_id = ? order by id"
+        );
+
+        // `or` and not the and chain run on all shard tables
+        let (sql, args) = intercept(
+            "select * from biz_order where user_id = ? or status = ?",
+            vec![to_value!(5), to_value!(1)],
+        )
+        .unwrap();
+        assert!(sql.contains("biz_order_0 ") && sql.contains("biz_order_3 "));
+        assert_eq!(args.len(), 8);
+        for sql in [
+            "select * from biz_order where not (user_id = ?)",
+            "select * from biz_order where id in (select id from biz_user where user_id = ?)",
+            "select * from biz_order where user_id = ? + 1",
+        ] {
+            let (sql, _) = intercept(sql, vec![to_value!(5)]).unwrap();
+            assert!(sql.contains(" union all "), "{}", sql);
+        }
+        assert!(intercept(
+            "delete from biz_order where user_id = ? or id = ?",
+            vec![to_value!(5), to_value!("1")]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_literal() {
+        // the `?` and keyword in the string literal is not a arg
+        let (sql, args) = intercept(
+            "select * from biz_order where name = 'a?b where or' and user_id = ?",
+            vec![to_value!(5)],
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "select * from biz_order_1 where name = 'a?b where or' and user_id = ?"
+        );
+        assert_eq!(args, vec![to_value!(5)]);
+        let (sql, args) = intercept(
+            "select * from biz_log where name = '?' and status = ? order by id limit ?",
+            vec![to_value!(1), to_value!(10)],
+        )
+        .unwrap();
+        assert_eq!(sql, "select * from (select * from biz_log_2022 where name = '?' and status = ? union all select * from biz_log_2023 where name = '?' and status = ?) as shard_t order by id limit ?");
+        assert_eq!(args, vec![to_value!(1), to_value!(1), to_value!(10)]);
+    }
+
+    #[test]
+    fn test_join() {
+        let (sql, _) = intercept(
+            "select * from biz_order o join biz_log l on o.id = l.order_id where o.user_id = ? and l.create_time = ?",
+            vec![to_value!(5), to_value!("2023-02-01")],
+        )
+        .unwrap();
+        assert_eq!(sql, "select * from biz_order_1 o join biz_log_2023 l on o.id = l.order_id where o.user_id = ? and l.create_time = ?");
+
+        let (sql, args) = intercept(
+            "select * from biz_order o join biz_log l on o.id = l.order_id where l.create_time = ?",
+            vec![to_value!("2023-02-01")],
+        )
+        .unwrap();
+        assert_eq!(sql, "select * from biz_order_0 o join biz_log_2023 l on o.id = l.order_id where l.create_time = ? union all select * from biz_order_1 o join biz_log_2023 l on o.id = l.order_id where l.create_time = ? union all select * from biz_order_2 o join biz_log_2023 l on o.id = l.order_id where l.create_time = ? union all select * from biz_order_3 o join biz_log_2023 l on o.id = l.order_id where l.create_time = ?");
+        assert_eq!(args.len(), 4);
+
+        assert!(intercept(
+            "select * from biz_order o join biz_log l on o.id = l.order_id",
+            vec![]
+        )
+        .is_err());
+    }
+}
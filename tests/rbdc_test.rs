@@ -1,7 +1,8 @@
 #[cfg(test)]
 mod test {
     use rbdc::db::Placeholder;
-    use rbdc::impl_exchange;
+    use rbdc::deadpool::managed::{PoolError, TimeoutType};
+    use rbdc::{impl_exchange, Error, ErrorKind};
 
     #[test]
     fn test_exchange() {
@@ -16,4 +17,29 @@ mod test {
         let sql = d.exchange(v);
         assert_eq!("insert into biz_activity (id,name,pc_link,h5_link,pc_banner_img,h5_banner_img,sort,status,remark,create_time,version,delete_flag) VALUES (@P1,@P2,@P3,@P4,@P5,@P6,@P7,@P8,@P9,@P10,@P11,@P12)", sql);
     }
+
+    #[test]
+    fn test_error() {
+        let e = Error::new(ErrorKind::UniqueViolation, "duplicate key").with_code("2067");
+        assert_eq!(e.to_string(), "duplicate key (code: 2067)");
+        let e = Error::new(
+            ErrorKind::UniqueViolation,
+            "db: duplicate key (SQLSTATE 23505)",
+        )
+        .with_code("23505");
+        assert_eq!(e.to_string(), "db: duplicate key (SQLSTATE 23505)");
+
+        let e = <Error as serde::ser::Error>::custom("fail");
+        assert_eq!(e.kind(), ErrorKind::Encode);
+
+        let e = Error::from(PoolError::Backend(
+            Error::new(ErrorKind::Io, "io").with_code("1"),
+        ));
+        assert_eq!(e.kind(), ErrorKind::Io);
+        assert_eq!(e.code(), Some("1"));
+        let e = Error::from(PoolError::Backend("fail".to_string()));
+        assert_eq!(e.to_string(), r#"Backend("fail")"#);
+        let e = Error::from(PoolError::<Error>::Timeout(TimeoutType::Wait));
+        assert_eq!(e.kind(), ErrorKind::PoolTimeout);
+    }
 }
@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod test {
+    use rbatis_codegen::codegen::schema::Schema;
+
+    const SCHEMA: &str = r#"
+    {
+        "biz_activity": {"id": "varchar(50)", "name": "varchar(255)", "status": "int", "create_time": "datetime"},
+        "biz_order": {"id": "varchar(50)", "activity_id": "varchar(50)", "amount": "decimal"}
+    }"#;
+
+    fn schema() -> Schema {
+        Schema::from_json(SCHEMA).unwrap()
+    }
+
+    #[test]
+    fn test_check_table() {
+        let schema = schema();
+        schema.check_table("biz_activity", &["id", "NAME"]).unwrap();
+        schema.check_table("public.biz_activity", &[]).unwrap();
+        assert_eq!(
+            schema
+                .check_table("biz_activity", &["nam"])
+                .unwrap_err()
+                .to_string(),
+            "[rbatis] column `nam` not in table `biz_activity` of schema"
+        );
+        assert_eq!(
+            schema
+                .check_table("biz_activty", &[])
+                .unwrap_err()
+                .to_string(),
+            "[rbatis] table `biz_activty` not in schema"
+        );
+    }
+
+    #[test]
+    fn test_check_sql() {
+        let schema = schema();
+        schema
+            .check_sql("select a.id,a.name as n,count(1) as total from biz_activity a where a.status = 1 and name like '%a%' order by total desc")
+            .unwrap();
+        schema
+            .check_sql("select o.amount,a.name from biz_order o left join biz_activity as a on o.activity_id = a.id where o.id = ?")
+            .unwrap();
+        schema
+            .check_sql("insert into biz_activity (id,name) values (?,?)")
+            .unwrap();
+        schema
+            .check_sql("update biz_activity set name = #{name} where id = #{id}")
+            .unwrap();
+        // the columns of dynamic table or sub query are not checked
+        schema
+            .check_sql("select x from ${table} where y = 1")
+            .unwrap();
+        schema
+            .check_sql("select t.x from (select id as x from biz_activity) t")
+            .unwrap();
+        // the `from` of function is not the table
+        schema
+            .check_sql("select extract(year from create_time) as y,substring(name from 1 for 2) from biz_activity where trim(both ' ' from name) = ?")
+            .unwrap();
+        schema
+            .check_sql("select * from biz_activity where create_time < current_timestamp and name = current_user order by create_time desc nulls last")
+            .unwrap();
+        schema
+            .check_sql("select cast(status as int) from biz_activity where id in (select activity_id from biz_order)")
+            .unwrap();
+        assert_eq!(
+            schema
+                .check_sql("select extract(year from create_time) from biz_activty")
+                .unwrap_err()
+                .to_string(),
+            "[rbatis] table `biz_activty` not in schema"
+        );
+        assert_eq!(
+            schema
+                .check_sql("select a.nam from biz_activity a")
+                .unwrap_err()
+                .to_string(),
+            "[rbatis] column `nam` not in table `biz_activity` of schema"
+        );
+        assert_eq!(
+            schema
+                .check_sql("select * from biz_activity where statu = 1")
+                .unwrap_err()
+                .to_string(),
+            "[rbatis] column `statu` not in table(s) {\"biz_activity\"} of schema"
+        );
+        assert_eq!(
+            schema
+                .check_sql("delete from biz_activty where id = 1")
+                .unwrap_err()
+                .to_string(),
+            "[rbatis] table `biz_activty` not in schema"
+        );
+    }
+
+    #[test]
+    fn test_check_py_sql_html() {
+        let schema = schema();
+        schema
+            .check_py_sql(
+                "select * from biz_activity where status = 1
+                  if name != '':
+                    ` and name like #{name}`
+                  for item in ids:
+                    ` and id = #{item}`",
+            )
+            .unwrap();
+        assert!(schema
+            .check_py_sql(
+                "select * from biz_activity
+                  if name != '':
+                    ` where names = #{name}`",
+            )
+            .is_err());
+        schema
+            .check_html(
+                r#"<select id="select_by_id">`select * from biz_activity`<where><if test="id != null">` and id = #{id}`</if></where></select>"#,
+            )
+            .unwrap();
+        assert!(schema
+            .check_html(
+                r#"<select id="select_by_id">`select * from biz_activity`<where><if test="id &lt; 1">` and ids = #{id}`</if></where></select>"#,
+            )
+            .is_err());
+    }
+}
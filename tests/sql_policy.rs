@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod test {
+    use rbatis_codegen::codegen::interpreter::eval_py_sql;
+    use rbatis_codegen::sql_policy::{
+        is_dangerous, is_identifier, set_sql_policy, to_sql, SqlPolicy,
+    };
+    use rbatis_macro_driver::rb_py;
+    use rbs::to_value;
+    use rbs::Value;
+
+    // the fn declare the return type, so the value rejected by the sql policy is returned as Err
+    #[rb_py("select * from t order by ${column} ${order.raw()}")]
+    pub fn py_select_order_by(
+        arg: &rbs::Value,
+        _tag: char,
+    ) -> Result<(String, Vec<Value>), rbatis_codegen::error::Error> {
+    }
+
+    #[rb_py("select * from t order by ${column} ${order.raw()}")]
+    pub fn py_select_order_by_sql(arg: &rbs::Value, _tag: char) {}
+
+    #[test]
+    fn test_is_identifier() {
+        assert!(is_identifier("name"));
+        assert!(is_identifier("t.create_time"));
+        assert!(is_identifier("\"name\""));
+        assert!(is_identifier("`name`"));
+        assert!(is_identifier("[name]"));
+        assert!(!is_identifier(""));
+        assert!(!is_identifier("1name"));
+        assert!(!is_identifier("id,name"));
+        assert!(!is_identifier("id desc"));
+        assert!(!is_identifier("\"na\"me\""));
+        assert!(!is_identifier("id;drop table t"));
+        assert!(!is_dangerous("id desc"));
+        assert!(is_dangerous("1 or '1'='1'"));
+        assert!(is_dangerous("id -- comment"));
+        assert!(is_dangerous("id /* comment */"));
+        assert!(is_dangerous("id;drop table t"));
+    }
+
+    // the policy is global, so it's tested in one test
+    #[test]
+    fn test_sql_policy() {
+        let arg = to_value! {
+            "column": "id;drop table t",
+            "order": "desc",
+        };
+        let py_sql = "select * from t order by ${column} ${order.raw()}";
+
+        set_sql_policy(SqlPolicy::Raw);
+        let (sql, _) = py_select_order_by(&arg, '?').unwrap();
+        assert_eq!(sql, "select * from t order by id;drop table t desc");
+        let (sql, _) = eval_py_sql(py_sql, &arg).unwrap();
+        assert_eq!(sql, "select * from t order by id;drop table t desc");
+
+        for policy in [SqlPolicy::Reject, SqlPolicy::Identifier] {
+            set_sql_policy(policy);
+            assert_eq!(
+                py_select_order_by(&arg, '?')
+                    .unwrap_err()
+                    .to_string(),
+                format!("[rbatis] the ${{}} value `id;drop table t` is rejected by the sql policy {:?}, use ${{expr.raw()}} if it's trusted", policy)
+            );
+            assert!(eval_py_sql(py_sql, &arg).is_err());
+            let (sql, _) = py_select_order_by_sql(&arg, '?');
+            assert!(sql.is_empty());
+
+            let arg = to_value! {
+                "column": "t.name",
+                "order": "desc",
+            };
+            let (sql, _) = py_select_order_by(&arg, '?').unwrap();
+            assert_eq!(sql, "select * from t order by t.name desc");
+            let (sql, _) = eval_py_sql(py_sql, &arg).unwrap();
+            assert_eq!(sql, "select * from t order by t.name desc");
+        }
+
+        set_sql_policy(SqlPolicy::Reject);
+        assert_eq!(to_sql(Value::from("id desc")).unwrap(), "id desc");
+        assert_eq!(to_sql(Value::I32(1)).unwrap(), "1");
+        set_sql_policy(SqlPolicy::Identifier);
+        assert!(to_sql(Value::from("id desc")).is_err());
+        assert_eq!(to_sql(Value::I32(1)).unwrap(), "1");
+        assert!(to_sql(Value::Array(vec![])).is_err());
+        set_sql_policy(SqlPolicy::Raw);
+    }
+}